@@ -94,6 +94,17 @@ impl Alphabet {
             Ok(self)
         }
     }
+
+    /// The characters of the alphabet and of its extension, each sorted, so the order does not depend on hashing.
+    pub(crate) fn sorted_characters(&self) -> (Vec<char>, Vec<char>) {
+        let sorted = |set: &HashSet<char>| {
+            let mut characters: Vec<_> = set.iter().copied().collect();
+            characters.sort_unstable();
+            characters
+        };
+
+        (sorted(&self.main), sorted(&self.extension))
+    }
 }
 
 impl FromStr for Alphabet {
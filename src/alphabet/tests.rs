@@ -26,7 +26,7 @@ use super::*;
 
 #[test]
 fn alphabet_can_be_created_from_a_hash_set() {
-    let set: HashSet<_> = ('a'..='z').into_iter().collect();
+    let set: HashSet<_> = ('a'..='z').collect();
 
     let creation_result = Alphabet::try_from(&set);
 
@@ -143,12 +143,11 @@ fn alphabet_does_not_contain_characters_missing_from_its_definition() {
     let alphabet = Alphabet::from_str(definition).unwrap();
 
     for character in ('d'..='z')
-        .into_iter()
-        .chain(('A'..='Z').into_iter())
-        .chain(('0'..='9').into_iter())
-        .chain(('а'..='я').into_iter())
-        .chain(('ぁ'..='ゖ').into_iter())
-        .chain(('ؠ'..='ۓ').into_iter())
+        .chain('A'..='Z')
+        .chain('0'..='9')
+        .chain('а'..='я')
+        .chain('ぁ'..='ゖ')
+        .chain('ؠ'..='ۓ')
     {
         assert!(!alphabet.contains(character))
     }
@@ -225,12 +224,11 @@ fn extended_alphabet_does_not_contain_characters_missing_from_its_definition() {
     let alphabet = Alphabet::from_str(definition).unwrap().extend('d').unwrap();
 
     for character in ('d'..='z')
-        .into_iter()
-        .chain(('A'..='Z').into_iter())
-        .chain(('0'..='9').into_iter())
-        .chain(('а'..='я').into_iter())
-        .chain(('ぁ'..='ゖ').into_iter())
-        .chain(('ؠ'..='ۓ').into_iter())
+        .chain('A'..='Z')
+        .chain('0'..='9')
+        .chain('а'..='я')
+        .chain('ぁ'..='ゖ')
+        .chain('ؠ'..='ۓ')
     {
         assert!(!alphabet.contains(character))
     }
@@ -243,12 +241,11 @@ fn extended_alphabet_does_not_contain_characters_missing_from_either_its_definit
     let alphabet = Alphabet::from_str(definition).unwrap().extend('d').unwrap();
 
     for character in ('e'..='z')
-        .into_iter()
-        .chain(('A'..='Z').into_iter())
-        .chain(('0'..='9').into_iter())
-        .chain(('а'..='я').into_iter())
-        .chain(('ぁ'..='ゖ').into_iter())
-        .chain(('ؠ'..='ۓ').into_iter())
+        .chain('A'..='Z')
+        .chain('0'..='9')
+        .chain('а'..='я')
+        .chain('ぁ'..='ゖ')
+        .chain('ؠ'..='ۓ')
     {
         assert!(!alphabet.contains_extended(character))
     }
@@ -256,7 +253,7 @@ fn extended_alphabet_does_not_contain_characters_missing_from_either_its_definit
 
 #[test]
 fn alphabet_can_be_cloned() {
-    let set: HashSet<_> = ('a'..='z').into_iter().collect();
+    let set: HashSet<_> = ('a'..='z').collect();
 
     let alphabet = Alphabet::try_from(&set).unwrap();
 
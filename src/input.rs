@@ -25,12 +25,23 @@ use std::{
 
 use anyhow::{Context, Ok, Result};
 
+/// A command entered by the user in the interactive mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserCommand {
+    /// Do the next step.
+    Continue,
+    /// Revert the last step.
+    StepBack,
+    /// Stop the application.
+    Stop,
+}
+
 /// A quick-and-dirty helper to handle user input.
 /// It reads lines from console in a separate thread and also handles Ctrl-C.
 pub struct UserInputHandler {
-    receiver: Receiver<bool>,
-    sender: Sender<bool>,
-    should_continue: bool,
+    receiver: Receiver<UserCommand>,
+    sender: Sender<UserCommand>,
+    is_stopped: bool,
     thread_handle: Option<JoinHandle<()>>,
 }
 
@@ -42,7 +53,7 @@ impl UserInputHandler {
         let handler_sender = signal_sender.clone();
         ctrlc::set_handler(move || {
             handler_sender
-                .send(false)
+                .send(UserCommand::Stop)
                 .expect("Could not send signal on the channel.")
         })
         .with_context(|| "Failed to setup the ctrl-C handler")?;
@@ -50,33 +61,47 @@ impl UserInputHandler {
         Ok(Self {
             receiver: signal_receiver,
             sender: signal_sender,
-            should_continue: true,
+            is_stopped: false,
             thread_handle: None,
         })
     }
 
     /// Spawns a thread for [stdin](std::io::stdin), waits until either the line is read or Ctrl-C signal is handled.
-    pub fn should_continue(&mut self) -> Result<bool> {
+    pub fn next_command(&mut self) -> Result<UserCommand> {
         self.wait_for_previous_thread();
 
-        println!("Press ENTER to continue or hit Ctrl-C to exit.");
+        if self.is_stopped {
+            return Ok(UserCommand::Stop);
+        }
 
-        if self.should_continue {
-            let stdin_sender = self.sender.clone();
+        println!(
+            "Press ENTER to continue, type \"back\" and press ENTER to revert the last step, or hit Ctrl-C to exit."
+        );
 
-            self.thread_handle = Some(thread::spawn(move || {
-                stdin()
-                    .read_line(&mut String::new())
-                    .expect("Could not read user's input.");
-                stdin_sender
-                    .send(true)
-                    .expect("Could not send signal on the channel.")
-            }));
+        let stdin_sender = self.sender.clone();
 
-            self.should_continue = self.receiver.recv()?;
-        }
+        self.thread_handle = Some(thread::spawn(move || {
+            let mut line = String::new();
+
+            stdin()
+                .read_line(&mut line)
+                .expect("Could not read user's input.");
+
+            let command = match line.trim() {
+                "b" | "back" => UserCommand::StepBack,
+                _ => UserCommand::Continue,
+            };
+
+            stdin_sender
+                .send(command)
+                .expect("Could not send signal on the channel.")
+        }));
+
+        let command = self.receiver.recv()?;
+
+        self.is_stopped = command == UserCommand::Stop;
 
-        Ok(self.should_continue)
+        Ok(command)
     }
 
     fn wait_for_previous_thread(&mut self) {
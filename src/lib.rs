@@ -27,8 +27,35 @@ pub mod prelude {
 
     pub use crate::scheme::{
         scheme_builder::{AlgorithmSchemeBuilder, AlgorithmSchemeDefinitionError},
+        scheme_file::{SchemeFile, SchemeFileParseError},
         AlgorithmScheme, AlgorithmSchemeFullApplicationError, AlgorithmSchemeInputValidationError,
-        ApplicationIterator, FullApplicationResult, SingleApplicationData, SingleApplicationResult,
-        SubstitutionFormulaDefinitionError,
+        ApplicationCycle, ApplicationIterator, ApplicationRewindError, ApplicationSnapshot,
+        ApplicationSnapshotParseError, ApplicationSnapshotRestorationError, AppliedSubstitution,
+        CancellationToken, ExecutionLimits, FullApplicationResult, GroupTieBreak, MatchStrategy,
+        OwnedApplicationIterator, OwnedSingleApplicationData, PartialApplicationResult,
+        ReversibleApplicationIterator, SingleApplicationData, SingleApplicationResult,
+        StochasticChoice, SubstitutionFormulaDefinitionError,
+    };
+
+    pub use crate::scheme::completion::{
+        CompletionError, CompletionLimits, CompletionReport, ConfluenceReport, CriticalPair,
+        LengthLexicographic, ReductionOrdering, RewritingRule, RewritingSystem,
+        RewritingSystemDefinitionError,
+    };
+
+    pub use crate::scheme::exploration::{
+        ExhaustedExplorationLimit, Exploration, ExplorationLimits, ExplorationOrder, Reachability,
+        RewritingPath, RewritingStep, SemiThueExplorer,
+    };
+
+    pub use crate::scheme::symbolic::{
+        PartialSymbolApplicationResult, SymbolApplicationIterator, SymbolApplicationResult,
+        SymbolApplicationStep, SymbolFormula, SymbolScheme, SymbolSchemeApplicationError,
+        SymbolSchemeBuilder, SymbolSchemeDefinitionError,
+    };
+
+    pub use crate::scheme::turing::{
+        CompiledTuringMachine, HeadMovement, TuringMachine, TuringMachineHalt,
+        TuringMachineParseError, TuringSimulationError,
     };
 }
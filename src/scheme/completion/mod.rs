@@ -0,0 +1,717 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! [RewritingSystem](RewritingSystem), the formulas of a scheme treated as a string rewriting system,
+//! with the checks of its confluence and the Knuth-Bendix completion.
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, VecDeque},
+};
+
+use thiserror::Error;
+
+use super::{
+    engine::{self, Execution, Rules},
+    limits::ExecutionLimits,
+};
+
+/// A string rewriting system: a word can be rewritten by any rule at any occurrence of its left side.
+///
+/// The system that rewrites each word into a unique normal form, no matter which rules are applied,
+/// is confluent and terminating, so two words are equal in the system exactly when their normal forms are the same.
+///
+/// # Example
+/// ```rust
+/// # use std::str;
+/// use markovalgorithms::prelude::*;
+///
+/// let scheme = AlgorithmSchemeBuilder::new()
+///     .with_alphabet(str::parse("ab").unwrap())
+///     .build_with_formula_definitions(["ab→a", "ba→b"].into_iter())
+///     .unwrap();
+///
+/// let system = scheme.rewriting_system().unwrap();
+/// let ordering = LengthLexicographic::new();
+///
+/// assert!(system.check_confluence(&ordering).is_terminating());
+/// assert!(!system.check_confluence(&ordering).is_confluent());
+///
+/// let completed = system.complete(&ordering, CompletionLimits::new()).unwrap();
+///
+/// assert!(completed.check_confluence(&ordering).is_confluent());
+/// assert_eq!(completed.normal_form("aab"), completed.normal_form("a"));
+/// assert_eq!(4, completed.rules().len());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewritingSystem {
+    rules: Vec<RewritingRule>,
+}
+
+/// A rule of a string rewriting system.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RewritingRule {
+    left: String,
+    right: String,
+}
+
+/// A word that two rules rewrite differently, because their left sides overlap or one contains the other,
+/// and the two words they rewrite it into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CriticalPair {
+    overlap: String,
+    rules: (usize, usize),
+    words: (String, String),
+}
+
+/// An ordering of words that the rules should decrease, so the rewriting always terminates.
+///
+/// The ordering is expected to be well-founded and compatible with the concatenation,
+/// any function that compares two words can be used as well.
+///
+/// # Example
+/// ```rust
+/// use std::cmp::Ordering;
+/// use markovalgorithms::prelude::*;
+///
+/// let by_count_of_b = |word: &str, other: &str| {
+///     let count = |word: &str| word.matches('b').count();
+///     count(word).cmp(&count(other)).then_with(|| LengthLexicographic::new().compare(word, other))
+/// };
+///
+/// assert_eq!(Ordering::Greater, by_count_of_b.compare("b", "aaa"));
+/// ```
+pub trait ReductionOrdering {
+    /// Compares the words, a rule rewrites the greater word into the lesser one.
+    fn compare(&self, word: &str, other: &str) -> Ordering;
+}
+
+/// The length-lexicographic ordering: the longer word is greater, and the words of the same length
+/// are compared by their first different characters.
+///
+/// The characters are compared by their precedence if it is declared, or by their code points otherwise.
+/// The characters without the declared precedence are greater than the ones with it.
+///
+/// # Example
+/// ```rust
+/// use std::cmp::Ordering;
+/// use markovalgorithms::prelude::*;
+///
+/// let ordering = LengthLexicographic::new();
+///
+/// assert_eq!(Ordering::Greater, ordering.compare("aa", "b"));
+/// assert_eq!(Ordering::Less, ordering.compare("ab", "ba"));
+/// assert_eq!(Ordering::Greater, ordering.with_precedence("ba").compare("ab", "ba"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct LengthLexicographic {
+    precedence: HashMap<char, usize>,
+}
+
+/// The report on the termination and the confluence of a rewriting system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfluenceReport {
+    unoriented_rules: Vec<RewritingRule>,
+    unjoinable_pairs: Vec<CriticalPair>,
+}
+
+/// The limits of the Knuth-Bendix completion, which does not have to stop on its own.
+///
+/// The limits that are not set are not checked, so the default value does not limit the completion at all.
+#[derive(Debug, Clone, Default)]
+pub struct CompletionLimits {
+    rules: Option<usize>,
+    pairs: Option<usize>,
+}
+
+/// The state of the completion that has not produced a confluent system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletionReport {
+    system: RewritingSystem,
+    unresolved_pairs: Vec<(String, String)>,
+}
+
+/// A formula of the scheme that cannot be a rule of a string rewriting system.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum RewritingSystemDefinitionError {
+    /// The formula has variables.
+    #[error("the substitution formula \"{0}\" has variables, so it is not a rule of a string rewriting system")]
+    FormulaWithVariables(String),
+    /// The formula is anchored to the boundaries of the word.
+    #[error("the substitution formula \"{0}\" is anchored, so it is not a rule of a string rewriting system")]
+    AnchoredFormula(String),
+}
+
+/// An error that stops the completion before it produces a confluent system.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum CompletionError {
+    /// Some pairs of words are equal in the system, but the ordering cannot turn them into rules.
+    #[error("the ordering cannot orient {} pairs of words", .0.unresolved_pairs().len())]
+    UnorientablePairs(CompletionReport),
+    /// The completion needs more rules than allowed.
+    #[error("the completion is stopped after reaching {} rules", .0.system().rules().len())]
+    HitTheRulesLimit(CompletionReport),
+    /// The completion needs to consider more pairs of words than allowed.
+    #[error("the completion is stopped after considering the allowed number of pairs of words")]
+    HitThePairsLimit(CompletionReport),
+}
+
+impl RewritingSystem {
+    /// Creates a system from the left and the right sides of the rules.
+    pub fn new<I, S>(rules: I) -> Self
+    where
+        I: IntoIterator<Item = (S, S)>,
+        S: Into<String>,
+    {
+        Self {
+            rules: rules
+                .into_iter()
+                .map(|(left, right)| RewritingRule {
+                    left: left.into(),
+                    right: right.into(),
+                })
+                .collect(),
+        }
+    }
+
+    /// The rules of the system.
+    pub fn rules(&self) -> &[RewritingRule] {
+        &self.rules
+    }
+
+    /// Rewrites the word until no rule is applicable.
+    ///
+    /// The rules are applied in the order of the system to their leftmost occurrences,
+    /// the normal form does not depend on the order if the system is confluent.
+    /// The rewriting may never stop if the system is not terminating.
+    pub fn normal_form(&self, word: &str) -> String {
+        Normalizer::new(&self.rules).normal_form(word)
+    }
+
+    /// Finds the critical pairs of the rules, the pairs that are the same words are left out.
+    ///
+    /// # Example
+    /// ```rust
+    /// use markovalgorithms::prelude::*;
+    ///
+    /// let system = RewritingSystem::new([("ab", "c"), ("bd", "e")]);
+    /// let pairs = system.critical_pairs();
+    ///
+    /// assert_eq!(1, pairs.len());
+    /// assert_eq!("abd", pairs[0].overlap());
+    /// assert_eq!(("cd", "ae"), pairs[0].words());
+    /// ```
+    pub fn critical_pairs(&self) -> Vec<CriticalPair> {
+        let characters: Vec<_> = self.rules.iter().map(RewritingRule::characters).collect();
+
+        let mut pairs = Vec::new();
+
+        for (first_index, first) in characters.iter().enumerate() {
+            for (second_index, second) in characters.iter().enumerate() {
+                pairs.extend(critical_pairs_of(
+                    (first_index, first),
+                    (second_index, second),
+                ));
+            }
+        }
+
+        pairs
+    }
+
+    /// Checks whether the ordering decreases every rule, so the system is terminating,
+    /// and whether every critical pair is rewritten into the same normal form.
+    ///
+    /// By Newman's lemma a terminating system is confluent exactly when all of its critical pairs are joinable.
+    /// The critical pairs are only checked if the system is terminating, since the normal forms may not exist otherwise.
+    pub fn check_confluence<O>(&self, ordering: &O) -> ConfluenceReport
+    where
+        O: ReductionOrdering + ?Sized,
+    {
+        let unoriented_rules: Vec<_> = self
+            .rules
+            .iter()
+            .filter(|rule| !rule.is_oriented_by(ordering))
+            .cloned()
+            .collect();
+
+        if !unoriented_rules.is_empty() {
+            return ConfluenceReport {
+                unoriented_rules,
+                unjoinable_pairs: Vec::new(),
+            };
+        }
+
+        let normalizer = Normalizer::new(&self.rules);
+
+        ConfluenceReport {
+            unoriented_rules,
+            unjoinable_pairs: self
+                .critical_pairs()
+                .into_iter()
+                .filter(|pair| {
+                    normalizer.normal_form(&pair.words.0) != normalizer.normal_form(&pair.words.1)
+                })
+                .collect(),
+        }
+    }
+
+    /// Turns the system into an equivalent confluent and terminating one with the Knuth-Bendix completion.
+    ///
+    /// The rules are treated as equations and oriented by the ordering after they are rewritten into normal forms,
+    /// then the critical pairs of the new rules are added as equations until all of them are joinable.
+    /// The rules that the new rules rewrite are turned back into equations or simplified,
+    /// so the completed system is reduced.
+    ///
+    /// The completion may not stop, so it should be limited for the systems it is not known to stop for.
+    ///
+    /// # Errors
+    /// Returns a distinct [error](CompletionError) if some equations cannot be oriented, or if a limit is exhausted.
+    /// Each error carries the [report](CompletionReport) with the rules found so far and the unresolved pairs.
+    ///
+    /// # Example
+    /// ```rust
+    /// use markovalgorithms::prelude::*;
+    ///
+    /// let system = RewritingSystem::new([("ba", "ab")]);
+    /// let ordering = LengthLexicographic::new();
+    ///
+    /// assert_eq!(system, system.complete(&ordering, CompletionLimits::new()).unwrap());
+    ///
+    /// let by_length = |word: &str, other: &str| word.len().cmp(&other.len());
+    /// let error = system.complete(&by_length, CompletionLimits::new()).unwrap_err();
+    ///
+    /// let CompletionError::UnorientablePairs(report) = error else {
+    ///     panic!("The rule cannot be oriented by the length.");
+    /// };
+    ///
+    /// assert_eq!([("ba".to_owned(), "ab".to_owned())], report.unresolved_pairs());
+    /// ```
+    pub fn complete<O>(
+        &self,
+        ordering: &O,
+        limits: CompletionLimits,
+    ) -> Result<RewritingSystem, CompletionError>
+    where
+        O: ReductionOrdering + ?Sized,
+    {
+        Completion::new(self, ordering, limits).run()
+    }
+}
+
+impl RewritingRule {
+    /// The left side of the rule.
+    pub fn left(&self) -> &str {
+        &self.left
+    }
+
+    /// The right side of the rule.
+    pub fn right(&self) -> &str {
+        &self.right
+    }
+
+    fn characters(&self) -> (Vec<char>, Vec<char>) {
+        (self.left.chars().collect(), self.right.chars().collect())
+    }
+
+    fn is_oriented_by<O>(&self, ordering: &O) -> bool
+    where
+        O: ReductionOrdering + ?Sized,
+    {
+        ordering.compare(&self.left, &self.right) == Ordering::Greater
+    }
+}
+
+impl CriticalPair {
+    /// The word both rules rewrite.
+    pub fn overlap(&self) -> &str {
+        &self.overlap
+    }
+
+    /// The indices of the rules, the first one rewrites the start of the overlap.
+    pub fn rules(&self) -> (usize, usize) {
+        self.rules
+    }
+
+    /// The words the overlap is rewritten into by the first and by the second rule.
+    pub fn words(&self) -> (&str, &str) {
+        (&self.words.0, &self.words.1)
+    }
+}
+
+impl<F> ReductionOrdering for F
+where
+    F: Fn(&str, &str) -> Ordering,
+{
+    fn compare(&self, word: &str, other: &str) -> Ordering {
+        self(word, other)
+    }
+}
+
+impl LengthLexicographic {
+    /// Creates the ordering that compares the characters by their code points.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares the precedence of the characters, from the least to the greatest.
+    ///
+    /// May be called multiple times in order to replace the prior precedence.
+    pub fn with_precedence(mut self, characters: &str) -> Self {
+        self.precedence = characters
+            .chars()
+            .enumerate()
+            .map(|(rank, character)| (character, rank))
+            .collect();
+        self
+    }
+
+    fn rank(&self, character: char) -> (usize, char) {
+        (
+            self.precedence
+                .get(&character)
+                .copied()
+                .unwrap_or(self.precedence.len()),
+            character,
+        )
+    }
+}
+
+impl ReductionOrdering for LengthLexicographic {
+    fn compare(&self, word: &str, other: &str) -> Ordering {
+        word.chars()
+            .count()
+            .cmp(&other.chars().count())
+            .then_with(|| {
+                word.chars()
+                    .map(|character| self.rank(character))
+                    .cmp(other.chars().map(|character| self.rank(character)))
+            })
+    }
+}
+
+impl ConfluenceReport {
+    /// The rules the ordering does not decrease.
+    pub fn unoriented_rules(&self) -> &[RewritingRule] {
+        &self.unoriented_rules
+    }
+
+    /// The critical pairs that are rewritten into different normal forms.
+    pub fn unjoinable_pairs(&self) -> &[CriticalPair] {
+        &self.unjoinable_pairs
+    }
+
+    /// Reports whether the ordering decreases every rule.
+    pub fn is_terminating(&self) -> bool {
+        self.unoriented_rules.is_empty()
+    }
+
+    /// Reports whether the system is terminating and every critical pair is joinable.
+    pub fn is_confluent(&self) -> bool {
+        self.is_terminating() && self.unjoinable_pairs.is_empty()
+    }
+}
+
+impl CompletionLimits {
+    /// Creates limits that do not restrict the completion.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the number of rules of the completed system.
+    ///
+    /// May be called multiple times in order to replace the prior limit.
+    pub fn with_rules(mut self, rules: usize) -> Self {
+        _ = self.rules.insert(rules);
+        self
+    }
+
+    /// Limits the number of pairs of words considered, including the rules of the initial system.
+    ///
+    /// May be called multiple times in order to replace the prior limit.
+    pub fn with_pairs(mut self, pairs: usize) -> Self {
+        _ = self.pairs.insert(pairs);
+        self
+    }
+}
+
+impl CompletionReport {
+    /// The rules found before the completion has stopped.
+    pub fn system(&self) -> &RewritingSystem {
+        &self.system
+    }
+
+    /// The pairs of words that are equal in the system but are neither rules nor joinable.
+    pub fn unresolved_pairs(&self) -> &[(String, String)] {
+        &self.unresolved_pairs
+    }
+}
+
+/// Finds the critical pairs of the first rule with the second one:
+/// the overlaps of the end of the first left side with the start of the second one,
+/// and the occurrences of the second left side inside the first one.
+fn critical_pairs_of(
+    (first_index, (first_left, first_right)): (usize, &(Vec<char>, Vec<char>)),
+    (second_index, (second_left, second_right)): (usize, &(Vec<char>, Vec<char>)),
+) -> Vec<CriticalPair> {
+    let mut pairs = Vec::new();
+
+    let mut add = |overlap: Vec<&[char]>, first: Vec<&[char]>, second: Vec<&[char]>| {
+        let (first, second): (String, String) = (
+            first.concat().into_iter().collect(),
+            second.concat().into_iter().collect(),
+        );
+
+        if first != second {
+            pairs.push(CriticalPair {
+                overlap: overlap.concat().into_iter().collect(),
+                rules: (first_index, second_index),
+                words: (first, second),
+            });
+        }
+    };
+
+    // The overlaps leave a part of each left side outside of the other one.
+    for length in 1..first_left.len().min(second_left.len()) {
+        let (prefix, suffix) = first_left.split_at(first_left.len() - length);
+        let (common, rest) = second_left.split_at(length);
+
+        if suffix == common {
+            add(
+                vec![first_left, rest],
+                vec![first_right, rest],
+                vec![prefix, second_right],
+            );
+        }
+    }
+
+    // The left sides of the same length are only compared once, and a left side always contains itself.
+    if second_left.len() < first_left.len()
+        || second_left.len() == first_left.len() && second_index > first_index
+    {
+        for start in 0..=first_left.len() - second_left.len() {
+            let (prefix, rest) = first_left.split_at(start);
+            let suffix = &rest[second_left.len()..];
+
+            if rest.starts_with(second_left) {
+                add(
+                    vec![first_left],
+                    vec![first_right],
+                    vec![prefix, second_right, suffix],
+                );
+            }
+        }
+    }
+
+    pairs
+}
+
+/// The rules compiled for the engine, which rewrites the words into their normal forms.
+struct Normalizer {
+    rules: Rules<u32>,
+}
+
+impl Normalizer {
+    fn new(rules: &[RewritingRule]) -> Self {
+        let sides: Vec<_> = rules
+            .iter()
+            .map(|rule| (Self::encode(&rule.left), Self::encode(&rule.right)))
+            .collect();
+
+        Self {
+            rules: Rules::new(
+                sides
+                    .iter()
+                    .map(|(left, right)| (left.as_slice(), right.as_slice(), false)),
+            ),
+        }
+    }
+
+    fn normal_form(&self, word: &str) -> String {
+        let mut execution = Execution::incremental(&self.rules, &Self::encode(word));
+
+        _ = engine::run::<_, (), _>(&self.rules, &mut execution, &ExecutionLimits::new(), |_| {
+            None
+        });
+
+        execution
+            .word
+            .units()
+            .map(|&unit| char::from_u32(unit).expect("The units are encoded characters."))
+            .collect()
+    }
+
+    fn encode(word: &str) -> Vec<u32> {
+        word.chars().map(u32::from).collect()
+    }
+}
+
+/// The state of the Knuth-Bendix completion.
+struct Completion<'a, O: ?Sized> {
+    ordering: &'a O,
+    limits: CompletionLimits,
+    rules: Vec<RewritingRule>,
+    normalizer: Normalizer,
+    equations: VecDeque<(String, String)>,
+    /// The equations that cannot be oriented, with the number of the rules that have been added before them.
+    postponed: Vec<(String, String, usize)>,
+    rules_added: usize,
+    pairs_considered: usize,
+}
+
+impl<'a, O> Completion<'a, O>
+where
+    O: ReductionOrdering + ?Sized,
+{
+    fn new(system: &RewritingSystem, ordering: &'a O, limits: CompletionLimits) -> Self {
+        Self {
+            ordering,
+            limits,
+            rules: Vec::new(),
+            normalizer: Normalizer::new(&[]),
+            equations: system
+                .rules
+                .iter()
+                .map(|rule| (rule.left.clone(), rule.right.clone()))
+                .collect(),
+            postponed: Vec::new(),
+            rules_added: 0,
+            pairs_considered: 0,
+        }
+    }
+
+    fn run(mut self) -> Result<RewritingSystem, CompletionError> {
+        loop {
+            let Some((word, other)) = self.equations.pop_front() else {
+                // The postponed equations are retried once the rules added after them may have joined them.
+                let (retried, postponed) = self
+                    .postponed
+                    .drain(..)
+                    .partition(|&(_, _, rules_added)| rules_added < self.rules_added);
+                self.postponed = postponed;
+
+                if retried.is_empty() {
+                    break;
+                }
+
+                self.equations
+                    .extend(retried.into_iter().map(|(word, other, _)| (word, other)));
+                continue;
+            };
+
+            if self
+                .limits
+                .pairs
+                .is_some_and(|limit| self.pairs_considered >= limit)
+            {
+                self.equations.push_front((word, other));
+                return Err(CompletionError::HitThePairsLimit(self.into_report()));
+            }
+
+            self.pairs_considered += 1;
+
+            let word = self.normalizer.normal_form(&word);
+            let other = self.normalizer.normal_form(&other);
+
+            let (left, right) = match self.ordering.compare(&word, &other) {
+                Ordering::Equal if word == other => continue,
+                Ordering::Greater => (word, other),
+                Ordering::Less => (other, word),
+                Ordering::Equal => {
+                    self.postponed.push((word, other, self.rules_added));
+                    continue;
+                }
+            };
+
+            if self
+                .limits
+                .rules
+                .is_some_and(|limit| self.rules.len() >= limit)
+            {
+                self.equations.push_front((left, right));
+                return Err(CompletionError::HitTheRulesLimit(self.into_report()));
+            }
+
+            self.add_rule(RewritingRule { left, right });
+        }
+
+        if self.postponed.is_empty() {
+            Ok(RewritingSystem { rules: self.rules })
+        } else {
+            Err(CompletionError::UnorientablePairs(self.into_report()))
+        }
+    }
+
+    /// Adds the rule, simplifies the other rules with it and adds its critical pairs as equations.
+    fn add_rule(&mut self, rule: RewritingRule) {
+        let (collapsed, kept): (Vec<_>, Vec<_>) = self
+            .rules
+            .drain(..)
+            .partition(|other| other.left.contains(&rule.left));
+
+        self.equations.extend(
+            collapsed
+                .into_iter()
+                .map(|collapsed| (collapsed.left, collapsed.right)),
+        );
+
+        self.rules = kept;
+        self.rules.push(rule);
+        self.rules_added += 1;
+        self.normalizer = Normalizer::new(&self.rules);
+
+        let last = self.rules.len() - 1;
+
+        for index in 0..last {
+            let right = self.normalizer.normal_form(&self.rules[index].right);
+            self.rules[index].right = right;
+        }
+
+        self.normalizer = Normalizer::new(&self.rules);
+
+        let characters: Vec<_> = self.rules.iter().map(RewritingRule::characters).collect();
+
+        for (index, other) in characters.iter().enumerate() {
+            let pairs = critical_pairs_of((last, &characters[last]), (index, other))
+                .into_iter()
+                .chain(if index == last {
+                    Vec::new()
+                } else {
+                    critical_pairs_of((index, other), (last, &characters[last]))
+                });
+
+            self.equations
+                .extend(pairs.map(|CriticalPair { words, .. }| words));
+        }
+    }
+
+    fn into_report(self) -> CompletionReport {
+        CompletionReport {
+            system: RewritingSystem { rules: self.rules },
+            unresolved_pairs: self
+                .postponed
+                .into_iter()
+                .map(|(word, other, _)| (word, other))
+                .chain(self.equations)
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,325 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{cmp::Ordering, str::FromStr};
+
+use crate::prelude::{AlgorithmSchemeBuilder, Alphabet};
+
+use super::*;
+
+fn rules(system: &RewritingSystem) -> Vec<(&str, &str)> {
+    let mut rules: Vec<_> = system
+        .rules()
+        .iter()
+        .map(|rule| (rule.left(), rule.right()))
+        .collect();
+    rules.sort_unstable();
+    rules
+}
+
+fn by_length(word: &str, other: &str) -> Ordering {
+    word.chars().count().cmp(&other.chars().count())
+}
+
+#[test]
+fn the_length_lexicographic_ordering_compares_the_lengths_first() {
+    let ordering = LengthLexicographic::new();
+
+    assert_eq!(Ordering::Greater, ordering.compare("aaa", "zz"));
+    assert_eq!(Ordering::Less, ordering.compare("", "a"));
+    assert_eq!(Ordering::Less, ordering.compare("aab", "aba"));
+    assert_eq!(Ordering::Equal, ordering.compare("abc", "abc"));
+    assert_eq!(Ordering::Greater, ordering.compare("яя", "zz"));
+}
+
+#[test]
+fn the_length_lexicographic_ordering_follows_the_precedence() {
+    let ordering = LengthLexicographic::new().with_precedence("cba");
+
+    assert_eq!(Ordering::Greater, ordering.compare("a", "c"));
+    assert_eq!(Ordering::Less, ordering.compare("bc", "ba"));
+    assert_eq!(Ordering::Greater, ordering.compare("d", "a"));
+    assert_eq!(Ordering::Less, ordering.compare("d", "e"));
+
+    let ordering = ordering.with_precedence("ab");
+
+    assert_eq!(Ordering::Less, ordering.compare("a", "c"));
+}
+
+#[test]
+fn the_critical_pairs_include_the_overlaps_and_the_inclusions() {
+    let system = RewritingSystem::new([("aba", "c"), ("ab", "d"), ("ba", "e")]);
+
+    let pairs: Vec<_> = system
+        .critical_pairs()
+        .into_iter()
+        .map(|pair| {
+            let (first, second) = pair.words();
+            (
+                pair.overlap().to_owned(),
+                pair.rules(),
+                first.to_owned(),
+                second.to_owned(),
+            )
+        })
+        .collect();
+
+    let pair = |overlap: &str, rules, first: &str, second: &str| {
+        (
+            overlap.to_owned(),
+            rules,
+            first.to_owned(),
+            second.to_owned(),
+        )
+    };
+
+    assert_eq!(
+        vec![
+            pair("ababa", (0, 0), "cba", "abc"),
+            pair("abab", (0, 1), "cb", "abd"),
+            pair("aba", (0, 1), "c", "da"),
+            pair("aba", (0, 2), "c", "ae"),
+            pair("aba", (1, 2), "da", "ae"),
+            pair("baba", (2, 0), "eba", "bc"),
+            pair("bab", (2, 1), "eb", "bd"),
+        ],
+        pairs
+    );
+}
+
+#[test]
+fn the_trivial_critical_pairs_are_left_out() {
+    let system = RewritingSystem::new([("aa", "a")]);
+
+    assert!(system.critical_pairs().is_empty());
+}
+
+#[test]
+fn the_normal_form_is_reached_by_the_rules() {
+    let system = RewritingSystem::new([("ba", "ab"), ("aa", ""), ("bbb", "")]);
+
+    assert_eq!("abb", system.normal_form("bab"));
+    assert_eq!("", system.normal_form("babab"));
+    assert_eq!("a", system.normal_form("bababa"));
+    assert_eq!("", system.normal_form(""));
+}
+
+#[test]
+fn the_rules_the_ordering_does_not_decrease_are_reported() {
+    let system = RewritingSystem::new([("ab", "ba"), ("b", "aa")]);
+    let report = system.check_confluence(&LengthLexicographic::new());
+
+    assert!(!report.is_terminating());
+    assert!(!report.is_confluent());
+    assert_eq!(system.rules(), report.unoriented_rules());
+    assert!(report.unjoinable_pairs().is_empty());
+}
+
+#[test]
+fn the_unjoinable_critical_pairs_are_reported() {
+    let system = RewritingSystem::new([("ab", "a"), ("ba", "b")]);
+    let report = system.check_confluence(&LengthLexicographic::new());
+
+    assert!(report.is_terminating());
+    assert!(!report.is_confluent());
+
+    let overlaps: Vec<_> = report
+        .unjoinable_pairs()
+        .iter()
+        .map(CriticalPair::overlap)
+        .collect();
+
+    assert_eq!(["aba", "bab"], overlaps.as_slice());
+}
+
+#[test]
+fn a_confluent_system_is_recognized() {
+    let system = RewritingSystem::new([("ba", "ab"), ("aa", ""), ("bbb", "")]);
+    let report = system.check_confluence(&LengthLexicographic::new());
+
+    assert!(report.is_confluent());
+}
+
+#[test]
+fn the_completion_adds_the_missing_rules() {
+    let system = RewritingSystem::new([("ab", "a"), ("ba", "b")]);
+    let ordering = LengthLexicographic::new();
+
+    let completed = system.complete(&ordering, CompletionLimits::new()).unwrap();
+
+    assert_eq!(
+        vec![("aa", "a"), ("ab", "a"), ("ba", "b"), ("bb", "b")],
+        rules(&completed)
+    );
+    assert!(completed.check_confluence(&ordering).is_confluent());
+}
+
+#[test]
+fn the_completion_orients_the_rules_by_the_ordering() {
+    let system = RewritingSystem::new([("ab", "ba")]);
+
+    let completed = system
+        .complete(&LengthLexicographic::new(), CompletionLimits::new())
+        .unwrap();
+
+    assert_eq!(vec![("ba", "ab")], rules(&completed));
+
+    let completed = system
+        .complete(
+            &LengthLexicographic::new().with_precedence("ba"),
+            CompletionLimits::new(),
+        )
+        .unwrap();
+
+    assert_eq!(vec![("ab", "ba")], rules(&completed));
+}
+
+#[test]
+fn the_completion_reduces_the_rules() {
+    let system = RewritingSystem::new([("aab", "b"), ("ab", "b")]);
+
+    let completed = system
+        .complete(&LengthLexicographic::new(), CompletionLimits::new())
+        .unwrap();
+
+    assert_eq!(vec![("ab", "b")], rules(&completed));
+}
+
+#[test]
+fn the_completion_of_a_group_presentation_decides_the_equality_of_words() {
+    // The free group of a single generator a with the inverse A.
+    let system = RewritingSystem::new([("aA", ""), ("Aa", "")]);
+
+    let completed = system
+        .complete(&LengthLexicographic::new(), CompletionLimits::new())
+        .unwrap();
+
+    assert_eq!(completed.normal_form("aaAaA"), completed.normal_form("a"));
+
+    // The symmetric group of three elements, generated by the transpositions a and b.
+    let system = RewritingSystem::new([("aa", ""), ("bb", ""), ("bab", "aba")]);
+    let ordering = LengthLexicographic::new();
+
+    let completed = system.complete(&ordering, CompletionLimits::new()).unwrap();
+
+    assert!(completed.check_confluence(&ordering).is_confluent());
+    assert_eq!(completed.normal_form("ababab"), "");
+    assert_eq!(completed.normal_form("baba"), completed.normal_form("ab"));
+
+    let mut elements: Vec<_> = ["", "a", "b", "ab", "ba", "aba", "bab", "abab", "baba"]
+        .into_iter()
+        .map(|word| completed.normal_form(word))
+        .collect();
+    elements.sort_unstable();
+    elements.dedup();
+
+    assert_eq!(6, elements.len());
+}
+
+#[test]
+fn the_unorientable_pairs_are_reported() {
+    let system = RewritingSystem::new([("ab", "ba"), ("aa", "")]);
+
+    let error = system
+        .complete(&by_length, CompletionLimits::new())
+        .unwrap_err();
+
+    let CompletionError::UnorientablePairs(report) = error else {
+        panic!("The completion should not be able to orient the pairs.");
+    };
+
+    assert_eq!(vec![("aa", "")], rules(report.system()));
+    assert_eq!(
+        [("ab".to_owned(), "ba".to_owned())],
+        report.unresolved_pairs()
+    );
+}
+
+#[test]
+fn the_completion_stops_at_the_limits() {
+    // The completion of the relation of the Baumslag–Solitar group does not stop.
+    let system = RewritingSystem::new([("abA", "bb"), ("aA", ""), ("Aa", "")]);
+    let ordering = LengthLexicographic::new().with_precedence("Aab");
+
+    let error = system
+        .complete(&ordering, CompletionLimits::new().with_rules(8))
+        .unwrap_err();
+
+    let CompletionError::HitTheRulesLimit(report) = error else {
+        panic!("The completion should exhaust the rules limit.");
+    };
+
+    assert_eq!(8, report.system().rules().len());
+    assert!(!report.unresolved_pairs().is_empty());
+
+    let error = system
+        .complete(&ordering, CompletionLimits::new().with_pairs(2))
+        .unwrap_err();
+
+    let CompletionError::HitThePairsLimit(report) = error else {
+        panic!("The completion should exhaust the pairs limit.");
+    };
+
+    assert_eq!(2, report.system().rules().len());
+    assert_eq!(
+        [("Aa".to_owned(), String::new())],
+        report.unresolved_pairs()
+    );
+}
+
+#[test]
+fn the_formulas_of_a_scheme_become_the_rules() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("ab").unwrap())
+        .build_with_formula_definitions(["ba→⋅ab", "aa→"].into_iter())
+        .unwrap();
+
+    let system = scheme.rewriting_system().unwrap();
+
+    assert_eq!(vec![("aa", ""), ("ba", "ab")], rules(&system));
+}
+
+#[test]
+fn the_formulas_with_variables_or_anchors_are_not_rules() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("ab").unwrap())
+        .with_variables()
+        .build_with_formula_definitions(["a→b", "e.xa→⋅e.x"].into_iter())
+        .unwrap();
+
+    assert_eq!(
+        Err(RewritingSystemDefinitionError::FormulaWithVariables(
+            "e.xa→⋅e.x".to_owned()
+        )),
+        scheme.rewriting_system()
+    );
+
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("ab").unwrap())
+        .with_anchors()
+        .build_with_formula_definitions(["^a→b"].into_iter())
+        .unwrap();
+
+    assert_eq!(
+        Err(RewritingSystemDefinitionError::AnchoredFormula(
+            "^a→b".to_owned()
+        )),
+        scheme.rewriting_system()
+    );
+}
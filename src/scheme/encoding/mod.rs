@@ -0,0 +1,105 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! [WordEncoding](WordEncoding), the translation between strings and the units the engine works with.
+
+#[cfg(test)]
+mod tests;
+
+use std::{borrow::Cow, collections::HashMap};
+
+use crate::alphabet::Alphabet;
+
+use super::word_buffer::WordBuffer;
+
+/// The way the words of a scheme over characters are represented as units.
+#[derive(Debug)]
+pub(crate) enum WordEncoding {
+    /// The units are the UTF-8 bytes of the words.
+    Utf8,
+    /// Each character is a single unit, see [DenseEncoding](DenseEncoding).
+    Dense(DenseEncoding),
+}
+
+/// The characters of a small alphabet numbered from zero, so each character takes a single byte.
+///
+/// The codes stay below `0x80`, so each of them starts a character as if it were ASCII,
+/// and the characters are counted the same way as in UTF-8 words.
+#[derive(Debug)]
+pub(crate) struct DenseEncoding {
+    characters: Vec<char>,
+    codes: HashMap<char, u8>,
+}
+
+impl WordEncoding {
+    /// The largest alphabet, including the extension, that is encoded densely.
+    pub(crate) const MAX_DENSE_CHARACTERS: usize = 0x80;
+
+    /// Selects the dense encoding for the small alphabets and UTF-8 for the rest.
+    pub(crate) fn new(alphabet: &Alphabet) -> Self {
+        let (main, extension) = alphabet.sorted_characters();
+
+        if main.len() + extension.len() > Self::MAX_DENSE_CHARACTERS {
+            return Self::Utf8;
+        }
+
+        let characters: Vec<_> = main.into_iter().chain(extension).collect();
+        let codes = characters
+            .iter()
+            .enumerate()
+            .map(|(code, &character)| (character, code as u8))
+            .collect();
+
+        Self::Dense(DenseEncoding { characters, codes })
+    }
+
+    /// Translates the word to units, the word is expected to consist of the characters of the alphabet.
+    pub(crate) fn encode<'w>(&self, word: &'w str) -> Cow<'w, [u8]> {
+        match self {
+            Self::Utf8 => Cow::Borrowed(word.as_bytes()),
+            Self::Dense(DenseEncoding { codes, .. }) => {
+                Cow::Owned(word.chars().map(|character| codes[&character]).collect())
+            }
+        }
+    }
+
+    /// Translates the word back to a string.
+    pub(crate) fn decode(&self, word: &WordBuffer<u8>) -> String {
+        match self {
+            Self::Utf8 => word.to_string(),
+            Self::Dense(DenseEncoding { characters, .. }) => word
+                .units()
+                .map(|&code| characters[usize::from(code)])
+                .collect(),
+        }
+    }
+
+    /// The number of bytes each code stands for, if the encoding is dense.
+    pub(crate) fn unit_widths(&self) -> Option<Vec<usize>> {
+        match self {
+            Self::Utf8 => None,
+            Self::Dense(DenseEncoding { characters, .. }) => Some(
+                characters
+                    .iter()
+                    .map(|character| character.len_utf8())
+                    .collect(),
+            ),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{collections::HashSet, str};
+
+use super::*;
+
+#[test]
+fn a_small_alphabet_is_encoded_densely() {
+    let alphabet = str::parse::<Alphabet>("ba").unwrap().extend('ж').unwrap();
+    let encoding = WordEncoding::new(&alphabet);
+
+    assert!(matches!(encoding, WordEncoding::Dense(_)));
+    assert_eq!([1, 2, 0, 2], *encoding.encode("bжaж"));
+    assert_eq!(Some(vec![1, 1, 2]), encoding.unit_widths());
+}
+
+#[test]
+fn a_large_alphabet_is_encoded_as_utf8() {
+    let characters: HashSet<_> = ('一'..)
+        .take(WordEncoding::MAX_DENSE_CHARACTERS + 1)
+        .collect();
+    let alphabet = Alphabet::try_from(&characters).unwrap();
+    let encoding = WordEncoding::new(&alphabet);
+
+    assert!(matches!(encoding, WordEncoding::Utf8));
+    assert_eq!("一丁".as_bytes(), &*encoding.encode("一丁"));
+    assert_eq!(None, encoding.unit_widths());
+}
+
+#[test]
+fn the_encoded_words_are_decoded_back() {
+    let alphabet = str::parse::<Alphabet>("abжぬ").unwrap();
+
+    for encoding in [WordEncoding::new(&alphabet), WordEncoding::Utf8] {
+        let word = "ぬaжbbж";
+        let buffer = WordBuffer::new(&encoding.encode(word));
+
+        assert_eq!(word, encoding.decode(&buffer));
+        assert_eq!(6, buffer.char_count());
+    }
+}
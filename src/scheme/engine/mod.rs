@@ -0,0 +1,986 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! The engine that applies substitution formulas to words made of [units](Unit),
+//! shared by the schemes over strings and the schemes over arbitrary symbols.
+
+#[cfg(test)]
+mod tests;
+
+use std::{cmp::Ordering, ops::Range};
+
+use super::{
+    limits::{ExecutionLimits, ExhaustedLimit},
+    match_index::MatchIndex,
+    matcher::{FormulaMatcher, PatternMatch},
+    pattern::{Pattern, PatternOccurrence},
+    random::SplitMix64,
+    word_buffer::{Unit, WordBuffer},
+};
+
+/// The substitution formulas translated to units, together with the automaton that selects them.
+#[derive(Debug)]
+pub(crate) struct Rules<U> {
+    matcher: FormulaMatcher<U>,
+    units: Vec<U>,
+    formulas: Vec<Rule>,
+    /// The formula of each pattern of the automaton, the formulas with variables are left out of it.
+    automaton_formulas: Vec<usize>,
+    /// The formulas with variables in their order, they are searched for one by one.
+    patterns: Vec<(usize, Pattern<U>)>,
+    /// The number of bytes each unit stands for, when the units are dense codes of characters.
+    unit_widths: Option<Vec<usize>>,
+    strategy: MatchStrategy,
+    /// The weights of the formulas and the source of randomness, when the formula to apply is chosen at random.
+    stochastic_selection: Option<StochasticSelection>,
+    /// The groups of formulas with equal priority, when the formulas are not totally ordered.
+    priority_groups: Option<PriorityGroups>,
+}
+
+#[derive(Debug)]
+struct StochasticSelection {
+    random: SplitMix64,
+    weights: Vec<u64>,
+}
+
+#[derive(Debug)]
+struct PriorityGroups {
+    /// The group of each formula, the groups are numbered in the order of the formulas.
+    groups: Vec<usize>,
+    tie_break: GroupTieBreak,
+}
+
+/// The way the formula to apply and its occurrence are selected on each step.
+///
+/// # Example
+/// ```rust
+/// # use std::str;
+/// use markovalgorithms::prelude::*;
+///
+/// let build = |strategy| {
+///     AlgorithmSchemeBuilder::new()
+///         .with_alphabet(str::parse("abc").unwrap())
+///         .with_match_strategy(strategy)
+///         .build_with_formula_definitions(["b→⋅c", "a→⋅c"].into_iter())
+///         .unwrap()
+/// };
+///
+/// assert_eq!("acab", build(MatchStrategy::Leftmost).apply("abab", 1).unwrap().word());
+/// assert_eq!("abac", build(MatchStrategy::Rightmost).apply("abab", 1).unwrap().word());
+/// assert_eq!("cbab", build(MatchStrategy::PositionFirst).apply("abab", 1).unwrap().word());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum MatchStrategy {
+    /// The first applicable formula rewrites its leftmost occurrence, as in the classic definition.
+    #[default]
+    Leftmost,
+    /// The first applicable formula rewrites its rightmost occurrence.
+    Rightmost,
+    /// The occurrence that starts first among the occurrences of all formulas is rewritten,
+    /// the formula that comes first wins among the occurrences that start at the same position.
+    PositionFirst,
+}
+
+impl MatchStrategy {
+    /// Checks whether the occurrence of a formula, given by the index of the formula and its start,
+    /// should be selected instead of the other one.
+    pub(crate) fn prefers(
+        self,
+        (index, start): (usize, usize),
+        (other_index, other_start): (usize, usize),
+    ) -> bool {
+        match self {
+            Self::Leftmost => (index, start) < (other_index, other_start),
+            Self::Rightmost => (index, other_start) < (other_index, start),
+            Self::PositionFirst => (start, index) < (other_start, other_index),
+        }
+    }
+}
+
+/// The way the occurrences that start at the same position are ordered inside a group of formulas with equal priority.
+///
+/// # Example
+/// ```rust
+/// # use std::str;
+/// use markovalgorithms::prelude::*;
+///
+/// let build = |tie_break| {
+///     AlgorithmSchemeBuilder::new()
+///         .with_alphabet(str::parse("abc").unwrap())
+///         .with_priority_groups(tie_break)
+///         .build_with_formula_definitions(["b→⋅c", "|a→⋅c", "|ab→⋅"].into_iter())
+///         .unwrap()
+/// };
+///
+/// assert_eq!("cbb", build(GroupTieBreak::FirstFormula).apply("abb", 1).unwrap().word());
+/// assert_eq!("b", build(GroupTieBreak::LongestMatch).apply("abb", 1).unwrap().word());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum GroupTieBreak {
+    /// The formula that comes first wins.
+    #[default]
+    FirstFormula,
+    /// The longest occurrence wins, the formula that comes first wins among the occurrences of the same length.
+    LongestMatch,
+}
+
+/// The sides of a formula, either fixed or with variables.
+#[derive(Debug)]
+pub(crate) enum RuleSides<'a, U> {
+    Literal(&'a [U], &'a [U]),
+    Pattern(Pattern<U>),
+}
+
+#[derive(Debug)]
+struct Rule {
+    left: Range<usize>,
+    right: Range<usize>,
+    is_final: bool,
+}
+
+impl<U: Unit> Rules<U> {
+    /// Creates the rules from the left sides, the right sides and the finality of the formulas, in their order.
+    pub(crate) fn new<'a, I>(formulas: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a [U], &'a [U], bool)>,
+        U: 'a,
+    {
+        Self::with_sides(
+            formulas
+                .into_iter()
+                .map(|(left, right, is_final)| (RuleSides::Literal(left, right), is_final)),
+        )
+    }
+
+    /// Creates the rules from the sides and the finality of the formulas, in their order.
+    ///
+    /// The formulas with variables are searched for one by one, but only until the automaton
+    /// selects a fixed formula that precedes them, unless the occurrences are selected by their position.
+    pub(crate) fn with_sides<'a, I>(formulas: I) -> Self
+    where
+        I: IntoIterator<Item = (RuleSides<'a, U>, bool)>,
+        U: 'a,
+    {
+        let mut units = Vec::new();
+        let mut patterns = Vec::new();
+
+        let formulas: Vec<_> = formulas
+            .into_iter()
+            .enumerate()
+            .map(|(formula_index, (sides, is_final))| {
+                let mut append = |part: &[U]| {
+                    let start = units.len();
+                    units.extend_from_slice(part);
+                    start..units.len()
+                };
+
+                let (left, right) = match sides {
+                    RuleSides::Literal(left, right) => (append(left), append(right)),
+                    RuleSides::Pattern(pattern) => {
+                        patterns.push((formula_index, pattern));
+                        (0..0, 0..0)
+                    }
+                };
+
+                Rule {
+                    left,
+                    right,
+                    is_final,
+                }
+            })
+            .collect();
+
+        let automaton_formulas: Vec<_> = (0..formulas.len())
+            .filter(|formula_index| {
+                patterns
+                    .binary_search_by_key(formula_index, |&(pattern_formula, _)| pattern_formula)
+                    .is_err()
+            })
+            .collect();
+
+        let matcher = FormulaMatcher::new(
+            automaton_formulas
+                .iter()
+                .map(|&formula_index| &units[formulas[formula_index].left.clone()]),
+        );
+
+        Self {
+            matcher,
+            units,
+            formulas,
+            automaton_formulas,
+            patterns,
+            unit_widths: None,
+            strategy: MatchStrategy::default(),
+            stochastic_selection: None,
+            priority_groups: None,
+        }
+    }
+
+    /// Chooses the formula to apply at random among the applicable ones, with the probabilities
+    /// proportional to the weights of the formulas, in their order.
+    ///
+    /// The occurrence of the chosen formula is still selected by the strategy.
+    pub(crate) fn with_stochastic_selection(mut self, seed: u64, weights: Vec<u64>) -> Self {
+        debug_assert_eq!(self.formulas.len(), weights.len());
+
+        _ = self.stochastic_selection.insert(StochasticSelection {
+            random: SplitMix64::new(seed),
+            weights,
+        });
+        self
+    }
+
+    /// The seed of the randomness, if the formula to apply is chosen at random.
+    pub(crate) fn seed(&self) -> Option<u64> {
+        self.stochastic_selection
+            .as_ref()
+            .map(|selection| selection.random.seed())
+    }
+
+    /// Splits the formulas into groups of equal priority, given the group of each formula in their order.
+    ///
+    /// The first group that has an applicable formula wins, and the strategy selects the occurrence
+    /// that comes first by its position among the occurrences of the formulas of the group instead of their order.
+    pub(crate) fn with_priority_groups(
+        mut self,
+        groups: Vec<usize>,
+        tie_break: GroupTieBreak,
+    ) -> Self {
+        debug_assert_eq!(self.formulas.len(), groups.len());
+
+        _ = self
+            .priority_groups
+            .insert(PriorityGroups { groups, tie_break });
+        self
+    }
+
+    /// Sets the way the formula to apply and its occurrence are selected.
+    pub(crate) fn with_strategy(mut self, strategy: MatchStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Declares that the units are dense codes below the number of widths, each standing for its width in bytes.
+    ///
+    /// The automaton switches to a transition table indexed by the codes,
+    /// and the lengths of the words in bytes are measured with the widths instead of the number of units.
+    pub(crate) fn with_dense_units(mut self, unit_widths: Vec<usize>) -> Self {
+        self.matcher = self.matcher.with_dense_transitions(unit_widths.len());
+        _ = self.unit_widths.insert(unit_widths);
+        self
+    }
+
+    /// The left side of the formula.
+    pub(crate) fn left(&self, formula_index: usize) -> &[U] {
+        &self.units[self.formulas[formula_index].left.clone()]
+    }
+
+    /// The right side of the formula.
+    pub(crate) fn right(&self, formula_index: usize) -> &[U] {
+        &self.units[self.formulas[formula_index].right.clone()]
+    }
+
+    /// Reports whether the formula stops the algorithm.
+    pub(crate) fn is_final(&self, formula_index: usize) -> bool {
+        self.formulas[formula_index].is_final
+    }
+
+    /// The number of formulas.
+    pub(crate) fn len(&self) -> usize {
+        self.formulas.len()
+    }
+
+    /// Finds every way to rewrite the word with a formula at one of its occurrences,
+    /// ordered by the formulas and then by the positions of the occurrences.
+    ///
+    /// The finality of the formulas, the strategy and the order of the formulas do not restrict the rewrites.
+    pub(crate) fn rewrites(&self, word: &WordBuffer<U>) -> Vec<Rewrite<U>> {
+        let mut rewrites = Vec::new();
+
+        let rewrite_at = |formula_index: usize, start: usize| Rewrite {
+            formula_index,
+            range: start..start + self.left(formula_index).len(),
+            replacement: self.right(formula_index).to_vec(),
+        };
+
+        self.matcher.for_each_occurrence(
+            word.units(),
+            0,
+            |PatternMatch {
+                 pattern_index,
+                 start,
+             }| {
+                rewrites.push(rewrite_at(self.automaton_formulas[pattern_index], start))
+            },
+        );
+
+        for pattern_index in self.matcher.empty_patterns() {
+            rewrites.extend(
+                (0..=word.len())
+                    .filter(|&position| {
+                        position == word.len() || word.unit(position).starts_character()
+                    })
+                    .map(|position| rewrite_at(self.automaton_formulas[pattern_index], position)),
+            );
+        }
+
+        for (formula_index, pattern) in &self.patterns {
+            rewrites.extend(pattern.find_all(word).into_iter().map(
+                |PatternOccurrence { range, replacement }| Rewrite {
+                    formula_index: *formula_index,
+                    range,
+                    replacement,
+                },
+            ));
+        }
+
+        // The sort is stable, so the bindings of the same occurrence keep the order they are found in.
+        rewrites.sort_by_key(|rewrite| (rewrite.formula_index, rewrite.range.start));
+
+        rewrites
+    }
+
+    /// The length of the units in bytes.
+    pub(crate) fn byte_len<'a, I>(&self, units: I) -> usize
+    where
+        I: IntoIterator<Item = &'a U>,
+        U: 'a,
+    {
+        match &self.unit_widths {
+            Some(unit_widths) => units
+                .into_iter()
+                .map(|unit| unit_widths[unit.index()])
+                .sum(),
+            None => units.into_iter().count(),
+        }
+    }
+}
+
+/// A way to rewrite a word with a formula at one of its occurrences.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Rewrite<U> {
+    pub(crate) formula_index: usize,
+    /// The units of the word the occurrence takes.
+    pub(crate) range: Range<usize>,
+    pub(crate) replacement: Vec<U>,
+}
+
+/// The state of a running application of rules, the word is rewritten in place.
+#[derive(Debug, Clone)]
+pub(crate) struct Execution<U> {
+    pub(crate) word: WordBuffer<U>,
+    index: Option<MatchIndex>,
+    bytes: usize,
+    /// The number of steps done to the word, the randomness of each step is drawn at its position.
+    steps_done: u64,
+}
+
+/// The outcome of a single step of an execution.
+#[derive(Debug, Clone)]
+pub(crate) struct StepOutcome<U> {
+    pub(crate) applied_formula: Option<usize>,
+    /// The position in units where the substitution has been made.
+    pub(crate) start: usize,
+    pub(crate) is_final: bool,
+    /// The substitution made by a formula with variables, the fixed formulas replace their left sides
+    /// with their right sides.
+    pub(crate) bound: Option<BoundSubstitution<U>>,
+    /// The applicable formulas the applied one has been chosen from at random, in their order.
+    pub(crate) alternatives: Option<Vec<usize>>,
+}
+
+/// An occurrence of a formula that may be applied.
+#[derive(Debug)]
+enum Candidate<U> {
+    Fixed {
+        formula_index: usize,
+        start: usize,
+    },
+    Bound {
+        formula_index: usize,
+        occurrence: PatternOccurrence<U>,
+    },
+}
+
+impl<U> Candidate<U> {
+    fn formula_index(&self) -> usize {
+        match self {
+            Self::Fixed { formula_index, .. } | Self::Bound { formula_index, .. } => *formula_index,
+        }
+    }
+
+    /// The index of the formula and the start of the occurrence, as the strategies compare them.
+    fn key(&self) -> (usize, usize) {
+        match self {
+            Self::Fixed {
+                formula_index,
+                start,
+            } => (*formula_index, *start),
+            Self::Bound {
+                formula_index,
+                occurrence,
+            } => (*formula_index, occurrence.range.start),
+        }
+    }
+
+    /// The length of the occurrence in units.
+    fn len(&self, rules: &Rules<U>) -> usize
+    where
+        U: Unit,
+    {
+        match self {
+            Self::Fixed { formula_index, .. } => rules.left(*formula_index).len(),
+            Self::Bound { occurrence, .. } => occurrence.range.len(),
+        }
+    }
+}
+
+impl PriorityGroups {
+    /// Keeps the candidates of the first group that has any, the candidates are in the order of the formulas.
+    fn first_applicable<U>(&self, mut candidates: Vec<Candidate<U>>) -> Vec<Candidate<U>> {
+        if let Some(first) = candidates
+            .first()
+            .map(|candidate| self.groups[candidate.formula_index()])
+        {
+            candidates.retain(|candidate| self.groups[candidate.formula_index()] == first);
+        }
+
+        candidates
+    }
+
+    /// Selects the candidate the strategy prefers, comparing the groups of the formulas instead of their indices.
+    fn select<U: Unit>(
+        &self,
+        rules: &Rules<U>,
+        candidates: Vec<Candidate<U>>,
+    ) -> Option<Candidate<U>> {
+        candidates.into_iter().min_by(|candidate, other| {
+            let (index, start) = candidate.key();
+            let (other_index, other_start) = other.key();
+
+            let group = self.groups[index].cmp(&self.groups[other_index]);
+            let position = match rules.strategy {
+                MatchStrategy::Rightmost => other_start.cmp(&start),
+                MatchStrategy::Leftmost | MatchStrategy::PositionFirst => start.cmp(&other_start),
+            };
+            let tie = match self.tie_break {
+                GroupTieBreak::FirstFormula => Ordering::Equal,
+                GroupTieBreak::LongestMatch => other.len(rules).cmp(&candidate.len(rules)),
+            };
+
+            match rules.strategy {
+                MatchStrategy::PositionFirst => position.then(group),
+                MatchStrategy::Leftmost | MatchStrategy::Rightmost => group.then(position),
+            }
+            .then(tie)
+            .then(index.cmp(&other_index))
+        })
+    }
+}
+
+impl StochasticSelection {
+    /// Chooses a candidate with the probability proportional to the weight of its formula.
+    fn choose<U>(&self, candidates: Vec<Candidate<U>>, position: u64) -> Option<Candidate<U>> {
+        let weight = |candidate: &Candidate<U>| self.weights[candidate.formula_index()];
+
+        let total = candidates.iter().map(weight).sum();
+        let mut draw = self.random.below_at(position, total);
+
+        candidates.into_iter().find(|candidate| {
+            if draw < weight(candidate) {
+                true
+            } else {
+                draw -= weight(candidate);
+                false
+            }
+        })
+    }
+}
+
+/// The units replaced by a formula with variables and the number of units it has inserted.
+#[derive(Debug, Clone)]
+pub(crate) struct BoundSubstitution<U> {
+    pub(crate) matched: Vec<U>,
+    pub(crate) inserted: usize,
+}
+
+impl<U: Unit> Execution<U> {
+    /// Creates an execution that scans the whole word on each step, which is the cheapest way to do a single step.
+    pub(crate) fn new(rules: &Rules<U>, word: &[U]) -> Self {
+        Self {
+            word: WordBuffer::new(word),
+            index: None,
+            bytes: rules.byte_len(word),
+            steps_done: 0,
+        }
+    }
+
+    /// Creates an execution that only rescans the neighbourhood of each substitution.
+    pub(crate) fn incremental(rules: &Rules<U>, word: &[U]) -> Self {
+        let buffer = WordBuffer::new(word);
+        let index = MatchIndex::new(&rules.matcher, &buffer);
+
+        Self {
+            word: buffer,
+            index: Some(index),
+            bytes: rules.byte_len(word),
+            steps_done: 0,
+        }
+    }
+
+    /// Counts the steps done to the word before the execution has been created,
+    /// so the resumed application draws the same randomness as the uninterrupted one.
+    pub(crate) fn after_steps(mut self, steps_done: u64) -> Self {
+        self.steps_done = steps_done;
+        self
+    }
+
+    /// The number of bytes before the position in units.
+    pub(crate) fn byte_len_before(&self, rules: &Rules<U>, position: usize) -> usize {
+        match rules.unit_widths {
+            Some(_) => rules.byte_len(self.word.units_in(0..position)),
+            None => position,
+        }
+    }
+
+    /// Checks whether the word is longer than the limits allow.
+    fn exceeds(&self, limits: &ExecutionLimits) -> bool {
+        limits.is_exceeded_by(self.word.char_count(), self.bytes)
+    }
+
+    /// The number of bytes in the range of units.
+    pub(crate) fn byte_len_in(&self, rules: &Rules<U>, range: Range<usize>) -> usize {
+        rules.byte_len(self.word.units_in(range))
+    }
+
+    /// Applies the formula the strategy of the rules selects to the word,
+    /// or the formula chosen at random among the applicable ones of the first applicable group.
+    pub(crate) fn step(&mut self, rules: &Rules<U>) -> StepOutcome<U> {
+        let (selected, alternatives) = match (&rules.stochastic_selection, &rules.priority_groups) {
+            (Some(selection), priority_groups) => {
+                let candidates = self.find_applicable(rules);
+                let candidates = match priority_groups {
+                    Some(priority_groups) => priority_groups.first_applicable(candidates),
+                    None => candidates,
+                };
+                let alternatives = candidates.iter().map(Candidate::formula_index).collect();
+
+                (
+                    selection.choose(candidates, self.steps_done),
+                    Some(alternatives),
+                )
+            }
+            (None, Some(priority_groups)) => (
+                priority_groups.select(rules, self.find_applicable(rules)),
+                None,
+            ),
+            (None, None) => (self.find_selected(rules), None),
+        };
+
+        self.steps_done += 1;
+
+        match selected {
+            Some(Candidate::Bound {
+                formula_index,
+                occurrence: PatternOccurrence { range, replacement },
+            }) => {
+                let matched = self.word.units_in(range.clone()).copied().collect();
+
+                self.replace(rules, range.clone(), &replacement);
+
+                StepOutcome {
+                    applied_formula: Some(formula_index),
+                    start: range.start,
+                    is_final: rules.is_final(formula_index),
+                    bound: Some(BoundSubstitution {
+                        matched,
+                        inserted: replacement.len(),
+                    }),
+                    alternatives,
+                }
+            }
+            Some(Candidate::Fixed {
+                formula_index,
+                start,
+            }) => {
+                let range = start..start + rules.left(formula_index).len();
+
+                self.replace(rules, range, rules.right(formula_index));
+
+                StepOutcome {
+                    applied_formula: Some(formula_index),
+                    start,
+                    is_final: rules.is_final(formula_index),
+                    bound: None,
+                    alternatives,
+                }
+            }
+            None => StepOutcome {
+                applied_formula: None,
+                start: 0,
+                is_final: true,
+                bound: None,
+                alternatives: None,
+            },
+        }
+    }
+
+    /// Finds the occurrence the strategy of the rules selects among the occurrences of all formulas.
+    fn find_selected(&self, rules: &Rules<U>) -> Option<Candidate<U>> {
+        let strategy = rules.strategy;
+
+        let mut selected = match &self.index {
+            Some(index) => index.find(&rules.matcher, strategy),
+            None => rules.matcher.find(self.word.units(), strategy),
+        }
+        .map(
+            |PatternMatch {
+                 pattern_index,
+                 start,
+             }| Candidate::Fixed {
+                formula_index: rules.automaton_formulas[pattern_index],
+                start,
+            },
+        );
+
+        for (formula_index, pattern) in &rules.patterns {
+            // Only the occurrences that start first may come from the formulas that follow the selected one.
+            if strategy != MatchStrategy::PositionFirst
+                && selected
+                    .as_ref()
+                    .is_some_and(|selected| selected.formula_index() < *formula_index)
+            {
+                break;
+            }
+
+            if let Some(occurrence) = self.find_pattern(pattern, strategy) {
+                let candidate = Candidate::Bound {
+                    formula_index: *formula_index,
+                    occurrence,
+                };
+
+                if selected
+                    .as_ref()
+                    .is_none_or(|selected| strategy.prefers(candidate.key(), selected.key()))
+                {
+                    selected = Some(candidate);
+                }
+            }
+        }
+
+        selected
+    }
+
+    /// Finds the occurrence the strategy of the rules selects for each applicable formula, in the order of the formulas.
+    fn find_applicable(&self, rules: &Rules<U>) -> Vec<Candidate<U>> {
+        let strategy = rules.strategy;
+
+        let fixed = match &self.index {
+            Some(index) => index.find_each(&rules.matcher, strategy),
+            None => rules.matcher.find_each(self.word.units(), strategy),
+        };
+
+        let mut candidates: Vec<_> = fixed
+            .into_iter()
+            .map(
+                |PatternMatch {
+                     pattern_index,
+                     start,
+                 }| Candidate::Fixed {
+                    formula_index: rules.automaton_formulas[pattern_index],
+                    start,
+                },
+            )
+            .chain(
+                rules
+                    .patterns
+                    .iter()
+                    .filter_map(|(formula_index, pattern)| {
+                        self.find_pattern(pattern, strategy)
+                            .map(|occurrence| Candidate::Bound {
+                                formula_index: *formula_index,
+                                occurrence,
+                            })
+                    }),
+            )
+            .collect();
+
+        candidates.sort_unstable_by_key(Candidate::formula_index);
+
+        candidates
+    }
+
+    fn find_pattern(
+        &self,
+        pattern: &Pattern<U>,
+        strategy: MatchStrategy,
+    ) -> Option<PatternOccurrence<U>> {
+        match strategy {
+            MatchStrategy::Rightmost => pattern.find_last(&self.word),
+            MatchStrategy::Leftmost | MatchStrategy::PositionFirst => pattern.find(&self.word),
+        }
+    }
+
+    /// Reverts the substitution made by a step.
+    pub(crate) fn undo(&mut self, rules: &Rules<U>, outcome: &StepOutcome<U>) {
+        self.steps_done = self.steps_done.saturating_sub(1);
+
+        match (&outcome.bound, outcome.applied_formula) {
+            (Some(BoundSubstitution { matched, inserted }), _) => {
+                self.replace(rules, outcome.start..outcome.start + inserted, matched);
+            }
+            (None, Some(formula_index)) => {
+                let range = outcome.start..outcome.start + rules.right(formula_index).len();
+
+                self.replace(rules, range, rules.left(formula_index));
+            }
+            (None, None) => {}
+        }
+    }
+
+    fn replace(&mut self, rules: &Rules<U>, range: Range<usize>, replacement: &[U]) {
+        self.bytes -= rules.byte_len(self.word.units_in(range.clone()));
+        self.bytes += rules.byte_len(replacement);
+
+        match &mut self.index {
+            Some(index) => index.replace(&rules.matcher, &mut self.word, range, replacement),
+            None => self.word.replace(range, replacement),
+        }
+    }
+}
+
+/// The way a run of an execution ends.
+#[derive(Debug)]
+pub(crate) enum RunOutcome<C> {
+    /// The algorithm is completed.
+    Completed { steps_done: u64 },
+    /// A limit is exhausted before the algorithm is completed.
+    Interrupted {
+        limit: ExhaustedLimit,
+        steps_done: u64,
+        last_applied_formula: Option<usize>,
+    },
+    /// The observer of the steps has found a cycle.
+    CycleDetected(C),
+}
+
+/// Does the steps until the algorithm is completed, a limit is exhausted, or a cycle is detected.
+///
+/// The steps are counted from the start of the run, and the final word is accepted even if it is too long.
+pub(crate) fn run<U, C, F>(
+    rules: &Rules<U>,
+    execution: &mut Execution<U>,
+    limits: &ExecutionLimits,
+    mut detect_cycle: F,
+) -> RunOutcome<C>
+where
+    U: Unit,
+    F: FnMut(&Execution<U>) -> Option<C>,
+{
+    let mut steps_done = 0;
+    let mut last_applied_formula = None;
+
+    if execution.exceeds(limits) {
+        return RunOutcome::Interrupted {
+            limit: ExhaustedLimit::WordLength,
+            steps_done,
+            last_applied_formula,
+        };
+    }
+
+    loop {
+        if let Some(limit) = limits.find_exhausted_before_step(steps_done) {
+            return RunOutcome::Interrupted {
+                limit,
+                steps_done,
+                last_applied_formula,
+            };
+        }
+
+        let outcome = execution.step(rules);
+
+        steps_done += 1;
+
+        if outcome.is_final {
+            return RunOutcome::Completed { steps_done };
+        }
+
+        last_applied_formula = outcome.applied_formula;
+
+        if execution.exceeds(limits) {
+            return RunOutcome::Interrupted {
+                limit: ExhaustedLimit::WordLength,
+                steps_done,
+                last_applied_formula,
+            };
+        }
+
+        if let Some(cycle) = detect_cycle(execution) {
+            return RunOutcome::CycleDetected(cycle);
+        }
+    }
+}
+
+/// Brent's cycle detection, only a single additional word is kept in memory.
+#[derive(Debug)]
+pub(crate) struct CycleDetector<U> {
+    saved_word: WordBuffer<U>,
+    power: u64,
+    period: u64,
+}
+
+impl<U: Unit> CycleDetector<U> {
+    pub(crate) fn new(word: &[U]) -> Self {
+        Self {
+            saved_word: WordBuffer::new(word),
+            power: 1,
+            period: 0,
+        }
+    }
+
+    /// Compares the word after another step with the saved one.
+    ///
+    /// # Returns
+    /// The period of the cycle, if the word repeats the saved one.
+    pub(crate) fn observe(&mut self, word: &WordBuffer<U>) -> Option<u64> {
+        self.period += 1;
+
+        if *word == self.saved_word {
+            return Some(self.period);
+        }
+
+        if self.period == self.power {
+            self.saved_word = word.clone();
+            self.power *= 2;
+            self.period = 0;
+        }
+
+        None
+    }
+}
+
+/// Finds the first step of a cycle with the known period and collects the words of the cycle.
+pub(crate) fn locate_cycle<U: Unit>(
+    rules: &Rules<U>,
+    word: &[U],
+    period: u64,
+) -> (u64, Vec<WordBuffer<U>>) {
+    let mut tortoise = Execution::incremental(rules, word);
+    let mut hare = Execution::incremental(rules, word);
+
+    for _ in 0..period {
+        hare.step(rules);
+    }
+
+    let mut start_step = 0;
+
+    while tortoise.word != hare.word {
+        tortoise.step(rules);
+        hare.step(rules);
+        start_step += 1;
+    }
+
+    let words = (0..period)
+        .map(|_| {
+            let word = tortoise.word.clone();
+            tortoise.step(rules);
+            word
+        })
+        .collect();
+
+    (start_step, words)
+}
+
+/// The progress of a step-by-step application, shared by the iterators.
+#[derive(Debug)]
+pub(crate) struct Progress<U> {
+    pub(crate) execution: Execution<U>,
+    pub(crate) is_completed: bool,
+    limits: ExecutionLimits,
+    steps_done_before: u64,
+    steps_done: u64,
+    pub(crate) last_applied_formula: Option<usize>,
+    pub(crate) exhausted_limit: Option<ExhaustedLimit>,
+}
+
+impl<U: Unit> Progress<U> {
+    pub(crate) fn new(rules: &Rules<U>, word: &[U], limits: ExecutionLimits) -> Self {
+        Self {
+            execution: Execution::incremental(rules, word),
+            is_completed: false,
+            limits,
+            steps_done_before: 0,
+            steps_done: 0,
+            last_applied_formula: None,
+            exhausted_limit: None,
+        }
+    }
+
+    /// The number of steps done, including the ones done before the progress has been restored.
+    pub(crate) fn total_steps_done(&self) -> u64 {
+        self.steps_done_before.saturating_add(self.steps_done)
+    }
+
+    /// Does the next step unless the algorithm is completed or a limit is exhausted.
+    pub(crate) fn advance(&mut self, rules: &Rules<U>) -> Option<StepOutcome<U>> {
+        if self.is_completed || self.exhausted_limit.is_some() {
+            return None;
+        }
+
+        if let Some(limit) = self.limits.find_exhausted_before_step(self.steps_done) {
+            self.exhausted_limit = Some(limit);
+            return None;
+        }
+
+        let outcome = self.execution.step(rules);
+
+        self.steps_done += 1;
+        self.last_applied_formula = outcome.applied_formula;
+
+        if outcome.is_final {
+            self.is_completed = true;
+        } else if self.execution.exceeds(&self.limits) {
+            self.exhausted_limit = Some(ExhaustedLimit::WordLength);
+        }
+
+        Some(outcome)
+    }
+
+    /// Counts the steps done before the progress has been restored.
+    pub(crate) fn resume_after(&mut self, steps_done: u64) {
+        self.steps_done_before = steps_done;
+        self.execution.steps_done = steps_done;
+    }
+
+    /// Reverts the last step, the outcome of the step before it is needed to restore the last applied formula.
+    pub(crate) fn step_back(
+        &mut self,
+        rules: &Rules<U>,
+        outcome: &StepOutcome<U>,
+        previous_outcome: Option<&StepOutcome<U>>,
+    ) {
+        self.execution.undo(rules, outcome);
+
+        self.steps_done = self.steps_done.saturating_sub(1);
+        self.last_applied_formula = previous_outcome.and_then(|outcome| outcome.applied_formula);
+        self.is_completed = false;
+        self.exhausted_limit = None;
+    }
+}
@@ -0,0 +1,137 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::*;
+
+fn create_rules(formulas: &[(&str, &str, bool)]) -> Rules<u8> {
+    Rules::new(
+        formulas
+            .iter()
+            .map(|&(left, right, is_final)| (left.as_bytes(), right.as_bytes(), is_final)),
+    )
+}
+
+#[test]
+fn the_rules_keep_the_sides_of_the_formulas() {
+    let rules = create_rules(&[("ab", "c", false), ("", "d", true)]);
+
+    assert_eq!(2, rules.len());
+    assert_eq!(b"ab", rules.left(0));
+    assert_eq!(b"c", rules.right(0));
+    assert!(!rules.is_final(0));
+    assert_eq!(b"", rules.left(1));
+    assert!(rules.is_final(1));
+}
+
+#[test]
+fn a_step_can_be_undone() {
+    let rules = create_rules(&[("ab", "ccc", false)]);
+    let mut execution = Execution::incremental(&rules, b"aabb");
+
+    let outcome = execution.step(&rules);
+
+    assert_eq!(b"acccb".to_vec(), execution.word.to_vec());
+
+    execution.undo(&rules, &outcome);
+
+    assert_eq!(b"aabb".to_vec(), execution.word.to_vec());
+    assert_eq!(Some(0), execution.step(&rules).applied_formula);
+}
+
+#[test]
+fn a_run_over_interned_symbols_is_completed() {
+    let left = [7_u32, 7];
+    let right = [100_000_u32];
+    let rules = Rules::new([(&left[..], &right[..], false)]);
+
+    let mut execution = Execution::incremental(&rules, &[7, 7, 7, 7, 7]);
+
+    let outcome = run::<_, (), _>(&rules, &mut execution, &ExecutionLimits::new(), |_| None);
+
+    assert!(matches!(outcome, RunOutcome::Completed { steps_done: 3 }));
+    assert_eq!(vec![100_000, 100_000, 7], execution.word.to_vec());
+}
+
+#[test]
+fn a_run_reports_the_exhausted_limit() {
+    let rules = create_rules(&[("a", "aa", false)]);
+    let mut execution = Execution::incremental(&rules, b"a");
+    let limits = ExecutionLimits::new().with_word_length_in_bytes(4);
+
+    let outcome = run::<_, (), _>(&rules, &mut execution, &limits, |_| None);
+
+    assert!(matches!(
+        outcome,
+        RunOutcome::Interrupted {
+            limit: ExhaustedLimit::WordLength,
+            steps_done: 4,
+            last_applied_formula: Some(0)
+        }
+    ));
+}
+
+#[test]
+fn a_cycle_is_located_after_it_is_detected() {
+    let rules = create_rules(&[("c", "a", false), ("ab", "ba", false), ("ba", "cb", false)]);
+    let mut execution = Execution::incremental(&rules, b"abbb");
+    let mut detector = CycleDetector::new(b"abbb");
+
+    let outcome = run(
+        &rules,
+        &mut execution,
+        &ExecutionLimits::new(),
+        |execution| detector.observe(&execution.word),
+    );
+
+    let RunOutcome::CycleDetected(period) = outcome else {
+        panic!("unexpected outcome: {outcome:?}");
+    };
+
+    let (start_step, words) = locate_cycle(&rules, b"abbb", period);
+
+    assert_eq!(2, start_step);
+    assert_eq!(
+        vec![b"bbab".to_vec(), b"bbba".to_vec(), b"bbcb".to_vec()],
+        words.iter().map(WordBuffer::to_vec).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn the_rewrites_cover_every_occurrence_of_every_formula() {
+    let rules = create_rules(&[("b", "c", true), ("", "d", false), ("ab", "", false)]);
+
+    let rewrites: Vec<_> = rules
+        .rewrites(&WordBuffer::new(b"abb"))
+        .into_iter()
+        .map(|rewrite| (rewrite.formula_index, rewrite.range, rewrite.replacement))
+        .collect();
+
+    assert_eq!(
+        vec![
+            (0, 1..2, b"c".to_vec()),
+            (0, 2..3, b"c".to_vec()),
+            (1, 0..0, b"d".to_vec()),
+            (1, 1..1, b"d".to_vec()),
+            (1, 2..2, b"d".to_vec()),
+            (1, 3..3, b"d".to_vec()),
+            (2, 0..2, Vec::new()),
+        ],
+        rewrites
+    );
+}
@@ -0,0 +1,574 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! [SemiThueExplorer](SemiThueExplorer), the search through every word the formulas of a scheme
+//! can rewrite a word into.
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::{HashMap, VecDeque},
+    ops::Range,
+};
+
+use super::{
+    engine::Rewrite,
+    word_buffer::{Unit, WordBuffer},
+    AlgorithmScheme, AlgorithmSchemeInputValidationError, AppliedSubstitution,
+};
+
+/// An explorer of the rewriting relation the formulas of a scheme define, as if they were the rules of a semi-Thue system.
+///
+/// A word can be rewritten by any formula at any of its occurrences, so the words reachable from the input
+/// form a graph instead of a single sequence. The final formulas do not stop the rewriting,
+/// and the strategy, the priority groups and the random choice of the scheme are ignored,
+/// while the variables and the anchors keep their meaning.
+///
+/// The graph may be infinite, so the search is bounded by the [limits](ExplorationLimits).
+/// Each word is visited once, when it is reached for the first time.
+///
+/// # Example
+/// ```rust
+/// # use std::str;
+/// use markovalgorithms::prelude::*;
+///
+/// let scheme = AlgorithmSchemeBuilder::new()
+///     .with_alphabet(str::parse("ab").unwrap())
+///     .build_with_formula_definitions(["ab→⋅ba"].into_iter())
+///     .unwrap();
+///
+/// let exploration = scheme.explorer().explore("abab", ExplorationLimits::new()).unwrap();
+///
+/// assert!(exploration.is_complete());
+/// assert_eq!(
+///     vec!["abab", "baab", "abba", "baba", "bbaa"],
+///     exploration.words().collect::<Vec<_>>()
+/// );
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SemiThueExplorer<'a> {
+    scheme: &'a AlgorithmScheme,
+    order: ExplorationOrder,
+}
+
+/// The order the words are visited in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ExplorationOrder {
+    /// The words closer to the input are visited first, so the paths to the words are the shortest ones.
+    #[default]
+    BreadthFirst,
+    /// The words reached from the last visited word are visited first.
+    DepthFirst,
+}
+
+/// The limits of the search through the reachable words.
+///
+/// The limits that are not set are not checked, so the default value does not limit the search at all.
+///
+/// # Example
+/// ```rust
+/// # use std::str;
+/// use markovalgorithms::prelude::*;
+///
+/// let scheme = AlgorithmSchemeBuilder::new()
+///     .with_alphabet(str::parse("a").unwrap())
+///     .build_with_formula_definitions(["a→aa"].into_iter())
+///     .unwrap();
+///
+/// let limits = ExplorationLimits::new().with_word_length_in_characters(3);
+/// let exploration = scheme.explorer().explore("a", limits).unwrap();
+///
+/// assert_eq!(vec!["a", "aa", "aaa"], exploration.words().collect::<Vec<_>>());
+/// assert_eq!(Some(ExhaustedExplorationLimit::WordLength), exploration.exhausted_limit());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ExplorationLimits {
+    depth: Option<u64>,
+    visited_words: Option<usize>,
+    word_length: Option<usize>,
+}
+
+/// The limit that has stopped the search before every reachable word is visited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExhaustedExplorationLimit {
+    /// Some words are only reachable with more rewrites than allowed.
+    Depth,
+    /// The search is stopped because it has visited as many words as allowed.
+    VisitedWords,
+    /// Some words are longer than allowed.
+    WordLength,
+}
+
+/// The words visited by a search and the ways they have been reached.
+#[derive(Debug)]
+pub struct Exploration<'a> {
+    scheme: &'a AlgorithmScheme,
+    visited: Vec<VisitedWord>,
+    indices: HashMap<Vec<u8>, usize>,
+    exhausted_limit: Option<ExhaustedExplorationLimit>,
+}
+
+#[derive(Debug)]
+struct VisitedWord {
+    word: Vec<u8>,
+    depth: u64,
+    /// The rewrite the word has been reached with, the input word has none.
+    reached_with: Option<ReachingRewrite>,
+}
+
+#[derive(Debug)]
+struct ReachingRewrite {
+    previous: usize,
+    formula_index: usize,
+    /// The units of the previous word that have been replaced.
+    range: Range<usize>,
+    inserted: usize,
+}
+
+/// The answer to the question whether a word can be rewritten into another one.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Reachability<'a> {
+    /// The word is reachable along the path.
+    Reachable(RewritingPath<'a>),
+    /// Every reachable word has been visited and the word is not among them.
+    Unreachable,
+    /// The word has not been reached before the limit is exhausted.
+    Unknown(ExhaustedExplorationLimit),
+}
+
+/// The rewrites that lead from a word to another one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewritingPath<'a> {
+    start: String,
+    steps: Vec<RewritingStep<'a>>,
+}
+
+/// A single rewrite of a path and the word it produces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewritingStep<'a> {
+    word: String,
+    applied_formula_definition: &'a str,
+    substitution: AppliedSubstitution,
+}
+
+impl<'a> SemiThueExplorer<'a> {
+    pub(super) fn new(scheme: &'a AlgorithmScheme) -> Self {
+        Self {
+            scheme,
+            order: ExplorationOrder::default(),
+        }
+    }
+
+    /// Sets the order the words are visited in, the breadth-first search is used by default.
+    ///
+    /// May be called multiple times in order to replace the prior order.
+    pub fn with_order(mut self, order: ExplorationOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Visits the words reachable from the input until all of them are visited or a limit is exhausted.
+    ///
+    /// # Errors
+    /// Returns an [error](AlgorithmSchemeInputValidationError) if the input is not valid.
+    pub fn explore(
+        &self,
+        word: &str,
+        limits: ExplorationLimits,
+    ) -> Result<Exploration<'a>, AlgorithmSchemeInputValidationError> {
+        self.scheme.assert_valid_word(word)?;
+
+        let (exploration, _) = self.search(&self.scheme.encoding.encode(word), None, &limits);
+
+        Ok(exploration)
+    }
+
+    /// Checks whether the input can be rewritten into the target, and finds the path to the target if it can.
+    ///
+    /// The search stops as soon as the target is reached. The target may contain the characters of the extension.
+    ///
+    /// # Errors
+    /// Returns an [error](AlgorithmSchemeInputValidationError) if the input or the target is not valid.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::str;
+    /// use markovalgorithms::prelude::*;
+    ///
+    /// let scheme = AlgorithmSchemeBuilder::new()
+    ///     .with_alphabet(str::parse("abc").unwrap())
+    ///     .build_with_formula_definitions(["ab→ba", "b→c"].into_iter())
+    ///     .unwrap();
+    ///
+    /// let explorer = scheme.explorer();
+    ///
+    /// let Reachability::Reachable(path) = explorer.can_reach("aab", "caa", ExplorationLimits::new()).unwrap() else {
+    ///     panic!("The word is reachable.");
+    /// };
+    ///
+    /// assert_eq!(vec!["aab", "aba", "baa", "caa"], path.words().collect::<Vec<_>>());
+    /// assert_eq!(
+    ///     Reachability::Unreachable,
+    ///     explorer.can_reach("aab", "bba", ExplorationLimits::new()).unwrap()
+    /// );
+    /// ```
+    pub fn can_reach(
+        &self,
+        from: &str,
+        to: &str,
+        limits: ExplorationLimits,
+    ) -> Result<Reachability<'a>, AlgorithmSchemeInputValidationError> {
+        self.scheme.assert_valid_word(from)?;
+        self.scheme.assert_known_characters(to)?;
+
+        let encoding = &self.scheme.encoding;
+        let (exploration, target) =
+            self.search(&encoding.encode(from), Some(&encoding.encode(to)), &limits);
+
+        Ok(match (target, exploration.exhausted_limit) {
+            (Some(index), _) => Reachability::Reachable(exploration.path_to_index(index)),
+            (None, Some(limit)) => Reachability::Unknown(limit),
+            (None, None) => Reachability::Unreachable,
+        })
+    }
+
+    /// Visits the words reachable from the input, stopping early when the target is reached.
+    ///
+    /// # Returns
+    /// The visited words and the index of the target among them, if it has been reached.
+    fn search(
+        &self,
+        word: &[u8],
+        target: Option<&[u8]>,
+        limits: &ExplorationLimits,
+    ) -> (Exploration<'a>, Option<usize>) {
+        let mut exploration = Exploration {
+            scheme: self.scheme,
+            visited: Vec::new(),
+            indices: HashMap::new(),
+            exhausted_limit: None,
+        };
+
+        let start = exploration.visit(word.to_vec(), 0, None);
+
+        if target == Some(word) {
+            return (exploration, Some(start));
+        }
+
+        let mut frontier = VecDeque::from([start]);
+
+        while let Some(index) = match self.order {
+            ExplorationOrder::BreadthFirst => frontier.pop_front(),
+            ExplorationOrder::DepthFirst => frontier.pop_back(),
+        } {
+            let depth = exploration.visited[index].depth;
+            let buffer = WordBuffer::new(&exploration.visited[index].word);
+            let mut reached = Vec::new();
+
+            for Rewrite {
+                formula_index,
+                range,
+                replacement,
+            } in self.scheme.rules.rewrites(&buffer)
+            {
+                let (before, after) = {
+                    let word = &exploration.visited[index].word;
+                    (&word[..range.start], &word[range.end..])
+                };
+                let next = [before, &replacement, after].concat();
+
+                if exploration.indices.contains_key(&next) {
+                    continue;
+                }
+
+                if limits.depth.is_some_and(|limit| depth >= limit) {
+                    exploration.exhaust(ExhaustedExplorationLimit::Depth);
+                    continue;
+                }
+
+                if limits.word_length.is_some_and(|limit| {
+                    next.iter().filter(|unit| unit.starts_character()).count() > limit
+                }) {
+                    exploration.exhaust(ExhaustedExplorationLimit::WordLength);
+                    continue;
+                }
+
+                // The target is compared first, so it is not missed if it is found right as the limit is reached.
+                let is_target = target == Some(next.as_slice());
+
+                if !is_target
+                    && limits
+                        .visited_words
+                        .is_some_and(|limit| exploration.visited.len() >= limit)
+                {
+                    // The search cannot go on without visiting more words, so this limit is reported above the others.
+                    exploration.exhausted_limit = Some(ExhaustedExplorationLimit::VisitedWords);
+                    return (exploration, None);
+                }
+                let next_index = exploration.visit(
+                    next,
+                    depth + 1,
+                    Some(ReachingRewrite {
+                        previous: index,
+                        formula_index,
+                        range,
+                        inserted: replacement.len(),
+                    }),
+                );
+
+                if is_target {
+                    return (exploration, Some(next_index));
+                }
+
+                reached.push(next_index);
+            }
+
+            match self.order {
+                ExplorationOrder::BreadthFirst => frontier.extend(reached),
+                // The word reached with the first rewrite is taken from the back of the frontier first.
+                ExplorationOrder::DepthFirst => frontier.extend(reached.into_iter().rev()),
+            }
+        }
+
+        (exploration, None)
+    }
+}
+
+impl ExplorationLimits {
+    /// Creates limits that do not restrict the search.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the number of rewrites between the input and the visited words.
+    ///
+    /// May be called multiple times in order to replace the prior limit.
+    pub fn with_depth(mut self, depth: u64) -> Self {
+        _ = self.depth.insert(depth);
+        self
+    }
+
+    /// Limits the number of visited words, including the input.
+    ///
+    /// The target of [can_reach](SemiThueExplorer::can_reach) is still recognized
+    /// if it is the word found right after the limit is reached.
+    ///
+    /// May be called multiple times in order to replace the prior limit.
+    pub fn with_visited_words(mut self, visited_words: usize) -> Self {
+        _ = self.visited_words.insert(visited_words);
+        self
+    }
+
+    /// Limits the length of the visited words, measured in characters.
+    ///
+    /// May be called multiple times in order to replace the prior limit.
+    pub fn with_word_length_in_characters(mut self, characters: usize) -> Self {
+        _ = self.word_length.insert(characters);
+        self
+    }
+}
+
+impl<'a> Exploration<'a> {
+    /// The visited words in the order they have been visited in, starting with the input.
+    pub fn words(&self) -> impl Iterator<Item = String> + '_ {
+        self.visited
+            .iter()
+            .map(|visited| self.decode(&visited.word))
+    }
+
+    /// The number of visited words.
+    pub fn len(&self) -> usize {
+        self.visited.len()
+    }
+
+    /// Reports whether no words are visited, which never happens since the input is always visited.
+    pub fn is_empty(&self) -> bool {
+        self.visited.is_empty()
+    }
+
+    /// Checks whether the word has been visited.
+    pub fn contains(&self, word: &str) -> bool {
+        self.find(word).is_some()
+    }
+
+    /// The number of rewrites the word has been reached with, if it has been visited.
+    pub fn depth_of(&self, word: &str) -> Option<u64> {
+        self.find(word).map(|index| self.visited[index].depth)
+    }
+
+    /// The path the word has been reached along, if it has been visited.
+    pub fn path_to(&self, word: &str) -> Option<RewritingPath<'a>> {
+        self.find(word).map(|index| self.path_to_index(index))
+    }
+
+    /// The limit that has stopped the search before every reachable word is visited, if any.
+    pub fn exhausted_limit(&self) -> Option<ExhaustedExplorationLimit> {
+        self.exhausted_limit
+    }
+
+    /// Reports whether every word reachable from the input has been visited.
+    pub fn is_complete(&self) -> bool {
+        self.exhausted_limit.is_none()
+    }
+
+    fn visit(&mut self, word: Vec<u8>, depth: u64, reached_with: Option<ReachingRewrite>) -> usize {
+        let index = self.visited.len();
+
+        _ = self.indices.insert(word.clone(), index);
+        self.visited.push(VisitedWord {
+            word,
+            depth,
+            reached_with,
+        });
+
+        index
+    }
+
+    /// Records a limit that has hidden some words, the first recorded limit is kept.
+    fn exhaust(&mut self, limit: ExhaustedExplorationLimit) {
+        _ = self.exhausted_limit.get_or_insert(limit);
+    }
+
+    fn find(&self, word: &str) -> Option<usize> {
+        // The words with unknown characters cannot be encoded, and they are never reached anyway.
+        self.scheme.assert_known_characters(word).ok()?;
+
+        self.indices
+            .get(self.scheme.encoding.encode(word).as_ref())
+            .copied()
+    }
+
+    fn decode(&self, word: &[u8]) -> String {
+        self.scheme.encoding.decode(&WordBuffer::new(word))
+    }
+
+    fn path_to_index(&self, mut index: usize) -> RewritingPath<'a> {
+        let mut steps = Vec::new();
+
+        while let Some(rewrite) = &self.visited[index].reached_with {
+            steps.push(self.describe_rewrite(index, rewrite));
+            index = rewrite.previous;
+        }
+
+        steps.reverse();
+
+        RewritingPath {
+            start: self.decode(&self.visited[index].word),
+            steps,
+        }
+    }
+
+    /// Locates the rewrite in the previous word and in the word it has produced.
+    fn describe_rewrite(&self, index: usize, rewrite: &ReachingRewrite) -> RewritingStep<'a> {
+        let rules = &self.scheme.rules;
+        let previous = &self.visited[rewrite.previous].word;
+        let word = &self.visited[index].word;
+
+        let start = rewrite.range.start;
+        let inserted = &word[start..start + rewrite.inserted];
+        let count_characters =
+            |units: &[u8]| units.iter().filter(|unit| unit.starts_character()).count();
+
+        // The part of the word before the rewrite is the same in both words.
+        let start_byte = rules.byte_len(&previous[..start]);
+        let start_character = count_characters(&previous[..start]);
+        let matched = &previous[rewrite.range.clone()];
+
+        RewritingStep {
+            word: self.decode(word),
+            applied_formula_definition: self.scheme.substitution_formulas[rewrite.formula_index]
+                .view()
+                .peek_definition(&self.scheme.store),
+            substitution: AppliedSubstitution {
+                formula_index: rewrite.formula_index,
+                matched_bytes: start_byte..start_byte + rules.byte_len(matched),
+                matched_characters: start_character..start_character + count_characters(matched),
+                inserted_bytes: start_byte..start_byte + rules.byte_len(inserted),
+                inserted_characters: start_character..start_character + count_characters(inserted),
+            },
+        }
+    }
+}
+
+impl<'a> Reachability<'a> {
+    /// Reports whether the word is known to be reachable.
+    pub fn is_reachable(&self) -> bool {
+        matches!(self, Self::Reachable(_))
+    }
+
+    /// The path to the word, if it is reachable.
+    pub fn path(&self) -> Option<&RewritingPath<'a>> {
+        match self {
+            Self::Reachable(path) => Some(path),
+            Self::Unreachable | Self::Unknown(_) => None,
+        }
+    }
+}
+
+impl<'a> RewritingPath<'a> {
+    /// The word the path starts with.
+    pub fn start(&self) -> &str {
+        &self.start
+    }
+
+    /// The word the path ends with.
+    pub fn end(&self) -> &str {
+        self.steps.last().map_or(&self.start, |step| &step.word)
+    }
+
+    /// The rewrites along the path.
+    pub fn steps(&self) -> &[RewritingStep<'a>] {
+        &self.steps
+    }
+
+    /// The number of rewrites along the path.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Reports whether the path ends where it starts, without rewrites.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// The words along the path, starting with the first word.
+    pub fn words(&self) -> impl Iterator<Item = &str> {
+        [self.start.as_str()]
+            .into_iter()
+            .chain(self.steps.iter().map(|step| step.word.as_str()))
+    }
+}
+
+impl<'a> RewritingStep<'a> {
+    /// The word produced by the rewrite.
+    pub fn word(&self) -> &str {
+        &self.word
+    }
+
+    /// The definition of the formula that has rewritten the word.
+    pub fn applied_formula_definition(&self) -> &'a str {
+        self.applied_formula_definition
+    }
+
+    /// The place of the rewrite in the previous word and in the produced one.
+    pub fn substitution(&self) -> &AppliedSubstitution {
+        &self.substitution
+    }
+}
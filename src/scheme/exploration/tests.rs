@@ -0,0 +1,302 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::str::FromStr;
+
+use crate::prelude::{AlgorithmSchemeBuilder, Alphabet};
+
+use super::*;
+
+fn create_scheme(alphabet: &str, formula_definitions: &[&str]) -> AlgorithmScheme {
+    AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str(alphabet).unwrap())
+        .build_with_formula_definitions(formula_definitions.iter().copied())
+        .unwrap()
+}
+
+fn explore(scheme: &AlgorithmScheme, word: &str, limits: ExplorationLimits) -> Vec<String> {
+    scheme
+        .explorer()
+        .explore(word, limits)
+        .unwrap()
+        .words()
+        .collect()
+}
+
+#[test]
+fn every_formula_rewrites_every_occurrence() {
+    let scheme = create_scheme("abc", &["a→⋅c", "b→a"]);
+
+    let mut words = explore(&scheme, "ab", ExplorationLimits::new());
+    words.sort_unstable();
+
+    assert_eq!(vec!["aa", "ab", "ac", "ca", "cb", "cc"], words);
+}
+
+#[test]
+fn the_breadth_first_search_finds_the_shortest_paths() {
+    let scheme = create_scheme("ab", &["a→b", "bb→a"]);
+    let exploration = scheme
+        .explorer()
+        .explore("aa", ExplorationLimits::new())
+        .unwrap();
+
+    assert!(exploration.is_complete());
+    assert_eq!(Some(0), exploration.depth_of("aa"));
+    assert_eq!(Some(1), exploration.depth_of("ab"));
+    assert_eq!(Some(2), exploration.depth_of("bb"));
+    assert_eq!(Some(3), exploration.depth_of("a"));
+    assert_eq!(Some(4), exploration.depth_of("b"));
+    assert_eq!(4, exploration.path_to("b").unwrap().len());
+
+    let depths: Vec<_> = exploration
+        .words()
+        .map(|word| exploration.depth_of(&word).unwrap())
+        .collect();
+
+    assert!(depths.windows(2).all(|pair| pair[0] <= pair[1]));
+}
+
+#[test]
+fn the_depth_first_search_follows_the_first_rewrites() {
+    let scheme = create_scheme("abc", &["a→b", "b→c"]);
+
+    let words = scheme
+        .explorer()
+        .with_order(ExplorationOrder::DepthFirst)
+        .explore("aa", ExplorationLimits::new())
+        .unwrap()
+        .words()
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        vec!["aa", "ba", "ab", "bb", "ca", "cb", "bc", "cc", "ac"],
+        words
+    );
+}
+
+#[test]
+fn a_path_describes_the_rewrites() {
+    let scheme = create_scheme("нab", &["a→нн", "нb→⋅a"]);
+
+    let reachability = scheme
+        .explorer()
+        .can_reach("ab", "нa", ExplorationLimits::new())
+        .unwrap();
+    let path = reachability.path().unwrap();
+
+    assert_eq!("ab", path.start());
+    assert_eq!("нa", path.end());
+    assert_eq!(2, path.len());
+    assert_eq!(vec!["ab", "ннb", "нa"], path.words().collect::<Vec<_>>());
+
+    let step = &path.steps()[1];
+    let substitution = step.substitution();
+
+    assert_eq!("нb→⋅a", step.applied_formula_definition());
+    assert_eq!(1, substitution.formula_index());
+    assert_eq!(2..5, substitution.matched_bytes());
+    assert_eq!(1..3, substitution.matched_characters());
+    assert_eq!(2..3, substitution.inserted_bytes());
+    assert_eq!(1..2, substitution.inserted_characters());
+}
+
+#[test]
+fn the_input_is_reachable_from_itself() {
+    let scheme = create_scheme("ab", &["a→b"]);
+
+    let reachability = scheme
+        .explorer()
+        .can_reach("ab", "ab", ExplorationLimits::new())
+        .unwrap();
+
+    assert!(reachability.path().unwrap().is_empty());
+}
+
+#[test]
+fn the_unreachable_words_are_only_reported_after_a_complete_search() {
+    let scheme = create_scheme("ab", &["a→b", "bb→a"]);
+    let explorer = scheme.explorer();
+
+    assert_eq!(
+        Reachability::Unreachable,
+        explorer
+            .can_reach("aa", "aaa", ExplorationLimits::new())
+            .unwrap()
+    );
+    assert_eq!(
+        Reachability::Unknown(ExhaustedExplorationLimit::Depth),
+        explorer
+            .can_reach("aa", "b", ExplorationLimits::new().with_depth(3))
+            .unwrap()
+    );
+    assert!(explorer
+        .can_reach("aa", "b", ExplorationLimits::new().with_depth(4))
+        .unwrap()
+        .is_reachable());
+}
+
+#[test]
+fn the_depth_limit_is_only_reported_if_it_hides_some_words() {
+    let scheme = create_scheme("ab", &["a→b"]);
+    let explorer = scheme.explorer();
+
+    let exploration = explorer
+        .explore("a", ExplorationLimits::new().with_depth(1))
+        .unwrap();
+
+    assert!(exploration.is_complete());
+
+    let exploration = explorer
+        .explore("aa", ExplorationLimits::new().with_depth(1))
+        .unwrap();
+
+    assert_eq!(
+        vec!["aa", "ba", "ab"],
+        exploration.words().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        Some(ExhaustedExplorationLimit::Depth),
+        exploration.exhausted_limit()
+    );
+}
+
+#[test]
+fn the_search_stops_after_visiting_as_many_words_as_allowed() {
+    let scheme = create_scheme("a", &["a→aa"]);
+
+    let exploration = scheme
+        .explorer()
+        .explore(
+            "a",
+            ExplorationLimits::new()
+                .with_visited_words(5)
+                .with_word_length_in_characters(100),
+        )
+        .unwrap();
+
+    assert_eq!(5, exploration.len());
+    assert_eq!(
+        Some(ExhaustedExplorationLimit::VisitedWords),
+        exploration.exhausted_limit()
+    );
+    assert_eq!(
+        Reachability::Unknown(ExhaustedExplorationLimit::VisitedWords),
+        scheme
+            .explorer()
+            .can_reach(
+                "a",
+                "aaaaaaaa",
+                ExplorationLimits::new().with_visited_words(5)
+            )
+            .unwrap()
+    );
+}
+
+#[test]
+fn the_target_found_right_at_the_limit_of_visited_words_is_reachable() {
+    let scheme = create_scheme("a", &["a→aa"]);
+    let can_reach = |target: &str| {
+        scheme
+            .explorer()
+            .can_reach("a", target, ExplorationLimits::new().with_visited_words(5))
+            .unwrap()
+    };
+
+    assert_eq!(4, can_reach("aaaaa").path().unwrap().len());
+    assert_eq!(5, can_reach("aaaaaa").path().unwrap().len());
+    assert_eq!(
+        Reachability::Unknown(ExhaustedExplorationLimit::VisitedWords),
+        can_reach("aaaaaaa")
+    );
+}
+
+#[test]
+fn the_empty_left_side_rewrites_every_position() {
+    let scheme = create_scheme("ab", &["→a"]);
+
+    assert_eq!(
+        vec!["b", "ab", "ba", "aab", "aba", "baa"],
+        explore(
+            &scheme,
+            "b",
+            ExplorationLimits::new().with_word_length_in_characters(3)
+        )
+    );
+}
+
+#[test]
+fn the_variables_are_bound_in_every_possible_way() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("abc").unwrap())
+        .with_variables()
+        .with_anchors()
+        .build_with_formula_definitions(["^e.xs.y$→s.ye.x"].into_iter())
+        .unwrap();
+
+    let mut words = explore(&scheme, "abc", ExplorationLimits::new());
+    words.sort_unstable();
+
+    assert_eq!(vec!["abc", "bca", "cab"], words);
+
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("ab").unwrap())
+        .with_variables()
+        .build_with_formula_definitions(["e.xe.y→⋅e.ye.x"].into_iter())
+        .unwrap();
+
+    let mut words = explore(&scheme, "aab", ExplorationLimits::new());
+    words.sort_unstable();
+
+    assert_eq!(vec!["aab", "aba", "baa"], words);
+}
+
+#[test]
+fn the_words_are_validated() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("ab").unwrap().extend('*').unwrap())
+        .build_with_formula_definitions(["a→*"].into_iter())
+        .unwrap();
+    let explorer = scheme.explorer();
+
+    assert_eq!(
+        Some(AlgorithmSchemeInputValidationError::UnknownCharactersEncountered("c".to_owned())),
+        explorer.explore("ac", ExplorationLimits::new()).err()
+    );
+    assert_eq!(
+        Some(AlgorithmSchemeInputValidationError::ExtensionCharactersEncountered("*".to_owned())),
+        explorer
+            .can_reach("a*", "a", ExplorationLimits::new())
+            .err()
+    );
+    assert_eq!(
+        Some(AlgorithmSchemeInputValidationError::UnknownCharactersEncountered("c".to_owned())),
+        explorer.can_reach("a", "c", ExplorationLimits::new()).err()
+    );
+    assert!(explorer
+        .can_reach("ab", "*b", ExplorationLimits::new())
+        .unwrap()
+        .is_reachable());
+
+    let exploration = explorer.explore("ab", ExplorationLimits::new()).unwrap();
+
+    assert!(exploration.contains("*b"));
+    assert!(!exploration.contains("cb"));
+    assert_eq!(None, exploration.path_to("cb"));
+}
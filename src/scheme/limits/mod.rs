@@ -0,0 +1,243 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! [ExecutionLimits](ExecutionLimits) structure and the [CancellationToken](CancellationToken) it can observe.
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use super::{AlgorithmSchemeFullApplicationError, PartialApplicationResult};
+
+/// The limits of resources the application of a scheme is allowed to consume.
+///
+/// The limits that are not set are not checked, so the default value does not limit the application at all.
+///
+/// # Example
+/// Basic usage:
+/// ```rust
+/// # use std::{str, time::Duration};
+/// use markovalgorithms::prelude::*;
+///
+/// let scheme = AlgorithmSchemeBuilder::new()
+///     .with_alphabet(str::parse("a").unwrap())
+///     .build_with_formula_definitions(["a→aa"].into_iter())
+///     .unwrap();
+///
+/// let limits = ExecutionLimits::new()
+///     .with_steps(1_000_000)
+///     .with_word_length_in_characters(100)
+///     .with_timeout(Duration::from_secs(10));
+///
+/// let error = scheme.apply("a", limits).unwrap_err();
+///
+/// assert!(matches!(error, AlgorithmSchemeFullApplicationError::HitTheWordLengthLimit(_)));
+/// ```
+/// A plain number of steps can be used wherever the limits are expected:
+/// ```rust
+/// use markovalgorithms::prelude::*;
+///
+/// let limits: ExecutionLimits = 1_000.into();
+///
+/// assert_eq!(Some(1_000), limits.steps());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionLimits {
+    steps: Option<u64>,
+    word_length: Option<WordLength>,
+    deadline: Option<Instant>,
+    cancellation_token: Option<CancellationToken>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WordLength {
+    Characters(usize),
+    Bytes(usize),
+}
+
+/// The limit that has been exhausted by an application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExhaustedLimit {
+    Steps,
+    WordLength,
+    Deadline,
+    Cancellation,
+}
+
+impl ExecutionLimits {
+    /// Creates limits that do not restrict the application.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the number of steps.
+    ///
+    /// May be called multiple times in order to replace the prior limit.
+    pub fn with_steps(mut self, steps: u64) -> Self {
+        _ = self.steps.insert(steps);
+        self
+    }
+
+    /// Limits the length of intermediate words, measured in characters.
+    ///
+    /// May be called multiple times in order to replace the prior limit of the word length.
+    pub fn with_word_length_in_characters(mut self, characters: usize) -> Self {
+        _ = self.word_length.insert(WordLength::Characters(characters));
+        self
+    }
+
+    /// Limits the length of intermediate words, measured in UTF-8 bytes.
+    ///
+    /// The words of the [schemes over symbols](crate::scheme::symbolic::SymbolScheme) are measured in symbols instead.
+    ///
+    /// May be called multiple times in order to replace the prior limit of the word length.
+    pub fn with_word_length_in_bytes(mut self, bytes: usize) -> Self {
+        _ = self.word_length.insert(WordLength::Bytes(bytes));
+        self
+    }
+
+    /// Sets the moment after which no more steps are done.
+    ///
+    /// May be called multiple times in order to replace the prior deadline.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        _ = self.deadline.insert(deadline);
+        self
+    }
+
+    /// Sets the deadline that comes after the timeout passes, counting from now.
+    ///
+    /// May be called multiple times in order to replace the prior deadline.
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    /// Sets the token that allows to stop the application from another thread.
+    ///
+    /// May be called multiple times in order to replace the prior token.
+    pub fn with_cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        _ = self.cancellation_token.insert(cancellation_token);
+        self
+    }
+
+    /// The maximum number of steps, if any.
+    pub fn steps(&self) -> Option<u64> {
+        self.steps
+    }
+
+    /// The deadline, if any.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Finds a limit that does not allow to do one more step.
+    pub(crate) fn find_exhausted_before_step(&self, steps_done: u64) -> Option<ExhaustedLimit> {
+        if self
+            .cancellation_token
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            Some(ExhaustedLimit::Cancellation)
+        } else if self.steps.is_some_and(|steps| steps_done >= steps) {
+            Some(ExhaustedLimit::Steps)
+        } else if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            Some(ExhaustedLimit::Deadline)
+        } else {
+            None
+        }
+    }
+
+    /// Checks whether the word of the given length is longer than allowed.
+    pub(crate) fn is_exceeded_by(&self, characters: usize, bytes: usize) -> bool {
+        match self.word_length {
+            Some(WordLength::Characters(limit)) => characters > limit,
+            Some(WordLength::Bytes(limit)) => bytes > limit,
+            None => false,
+        }
+    }
+}
+
+impl ExhaustedLimit {
+    /// Creates the error that corresponds to the exhausted limit.
+    pub(crate) fn into_error(
+        self,
+        partial_result: PartialApplicationResult,
+    ) -> AlgorithmSchemeFullApplicationError {
+        match self {
+            Self::Steps => AlgorithmSchemeFullApplicationError::HitTheStepsLimit(partial_result),
+            Self::WordLength => {
+                AlgorithmSchemeFullApplicationError::HitTheWordLengthLimit(partial_result)
+            }
+            Self::Deadline => AlgorithmSchemeFullApplicationError::HitTheDeadline(partial_result),
+            Self::Cancellation => AlgorithmSchemeFullApplicationError::Cancelled(partial_result),
+        }
+    }
+}
+
+impl From<u32> for ExecutionLimits {
+    fn from(steps: u32) -> Self {
+        Self::new().with_steps(steps.into())
+    }
+}
+
+/// A token that allows to cooperatively stop the application of a scheme.
+///
+/// The clones of the token share the same state, so a clone can be sent to another thread to cancel the application.
+///
+/// # Example
+/// ```rust
+/// use markovalgorithms::prelude::*;
+///
+/// let token = CancellationToken::new();
+/// let clone = token.clone();
+///
+/// clone.cancel();
+///
+/// assert!(token.is_cancelled());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    is_cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Creates a token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests the cancellation of all applications that observe the token.
+    pub fn cancel(&self) {
+        self.is_cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Checks whether the cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.is_cancelled.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,88 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::*;
+
+#[test]
+fn default_limits_do_not_restrict_the_application() {
+    let limits = ExecutionLimits::default();
+
+    assert_eq!(None, limits.find_exhausted_before_step(u64::MAX));
+    assert!(!limits.is_exceeded_by(10_000, 10_000));
+}
+
+#[test]
+fn limits_can_be_created_from_the_number_of_steps() {
+    let limits = ExecutionLimits::from(10_u32);
+
+    assert_eq!(Some(10), limits.steps());
+    assert_eq!(None, limits.find_exhausted_before_step(9));
+    assert_eq!(
+        Some(ExhaustedLimit::Steps),
+        limits.find_exhausted_before_step(10)
+    );
+}
+
+#[test]
+fn the_word_length_can_be_limited_in_characters() {
+    let limits = ExecutionLimits::new().with_word_length_in_characters(2);
+
+    assert!(!limits.is_exceeded_by(2, 6));
+    assert!(limits.is_exceeded_by(3, 3));
+}
+
+#[test]
+fn the_word_length_can_be_limited_in_bytes() {
+    let limits = ExecutionLimits::new().with_word_length_in_bytes(4);
+
+    assert!(!limits.is_exceeded_by(4, 4));
+    assert!(limits.is_exceeded_by(2, 6));
+}
+
+#[test]
+fn a_passed_deadline_exhausts_the_limits() {
+    let limits = ExecutionLimits::new().with_deadline(Instant::now());
+
+    assert_eq!(
+        Some(ExhaustedLimit::Deadline),
+        limits.find_exhausted_before_step(0)
+    );
+}
+
+#[test]
+fn a_future_deadline_does_not_exhaust_the_limits() {
+    let limits = ExecutionLimits::new().with_timeout(Duration::from_secs(3_600));
+
+    assert_eq!(None, limits.find_exhausted_before_step(0));
+}
+
+#[test]
+fn a_cancelled_token_exhausts_the_limits() {
+    let token = CancellationToken::new();
+    let limits = ExecutionLimits::new().with_cancellation_token(token.clone());
+
+    assert_eq!(None, limits.find_exhausted_before_step(0));
+
+    token.cancel();
+
+    assert_eq!(
+        Some(ExhaustedLimit::Cancellation),
+        limits.find_exhausted_before_step(0)
+    );
+}
@@ -0,0 +1,327 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! [MatchIndex](MatchIndex), the occurrences of the formulas that are kept up to date while the word is rewritten.
+
+#[cfg(test)]
+mod tests;
+
+use std::{collections::BTreeSet, ops::Range};
+
+use super::{
+    engine::MatchStrategy,
+    matcher::{FormulaMatcher, PatternMatch},
+    word_buffer::{Unit, WordBuffer},
+};
+
+/// An index of all occurrences of the left sides of the formulas in the word.
+///
+/// After a substitution only the occurrences that may overlap the replaced range are rescanned.
+/// The occurrences before the last substitution are counted from the start of the word
+/// and the occurrences after it are counted from the end of the word,
+/// so a substitution does not shift the positions that have already been found.
+#[derive(Debug, Clone)]
+pub(crate) struct MatchIndex {
+    occurrences: Vec<PatternOccurrences>,
+    /// The patterns that have at least one occurrence, ordered by their index.
+    applicable: BTreeSet<usize>,
+    /// The position that separates the head and the tail occurrences.
+    split: usize,
+    length: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+struct PatternOccurrences {
+    /// The starts of the occurrences that lie before the split, counted from the start of the word.
+    head: BTreeSet<usize>,
+    /// The starts of the occurrences that lie at or after the split, counted from the end of the word.
+    tail: BTreeSet<usize>,
+}
+
+impl PatternOccurrences {
+    fn is_empty(&self) -> bool {
+        self.head.is_empty() && self.tail.is_empty()
+    }
+}
+
+impl MatchIndex {
+    /// Scans the whole word to find all the occurrences.
+    pub(crate) fn new<U: Unit>(matcher: &FormulaMatcher<U>, word: &WordBuffer<U>) -> Self {
+        let mut index = Self {
+            occurrences: vec![PatternOccurrences::default(); matcher.pattern_count()],
+            applicable: BTreeSet::new(),
+            split: word.len(),
+            length: word.len(),
+        };
+
+        matcher.for_each_occurrence(word.units(), 0, |occurrence| index.insert_head(occurrence));
+
+        index
+    }
+
+    /// Finds the pattern with the lowest index that occurs in the word, and its leftmost occurrence.
+    pub(crate) fn find_first<U: Unit>(&self, matcher: &FormulaMatcher<U>) -> Option<PatternMatch> {
+        let empty = matcher
+            .first_empty_pattern()
+            .map(|pattern_index| PatternMatch {
+                pattern_index,
+                start: 0,
+            });
+
+        match self.applicable.first() {
+            Some(&pattern_index)
+                if empty.is_none_or(|empty| pattern_index < empty.pattern_index) =>
+            {
+                Some(PatternMatch {
+                    pattern_index,
+                    start: self.leftmost(pattern_index),
+                })
+            }
+            _ => empty,
+        }
+    }
+
+    /// Finds the occurrence of a pattern the strategy selects.
+    pub(crate) fn find<U: Unit>(
+        &self,
+        matcher: &FormulaMatcher<U>,
+        strategy: MatchStrategy,
+    ) -> Option<PatternMatch> {
+        match strategy {
+            MatchStrategy::Leftmost => self.find_first(matcher),
+            MatchStrategy::Rightmost => {
+                let empty = matcher.first_empty_pattern();
+
+                match self.applicable.first() {
+                    Some(&pattern_index) if empty.is_none_or(|empty| pattern_index < empty) => {
+                        Some(PatternMatch {
+                            pattern_index,
+                            start: self.rightmost(pattern_index),
+                        })
+                    }
+                    _ => empty.map(|pattern_index| PatternMatch {
+                        pattern_index,
+                        start: self.length,
+                    }),
+                }
+            }
+            MatchStrategy::PositionFirst => matcher
+                .first_empty_pattern()
+                .map(|pattern_index| (0, pattern_index))
+                .into_iter()
+                .chain(
+                    self.applicable
+                        .iter()
+                        .map(|&pattern_index| (self.leftmost(pattern_index), pattern_index)),
+                )
+                .min()
+                .map(|(start, pattern_index)| PatternMatch {
+                    pattern_index,
+                    start,
+                }),
+        }
+    }
+
+    /// Finds the occurrence the strategy selects for each pattern that occurs in the word, in the order of patterns.
+    ///
+    /// The leftmost occurrences are selected unless the strategy selects the rightmost ones.
+    pub(crate) fn find_each<U: Unit>(
+        &self,
+        matcher: &FormulaMatcher<U>,
+        strategy: MatchStrategy,
+    ) -> Vec<PatternMatch> {
+        let mut occurrences: Vec<_> = self
+            .applicable
+            .iter()
+            .map(|&pattern_index| PatternMatch {
+                pattern_index,
+                start: match strategy {
+                    MatchStrategy::Rightmost => self.rightmost(pattern_index),
+                    MatchStrategy::Leftmost | MatchStrategy::PositionFirst => {
+                        self.leftmost(pattern_index)
+                    }
+                },
+            })
+            .chain(matcher.empty_patterns().map(|pattern_index| PatternMatch {
+                pattern_index,
+                start: match strategy {
+                    MatchStrategy::Rightmost => self.length,
+                    MatchStrategy::Leftmost | MatchStrategy::PositionFirst => 0,
+                },
+            }))
+            .collect();
+
+        occurrences.sort_unstable_by_key(|occurrence| occurrence.pattern_index);
+
+        occurrences
+    }
+
+    /// Replaces a range of the word and updates the occurrences around it.
+    pub(crate) fn replace<U: Unit>(
+        &mut self,
+        matcher: &FormulaMatcher<U>,
+        word: &mut WordBuffer<U>,
+        range: Range<usize>,
+        replacement: &[U],
+    ) {
+        let reach = matcher.longest_pattern_length().saturating_sub(1);
+        let window_start = range.start.saturating_sub(reach);
+
+        self.move_split(matcher, word, range.end);
+
+        // Any occurrence that starts in the window may overlap the replaced range.
+        let window_end = (range.end + reach).min(self.length);
+        matcher.for_each_occurrence(
+            word.units_in(window_start..window_end),
+            window_start,
+            |occurrence| {
+                if occurrence.start < range.end {
+                    self.remove_head(occurrence);
+                }
+            },
+        );
+
+        word.replace(range.clone(), replacement);
+
+        let inserted_end = range.start + replacement.len();
+        self.length = word.len();
+        self.split = inserted_end;
+
+        let window_end = (inserted_end + reach).min(self.length);
+        matcher.for_each_occurrence(
+            word.units_in(window_start..window_end),
+            window_start,
+            |occurrence| {
+                if occurrence.start < inserted_end {
+                    self.insert_head(occurrence);
+                }
+            },
+        );
+    }
+
+    /// Moves the occurrences between the current split and the new one to the other side.
+    fn move_split<U: Unit>(
+        &mut self,
+        matcher: &FormulaMatcher<U>,
+        word: &WordBuffer<U>,
+        split: usize,
+    ) {
+        let reach = matcher.longest_pattern_length().saturating_sub(1);
+        let (start, end) = (self.split.min(split), self.split.max(split));
+        let length = self.length;
+        let moves_to_tail = split < self.split;
+
+        matcher.for_each_occurrence(
+            word.units_in(start..(end + reach).min(length)),
+            start,
+            |PatternMatch {
+                 pattern_index,
+                 start: occurrence_start,
+             }| {
+                if occurrence_start < end {
+                    let occurrences = &mut self.occurrences[pattern_index];
+
+                    if moves_to_tail {
+                        occurrences.head.remove(&occurrence_start);
+                        occurrences.tail.insert(length - occurrence_start);
+                    } else {
+                        occurrences.tail.remove(&(length - occurrence_start));
+                        occurrences.head.insert(occurrence_start);
+                    }
+                }
+            },
+        );
+
+        self.split = split;
+    }
+
+    fn leftmost(&self, pattern_index: usize) -> usize {
+        let occurrences = &self.occurrences[pattern_index];
+
+        occurrences.head.first().copied().unwrap_or_else(|| {
+            self.length
+                - occurrences
+                    .tail
+                    .last()
+                    .expect("Applicable patterns have at least one occurrence.")
+        })
+    }
+
+    fn rightmost(&self, pattern_index: usize) -> usize {
+        let occurrences = &self.occurrences[pattern_index];
+
+        occurrences
+            .tail
+            .first()
+            .map(|from_end| self.length - from_end)
+            .unwrap_or_else(|| {
+                *occurrences
+                    .head
+                    .last()
+                    .expect("Applicable patterns have at least one occurrence.")
+            })
+    }
+
+    fn insert_head(&mut self, occurrence: PatternMatch) {
+        self.occurrences[occurrence.pattern_index]
+            .head
+            .insert(occurrence.start);
+        self.applicable.insert(occurrence.pattern_index);
+    }
+
+    fn remove_head(&mut self, occurrence: PatternMatch) {
+        let occurrences = &mut self.occurrences[occurrence.pattern_index];
+
+        occurrences.head.remove(&occurrence.start);
+
+        if occurrences.is_empty() {
+            self.applicable.remove(&occurrence.pattern_index);
+        }
+    }
+
+    /// Lists all the occurrences ordered by their position in the word.
+    #[cfg(test)]
+    fn all_occurrences(&self) -> Vec<PatternMatch> {
+        let mut all: Vec<_> = self
+            .occurrences
+            .iter()
+            .enumerate()
+            .flat_map(|(pattern_index, occurrences)| {
+                occurrences
+                    .head
+                    .iter()
+                    .copied()
+                    .chain(
+                        occurrences
+                            .tail
+                            .iter()
+                            .map(|from_end| self.length - from_end),
+                    )
+                    .map(move |start| PatternMatch {
+                        pattern_index,
+                        start,
+                    })
+            })
+            .collect();
+
+        all.sort_by_key(|occurrence| (occurrence.start, occurrence.pattern_index));
+
+        all
+    }
+}
@@ -0,0 +1,202 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::iter;
+
+use rand::{seq::SliceRandom, Rng};
+
+use super::*;
+
+fn create_matcher(patterns: &[&str]) -> FormulaMatcher<u8> {
+    FormulaMatcher::new(patterns.iter().map(|pattern| pattern.as_bytes()))
+}
+
+#[test]
+fn the_index_contains_all_occurrences_of_the_initial_word() {
+    let matcher = create_matcher(&["ab", "b", "bab"]);
+    let word = WordBuffer::from("abab");
+
+    let index = MatchIndex::new(&matcher, &word);
+
+    assert_eq!(
+        vec![
+            PatternMatch {
+                pattern_index: 0,
+                start: 0
+            },
+            PatternMatch {
+                pattern_index: 1,
+                start: 1
+            },
+            PatternMatch {
+                pattern_index: 2,
+                start: 1
+            },
+            PatternMatch {
+                pattern_index: 0,
+                start: 2
+            },
+            PatternMatch {
+                pattern_index: 1,
+                start: 3
+            },
+        ],
+        index.all_occurrences()
+    );
+}
+
+#[test]
+fn occurrences_destroyed_by_a_replacement_are_removed() {
+    let matcher = create_matcher(&["ab", "c"]);
+    let mut word = WordBuffer::from("abab");
+    let mut index = MatchIndex::new(&matcher, &word);
+
+    index.replace(&matcher, &mut word, 1..2, b"c");
+
+    assert_eq!(
+        Some(PatternMatch {
+            pattern_index: 0,
+            start: 2
+        }),
+        index.find_first(&matcher)
+    );
+    assert_eq!("acab", word.to_string());
+}
+
+#[test]
+fn occurrences_created_by_a_replacement_are_added() {
+    let matcher = create_matcher(&["aa", "c"]);
+    let mut word = WordBuffer::from("acab");
+    let mut index = MatchIndex::new(&matcher, &word);
+
+    index.replace(&matcher, &mut word, 1..2, b"a");
+
+    assert_eq!(
+        Some(PatternMatch {
+            pattern_index: 0,
+            start: 0
+        }),
+        index.find_first(&matcher)
+    );
+}
+
+#[test]
+fn an_empty_pattern_takes_precedence_over_the_following_patterns() {
+    let matcher = create_matcher(&["b", "", "a"]);
+    let word = WordBuffer::from("aa");
+
+    let index = MatchIndex::new(&matcher, &word);
+
+    assert_eq!(
+        Some(PatternMatch {
+            pattern_index: 1,
+            start: 0
+        }),
+        index.find_first(&matcher)
+    );
+}
+
+#[test]
+fn each_applicable_pattern_is_found_with_its_selected_occurrence() {
+    let matcher = create_matcher(&["b", "", "c", "a"]);
+    let word = WordBuffer::from("abab");
+
+    let index = MatchIndex::new(&matcher, &word);
+
+    let starts = |strategy| {
+        index
+            .find_each(&matcher, strategy)
+            .into_iter()
+            .map(|occurrence| (occurrence.pattern_index, occurrence.start))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        vec![(0, 1), (1, 0), (3, 0)],
+        starts(MatchStrategy::Leftmost)
+    );
+    assert_eq!(
+        vec![(0, 3), (1, 4), (3, 2)],
+        starts(MatchStrategy::Rightmost)
+    );
+}
+
+#[test]
+fn the_index_agrees_with_a_full_rescan_after_random_replacements() {
+    let mut generator = rand::thread_rng();
+    let characters = ['a', 'b', 'c'];
+
+    let random_string = |generator: &mut rand::rngs::ThreadRng, length: usize| -> String {
+        iter::repeat_with(|| {
+            characters
+                .choose(generator)
+                .expect("The slice to choose from is not empty.")
+        })
+        .take(length)
+        .collect()
+    };
+
+    for _ in 0..200 {
+        let patterns: Vec<_> = (0..generator.gen_range(1..8))
+            .map(|_| {
+                let length = generator.gen_range(0..4);
+                random_string(&mut generator, length)
+            })
+            .collect();
+        let patterns: Vec<_> = patterns.iter().map(String::as_str).collect();
+        let matcher = create_matcher(&patterns);
+
+        let length = generator.gen_range(0..30);
+        let mut word = WordBuffer::from(random_string(&mut generator, length).as_str());
+        let mut index = MatchIndex::new(&matcher, &word);
+
+        for _ in 0..50 {
+            let start = generator.gen_range(0..=word.len());
+            let end = generator.gen_range(start..=word.len().min(start + 3));
+            let length = generator.gen_range(0..4);
+            let replacement = random_string(&mut generator, length);
+
+            index.replace(&matcher, &mut word, start..end, replacement.as_bytes());
+
+            let rescanned = MatchIndex::new(&matcher, &word);
+
+            assert_eq!(
+                rescanned.all_occurrences(),
+                index.all_occurrences(),
+                "patterns: {patterns:?}, word: {word}"
+            );
+            for strategy in [
+                MatchStrategy::Leftmost,
+                MatchStrategy::Rightmost,
+                MatchStrategy::PositionFirst,
+            ] {
+                assert_eq!(
+                    matcher.find(word.units(), strategy),
+                    index.find(&matcher, strategy),
+                    "patterns: {patterns:?}, word: {word}, strategy: {strategy:?}"
+                );
+                assert_eq!(
+                    matcher.find_each(word.units(), strategy),
+                    index.find_each(&matcher, strategy),
+                    "patterns: {patterns:?}, word: {word}, strategy: {strategy:?}"
+                );
+            }
+        }
+    }
+}
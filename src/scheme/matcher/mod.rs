@@ -0,0 +1,448 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! [FormulaMatcher](FormulaMatcher), a multi-pattern automaton over the left sides of substitution formulas.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::VecDeque;
+
+use super::{engine::MatchStrategy, word_buffer::Unit};
+
+/// An Aho-Corasick automaton that selects the first applicable substitution formula in a single pass.
+///
+/// The patterns are indexed in the order of the formulas, so the pattern with the lowest index
+/// that occurs anywhere in the word wins, exactly as if the formulas were checked one by one.
+/// The automaton works over any ordered units, like the UTF-8 bytes of a string or interned symbols.
+#[derive(Debug)]
+pub(crate) struct FormulaMatcher<U> {
+    nodes: Vec<Node<U>>,
+    pattern_lengths: Vec<usize>,
+    longest_pattern_length: usize,
+    first_empty_pattern: Option<usize>,
+    dense_transitions: Option<DenseTransitions>,
+}
+
+/// A complete transition table for the units below the width, with the failure links already followed.
+#[derive(Debug)]
+struct DenseTransitions {
+    width: usize,
+    targets: Vec<u32>,
+}
+
+/// An occurrence of a pattern, selected among the others in a word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PatternMatch {
+    pub(crate) pattern_index: usize,
+    pub(crate) start: usize,
+}
+
+#[derive(Debug)]
+struct Node<U> {
+    /// Transitions sorted by the unit.
+    transitions: Vec<(U, usize)>,
+    failure: usize,
+    /// The patterns that end exactly in this node.
+    outputs: Vec<usize>,
+    /// The closest node reachable by failure links that has its own outputs.
+    output_link: Option<usize>,
+    /// The lowest index among the patterns that end in this node, including the ones reachable by failure links.
+    best_output: Option<usize>,
+}
+
+impl<U> Default for Node<U> {
+    fn default() -> Self {
+        Self {
+            transitions: Vec::new(),
+            failure: 0,
+            outputs: Vec::new(),
+            output_link: None,
+            best_output: None,
+        }
+    }
+}
+
+impl<U: Unit> Node<U> {
+    fn get_transition(&self, unit: U) -> Option<usize> {
+        self.transitions
+            .binary_search_by_key(&unit, |&(key, _)| key)
+            .ok()
+            .map(|index| self.transitions[index].1)
+    }
+}
+
+impl<U: Unit> FormulaMatcher<U> {
+    const ROOT: usize = 0;
+
+    /// The largest number of entries in a dense transition table, so the table stays within a few megabytes.
+    const MAX_DENSE_TRANSITIONS: usize = 1 << 20;
+
+    /// Builds the automaton from the left sides of the formulas, in the order of the formulas.
+    pub(crate) fn new<'a, I>(patterns: I) -> Self
+    where
+        I: Iterator<Item = &'a [U]>,
+        U: 'a,
+    {
+        let mut nodes = vec![Node::default()];
+        let mut pattern_lengths = Vec::new();
+        let mut first_empty_pattern = None;
+
+        for (pattern_index, pattern) in patterns.enumerate() {
+            pattern_lengths.push(pattern.len());
+
+            if pattern.is_empty() {
+                first_empty_pattern.get_or_insert(pattern_index);
+                continue;
+            }
+
+            let mut current = Self::ROOT;
+
+            for &unit in pattern {
+                current = match nodes[current].get_transition(unit) {
+                    Some(next) => next,
+                    None => {
+                        let next = nodes.len();
+                        nodes.push(Node::default());
+
+                        let transitions = &mut nodes[current].transitions;
+                        let position = transitions
+                            .binary_search_by_key(&unit, |&(key, _)| key)
+                            .expect_err("The transition is known to be absent.");
+                        transitions.insert(position, (unit, next));
+
+                        next
+                    }
+                };
+            }
+
+            nodes[current].outputs.push(pattern_index);
+            nodes[current].best_output.get_or_insert(pattern_index);
+        }
+
+        Self::link_failures(&mut nodes);
+
+        let longest_pattern_length = pattern_lengths.iter().copied().max().unwrap_or(0);
+
+        Self {
+            nodes,
+            pattern_lengths,
+            longest_pattern_length,
+            first_empty_pattern,
+            dense_transitions: None,
+        }
+    }
+
+    /// Precomputes the transitions for every unit below the width, so each unit is processed by a single lookup.
+    ///
+    /// Only worth it when the units are dense codes of a small alphabet.
+    /// The table is not built if it would be too large, the automaton keeps working without it.
+    pub(crate) fn with_dense_transitions(mut self, width: usize) -> Self {
+        let Some(size) = self
+            .nodes
+            .len()
+            .checked_mul(width)
+            .filter(|&size| size <= Self::MAX_DENSE_TRANSITIONS)
+        else {
+            return self;
+        };
+
+        let mut targets = vec![0; size];
+        let mut queue = VecDeque::from([Self::ROOT]);
+
+        // In breadth-first order the row of the failure node is complete before the row of the node is filled,
+        // so the row starts as a copy of it and only the own transitions differ.
+        while let Some(current) = queue.pop_front() {
+            let row = current * width;
+
+            if current != Self::ROOT {
+                let failure_row = self.nodes[current].failure * width;
+                targets.copy_within(failure_row..failure_row + width, row);
+            }
+
+            for &(unit, child) in &self.nodes[current].transitions {
+                if unit.index() < width {
+                    targets[row + unit.index()] = u32::try_from(child)
+                        .expect("The table is small enough to be indexed with u32.");
+                }
+
+                queue.push_back(child);
+            }
+        }
+
+        _ = self
+            .dense_transitions
+            .insert(DenseTransitions { width, targets });
+        self
+    }
+
+    /// Finds the pattern with the lowest index that occurs in the haystack, and its leftmost occurrence.
+    pub(crate) fn find_first<'h, I>(&self, haystack: I) -> Option<PatternMatch>
+    where
+        I: IntoIterator<Item = &'h U>,
+        U: 'h,
+    {
+        // Patterns that come after the first empty one can never be selected.
+        let limit = self
+            .first_empty_pattern
+            .unwrap_or(self.pattern_lengths.len());
+
+        let mut best: Option<PatternMatch> = None;
+        let mut current = Self::ROOT;
+
+        for (position, &unit) in haystack.into_iter().enumerate() {
+            current = self.step(current, unit);
+
+            if let Some(pattern_index) = self.nodes[current].best_output {
+                if pattern_index < limit
+                    && best.is_none_or(|best| pattern_index < best.pattern_index)
+                {
+                    best = Some(PatternMatch {
+                        pattern_index,
+                        start: position + 1 - self.pattern_lengths[pattern_index],
+                    });
+
+                    if pattern_index == 0 {
+                        break;
+                    }
+                }
+            }
+        }
+
+        best.or_else(|| {
+            self.first_empty_pattern.map(|pattern_index| PatternMatch {
+                pattern_index,
+                start: 0,
+            })
+        })
+    }
+
+    /// Finds the occurrence of a pattern the strategy selects in the haystack.
+    pub(crate) fn find<'h, I>(&self, haystack: I, strategy: MatchStrategy) -> Option<PatternMatch>
+    where
+        I: IntoIterator<Item = &'h U>,
+        U: 'h,
+    {
+        if strategy == MatchStrategy::Leftmost {
+            return self.find_first(haystack);
+        }
+
+        let mut length = 0;
+        let mut best: Option<PatternMatch> = None;
+
+        let select = |best: &mut Option<PatternMatch>, candidate: PatternMatch| {
+            let is_preferred = best.is_none_or(|best| {
+                strategy.prefers(
+                    (candidate.pattern_index, candidate.start),
+                    (best.pattern_index, best.start),
+                )
+            });
+
+            if is_preferred {
+                _ = best.insert(candidate);
+            }
+        };
+
+        self.for_each_occurrence(
+            haystack.into_iter().inspect(|_| length += 1),
+            0,
+            |occurrence| select(&mut best, occurrence),
+        );
+
+        if let Some(pattern_index) = self.first_empty_pattern {
+            // The empty pattern occurs everywhere, so only its first and its last occurrences may be selected.
+            let start = match strategy {
+                MatchStrategy::Rightmost => length,
+                MatchStrategy::Leftmost | MatchStrategy::PositionFirst => 0,
+            };
+
+            select(
+                &mut best,
+                PatternMatch {
+                    pattern_index,
+                    start,
+                },
+            );
+        }
+
+        best
+    }
+
+    /// Finds the occurrence the strategy selects for each pattern that occurs in the haystack, in the order of patterns.
+    ///
+    /// The leftmost occurrences are selected unless the strategy selects the rightmost ones.
+    pub(crate) fn find_each<'h, I>(&self, haystack: I, strategy: MatchStrategy) -> Vec<PatternMatch>
+    where
+        I: IntoIterator<Item = &'h U>,
+        U: 'h,
+    {
+        let mut length = 0;
+        let mut starts = vec![None; self.pattern_count()];
+
+        self.for_each_occurrence(
+            haystack.into_iter().inspect(|_| length += 1),
+            0,
+            |PatternMatch {
+                 pattern_index,
+                 start,
+             }| {
+                // The occurrences of a pattern are reported from left to right.
+                let slot = &mut starts[pattern_index];
+
+                if slot.is_none() || strategy == MatchStrategy::Rightmost {
+                    *slot = Some(start);
+                }
+            },
+        );
+
+        for pattern_index in self.empty_patterns() {
+            starts[pattern_index] = Some(match strategy {
+                MatchStrategy::Rightmost => length,
+                MatchStrategy::Leftmost | MatchStrategy::PositionFirst => 0,
+            });
+        }
+
+        starts
+            .into_iter()
+            .enumerate()
+            .filter_map(|(pattern_index, start)| {
+                start.map(|start| PatternMatch {
+                    pattern_index,
+                    start,
+                })
+            })
+            .collect()
+    }
+
+    /// Reports every occurrence of every non-empty pattern in the haystack.
+    ///
+    /// The positions are reported relative to the start of the haystack shifted by the offset.
+    pub(crate) fn for_each_occurrence<'h, I, F>(&self, haystack: I, offset: usize, mut report: F)
+    where
+        I: IntoIterator<Item = &'h U>,
+        U: 'h,
+        F: FnMut(PatternMatch),
+    {
+        let mut current = Self::ROOT;
+
+        for (position, &unit) in haystack.into_iter().enumerate() {
+            current = self.step(current, unit);
+
+            let mut node = if self.nodes[current].outputs.is_empty() {
+                self.nodes[current].output_link
+            } else {
+                Some(current)
+            };
+
+            while let Some(output_node) = node {
+                for &pattern_index in &self.nodes[output_node].outputs {
+                    report(PatternMatch {
+                        pattern_index,
+                        start: offset + position + 1 - self.pattern_lengths[pattern_index],
+                    });
+                }
+
+                node = self.nodes[output_node].output_link;
+            }
+        }
+    }
+
+    /// The number of patterns the automaton is built from.
+    pub(crate) fn pattern_count(&self) -> usize {
+        self.pattern_lengths.len()
+    }
+
+    /// The length of the longest pattern.
+    pub(crate) fn longest_pattern_length(&self) -> usize {
+        self.longest_pattern_length
+    }
+
+    /// The indices of the empty patterns, which are applicable to any word.
+    pub(crate) fn empty_patterns(&self) -> impl Iterator<Item = usize> + '_ {
+        self.pattern_lengths
+            .iter()
+            .enumerate()
+            .filter(|&(_, &length)| length == 0)
+            .map(|(pattern_index, _)| pattern_index)
+    }
+
+    /// The index of the first empty pattern, which is applicable to any word.
+    pub(crate) fn first_empty_pattern(&self) -> Option<usize> {
+        self.first_empty_pattern
+    }
+
+    fn step(&self, mut current: usize, unit: U) -> usize {
+        if let Some(DenseTransitions { width, targets }) = &self.dense_transitions {
+            if unit.index() < *width {
+                return targets[current * width + unit.index()] as usize;
+            }
+        }
+
+        loop {
+            if let Some(next) = self.nodes[current].get_transition(unit) {
+                return next;
+            }
+            if current == Self::ROOT {
+                return Self::ROOT;
+            }
+            current = self.nodes[current].failure;
+        }
+    }
+
+    /// Computes the failure links in breadth-first order and propagates the outputs along them.
+    fn link_failures(nodes: &mut [Node<U>]) {
+        let mut queue: VecDeque<_> = nodes[Self::ROOT]
+            .transitions
+            .iter()
+            .map(|&(_, child)| child)
+            .collect();
+
+        while let Some(current) = queue.pop_front() {
+            let transitions = nodes[current].transitions.clone();
+
+            for (unit, child) in transitions {
+                let mut fallback = nodes[current].failure;
+
+                let failure = loop {
+                    if let Some(next) = nodes[fallback].get_transition(unit) {
+                        break next;
+                    }
+                    if fallback == Self::ROOT {
+                        break Self::ROOT;
+                    }
+                    fallback = nodes[fallback].failure;
+                };
+
+                nodes[child].failure = failure;
+                nodes[child].output_link = if nodes[failure].outputs.is_empty() {
+                    nodes[failure].output_link
+                } else {
+                    Some(failure)
+                };
+                nodes[child].best_output =
+                    match (nodes[child].best_output, nodes[failure].best_output) {
+                        (Some(own), Some(inherited)) => Some(own.min(inherited)),
+                        (own, inherited) => own.or(inherited),
+                    };
+
+                queue.push_back(child);
+            }
+        }
+    }
+}
@@ -0,0 +1,326 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::iter;
+
+use rand::{seq::SliceRandom, Rng};
+
+use super::*;
+
+fn create_matcher(patterns: &[&str]) -> FormulaMatcher<u8> {
+    FormulaMatcher::new(patterns.iter().map(|pattern| pattern.as_bytes()))
+}
+
+fn find_naively(patterns: &[&str], haystack: &str) -> Option<PatternMatch> {
+    patterns
+        .iter()
+        .enumerate()
+        .find_map(|(pattern_index, pattern)| {
+            haystack.find(pattern).map(|start| PatternMatch {
+                pattern_index,
+                start,
+            })
+        })
+}
+
+/// Selects among all occurrences of all patterns, the empty patterns occur at every position.
+fn find_naively_with(
+    patterns: &[&str],
+    haystack: &str,
+    strategy: MatchStrategy,
+) -> Option<PatternMatch> {
+    let occurrences = patterns
+        .iter()
+        .enumerate()
+        .flat_map(|(pattern_index, pattern)| {
+            (0..=haystack.len())
+                .filter(move |&start| haystack[start..].starts_with(pattern))
+                .map(move |start| PatternMatch {
+                    pattern_index,
+                    start,
+                })
+        });
+
+    occurrences.fold(None, |best, occurrence| match best {
+        Some(best)
+            if !strategy.prefers(
+                (occurrence.pattern_index, occurrence.start),
+                (best.pattern_index, best.start),
+            ) =>
+        {
+            Some(best)
+        }
+        _ => Some(occurrence),
+    })
+}
+
+#[test]
+fn nothing_is_found_if_no_pattern_occurs() {
+    let matcher = create_matcher(&["ab", "ba"]);
+
+    assert_eq!(None, matcher.find_first(b"aaaa"));
+}
+
+#[test]
+fn the_first_pattern_in_order_wins_over_the_leftmost_occurrence() {
+    let matcher = create_matcher(&["cd", "ab"]);
+
+    assert_eq!(
+        Some(PatternMatch {
+            pattern_index: 0,
+            start: 4
+        }),
+        matcher.find_first(b"abcacd")
+    );
+}
+
+#[test]
+fn the_leftmost_occurrence_of_the_selected_pattern_is_reported() {
+    let matcher = create_matcher(&["b", "a"]);
+
+    assert_eq!(
+        Some(PatternMatch {
+            pattern_index: 0,
+            start: 1
+        }),
+        matcher.find_first(b"abab")
+    );
+}
+
+#[test]
+fn patterns_found_through_failure_links_are_reported() {
+    let matcher = create_matcher(&["abcd", "bc"]);
+
+    assert_eq!(
+        Some(PatternMatch {
+            pattern_index: 1,
+            start: 2
+        }),
+        matcher.find_first(b"abbcd")
+    );
+}
+
+#[test]
+fn an_empty_pattern_matches_at_the_start_of_any_word() {
+    let matcher = create_matcher(&["ab", "", "a"]);
+
+    assert_eq!(
+        Some(PatternMatch {
+            pattern_index: 1,
+            start: 0
+        }),
+        matcher.find_first(b"aaa")
+    );
+    assert_eq!(
+        Some(PatternMatch {
+            pattern_index: 1,
+            start: 0
+        }),
+        matcher.find_first(b"")
+    );
+}
+
+#[test]
+fn a_pattern_before_an_empty_one_takes_precedence() {
+    let matcher = create_matcher(&["ab", "", "a"]);
+
+    assert_eq!(
+        Some(PatternMatch {
+            pattern_index: 0,
+            start: 2
+        }),
+        matcher.find_first(b"aaab")
+    );
+}
+
+#[test]
+fn multibyte_patterns_are_matched_at_character_boundaries() {
+    let matcher = create_matcher(&["ぬ", "у"]);
+
+    assert_eq!(
+        Some(PatternMatch {
+            pattern_index: 1,
+            start: 2
+        }),
+        matcher.find_first("нуу".as_bytes())
+    );
+}
+
+#[test]
+fn patterns_of_wider_units_are_matched() {
+    let patterns: [&[u32]; 2] = [&[70_000, 3], &[3]];
+    let matcher = FormulaMatcher::new(patterns.into_iter());
+
+    assert_eq!(
+        Some(PatternMatch {
+            pattern_index: 0,
+            start: 2
+        }),
+        matcher.find_first(&[3, 1, 70_000, 3])
+    );
+}
+
+#[test]
+fn the_matcher_agrees_with_the_naive_search_on_fuzzed_input() {
+    let mut generator = rand::thread_rng();
+    let characters = ['a', 'b', 'c'];
+
+    let mut random_string = |length: usize| -> String {
+        iter::repeat_with(|| {
+            characters
+                .choose(&mut generator)
+                .expect("The slice to choose from is not empty.")
+        })
+        .take(length)
+        .collect()
+    };
+
+    for _ in 0..1_000 {
+        let patterns: Vec<_> = (0..rand::thread_rng().gen_range(1..10))
+            .map(|_| random_string(rand::thread_rng().gen_range(0..4)))
+            .collect();
+        let patterns: Vec<_> = patterns.iter().map(String::as_str).collect();
+        let haystack = random_string(rand::thread_rng().gen_range(0..20));
+
+        let matcher = create_matcher(&patterns);
+
+        assert_eq!(
+            find_naively(&patterns, &haystack),
+            matcher.find_first(haystack.as_bytes()),
+            "patterns: {patterns:?}, haystack: {haystack:?}"
+        );
+    }
+}
+
+#[test]
+fn the_occurrence_is_selected_by_the_strategy() {
+    let matcher = create_matcher(&["b", "a"]);
+
+    assert_eq!(
+        Some(PatternMatch {
+            pattern_index: 0,
+            start: 3
+        }),
+        matcher.find(b"abab", MatchStrategy::Rightmost)
+    );
+    assert_eq!(
+        Some(PatternMatch {
+            pattern_index: 1,
+            start: 0
+        }),
+        matcher.find(b"abab", MatchStrategy::PositionFirst)
+    );
+}
+
+#[test]
+fn an_empty_pattern_matches_at_the_end_of_any_word_when_the_rightmost_occurrence_is_selected() {
+    let matcher = create_matcher(&["c", "", "a"]);
+
+    assert_eq!(
+        Some(PatternMatch {
+            pattern_index: 1,
+            start: 4
+        }),
+        matcher.find(b"abab", MatchStrategy::Rightmost)
+    );
+    assert_eq!(
+        Some(PatternMatch {
+            pattern_index: 1,
+            start: 0
+        }),
+        matcher.find(b"abab", MatchStrategy::PositionFirst)
+    );
+}
+
+#[test]
+fn the_strategies_agree_with_the_naive_search_on_fuzzed_input() {
+    let mut generator = rand::thread_rng();
+    let characters = ['a', 'b', 'c'];
+
+    let mut random_string = |length: usize| -> String {
+        iter::repeat_with(|| {
+            characters
+                .choose(&mut generator)
+                .expect("The slice to choose from is not empty.")
+        })
+        .take(length)
+        .collect()
+    };
+
+    for _ in 0..1_000 {
+        let patterns: Vec<_> = (0..rand::thread_rng().gen_range(1..10))
+            .map(|_| random_string(rand::thread_rng().gen_range(0..4)))
+            .collect();
+        let patterns: Vec<_> = patterns.iter().map(String::as_str).collect();
+        let haystack = random_string(rand::thread_rng().gen_range(0..20));
+
+        let matcher = create_matcher(&patterns);
+
+        for strategy in [
+            MatchStrategy::Leftmost,
+            MatchStrategy::Rightmost,
+            MatchStrategy::PositionFirst,
+        ] {
+            assert_eq!(
+                find_naively_with(&patterns, &haystack, strategy),
+                matcher.find(haystack.as_bytes(), strategy),
+                "patterns: {patterns:?}, haystack: {haystack:?}, strategy: {strategy:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn the_dense_transitions_agree_with_the_sparse_ones_on_fuzzed_input() {
+    let mut generator = rand::thread_rng();
+
+    // The last unit lies beyond the table, so the sparse transitions are used for it.
+    let units = [0_u8, 1, 2, 3];
+    let width = 3;
+
+    for _ in 0..1_000 {
+        let mut random_units = |length: usize| -> Vec<u8> {
+            iter::repeat_with(|| *units.choose(&mut generator).unwrap())
+                .take(length)
+                .collect()
+        };
+
+        let patterns: Vec<_> = (0..rand::thread_rng().gen_range(1..10))
+            .map(|_| random_units(rand::thread_rng().gen_range(0..4)))
+            .collect();
+        let haystack = random_units(rand::thread_rng().gen_range(0..20));
+
+        let sparse = FormulaMatcher::new(patterns.iter().map(Vec::as_slice));
+        let dense =
+            FormulaMatcher::new(patterns.iter().map(Vec::as_slice)).with_dense_transitions(width);
+
+        let occurrences = |matcher: &FormulaMatcher<u8>| {
+            let mut occurrences = Vec::new();
+            matcher.for_each_occurrence(&haystack, 0, |occurrence| occurrences.push(occurrence));
+            occurrences
+        };
+
+        assert_eq!(
+            sparse.find_first(&haystack),
+            dense.find_first(&haystack),
+            "patterns: {patterns:?}, haystack: {haystack:?}"
+        );
+        assert_eq!(occurrences(&sparse), occurrences(&dense));
+    }
+}
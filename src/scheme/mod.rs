@@ -22,13 +22,59 @@
 #[cfg(test)]
 mod tests;
 
-use std::ops::Range;
+use std::{
+    collections::VecDeque,
+    num::NonZeroUsize,
+    ops::Range,
+    panic,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
 
 use thiserror::Error;
 
 use crate::alphabet::Alphabet;
 
+pub use self::{
+    engine::{GroupTieBreak, MatchStrategy},
+    limits::{CancellationToken, ExecutionLimits},
+    snapshot::{
+        ApplicationSnapshot, ApplicationSnapshotParseError, ApplicationSnapshotRestorationError,
+    },
+};
+
+use self::{
+    completion::{RewritingSystem, RewritingSystemDefinitionError},
+    encoding::WordEncoding,
+    engine::{
+        BoundSubstitution, CycleDetector, Execution, Progress, Rules, RunOutcome, StepOutcome,
+    },
+    exploration::SemiThueExplorer,
+    pattern::Anchors,
+    variables::FormulaVariables,
+    word_buffer::Unit,
+};
+
+pub mod completion;
+mod encoding;
+mod engine;
+pub mod exploration;
+pub mod limits;
+mod match_index;
+mod matcher;
+mod pattern;
+mod random;
 pub mod scheme_builder;
+pub mod scheme_file;
+pub mod snapshot;
+pub mod symbolic;
+mod template;
+pub mod turing;
+mod variables;
+mod word_buffer;
 
 /// An algorithm scheme, can be applied to process input strings.
 ///
@@ -94,104 +140,658 @@ pub struct AlgorithmScheme {
     properties: SchemeProperties,
     store: String,
     substitution_formulas: Vec<SubstitutionFormula>,
+    encoding: WordEncoding,
+    rules: Rules<u8>,
+    fingerprint: u64,
 }
 
+// The scheme and the owned iterator are shared between and moved across threads,
+// so losing these auto traits would be a breaking change.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<AlgorithmScheme>();
+    assert_send_sync::<OwnedApplicationIterator>();
+    assert_send_sync::<OwnedSingleApplicationData>();
+};
+
 impl AlgorithmScheme {
     /// Applies the algorithm scheme once to the input string.
     pub fn apply_once(
         &self,
         word: &str,
-    ) -> Result<SingleApplicationResult, AlgorithmSchemeInputValidationError> {
+    ) -> Result<SingleApplicationResult<'_>, AlgorithmSchemeInputValidationError> {
         self.assert_valid_word(word)?;
 
-        Ok(self.apply_once_unsafe(word))
+        let mut execution = Execution::new(&self.rules, &self.encoding.encode(word));
+        let outcome = execution.step(&self.rules);
+
+        Ok(self.describe_step(&outcome, 1, &execution))
     }
 
     /// Applies the algorithm scheme to the input string until the algorithm is completed.
     ///
     /// # Arguments
     /// - `word` — the input string.
-    /// - `steps_limit` — the maximum number of steps to do.
-    pub fn apply(
+    /// - `limits` — the [limits](ExecutionLimits) of the application, or simply the maximum number of steps to do.
+    ///
+    /// # Errors
+    /// Returns an [error](AlgorithmSchemeFullApplicationError) if the input is not valid,
+    /// or a distinct error for each exhausted limit that carries the [partial result](PartialApplicationResult).
+    pub fn apply<L>(
+        &self,
+        word: &str,
+        limits: L,
+    ) -> Result<FullApplicationResult, AlgorithmSchemeFullApplicationError>
+    where
+        L: Into<ExecutionLimits>,
+    {
+        let limits = limits.into();
+
+        Self::assert_non_zero_limit(&limits)?;
+
+        self.assert_valid_word(word).map_err(|error| {
+            AlgorithmSchemeFullApplicationError::InputValidationError { source: error }
+        })?;
+
+        self.run(word, 0, &limits, |_| None)
+    }
+
+    /// Continues the application that has been interrupted before the algorithm is completed.
+    ///
+    /// # Arguments
+    /// - `partial_result` — the state of the interrupted application.
+    /// - `limits` — the [limits](ExecutionLimits) of the resumed application, the steps are counted anew.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::str;
+    /// use markovalgorithms::prelude::*;
+    ///
+    /// let scheme = AlgorithmSchemeBuilder::new()
+    ///     .with_alphabet(str::parse("ab").unwrap())
+    ///     .build_with_formula_definitions(["a→b"].into_iter())
+    ///     .unwrap();
+    ///
+    /// let error = scheme.apply("aaa", 2).unwrap_err();
+    ///
+    /// let partial_result = if let AlgorithmSchemeFullApplicationError::HitTheStepsLimit(partial_result) = error {
+    ///     Some(partial_result)
+    /// } else {
+    ///     None
+    /// }.unwrap();
+    ///
+    /// assert_eq!("bba", partial_result.word());
+    /// assert_eq!(Some("a→b"), partial_result.last_applied_formula_definition());
+    ///
+    /// let result = scheme.resume(partial_result, 10).unwrap();
+    ///
+    /// assert_eq!("bbb", result.word());
+    /// assert_eq!(4, result.steps_done());
+    /// ```
+    pub fn resume<L>(
+        &self,
+        partial_result: PartialApplicationResult,
+        limits: L,
+    ) -> Result<FullApplicationResult, AlgorithmSchemeFullApplicationError>
+    where
+        L: Into<ExecutionLimits>,
+    {
+        let limits = limits.into();
+
+        Self::assert_non_zero_limit(&limits)?;
+
+        self.assert_known_characters(&partial_result.word)
+            .map_err(
+                |error| AlgorithmSchemeFullApplicationError::InputValidationError { source: error },
+            )?;
+
+        self.run(
+            &partial_result.word,
+            partial_result.steps_done,
+            &limits,
+            |_| None,
+        )
+    }
+
+    /// Applies the algorithm scheme to the input string until the algorithm is completed,
+    /// stopping early if the word starts to repeat itself.
+    ///
+    /// Since the algorithm is deterministic, a repeated word means that the algorithm never completes.
+    /// The detection uses Brent's algorithm, so only a single additional word is kept in memory,
+    /// but each step requires comparing the current word with the saved one.
+    ///
+    /// A scheme that chooses the formulas at random may repeat a word and still complete later,
+    /// so the cycles are not detected for it.
+    ///
+    /// # Arguments
+    /// - `word` — the input string.
+    /// - `limits` — the [limits](ExecutionLimits) of the application, or simply the maximum number of steps to do.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::str;
+    /// use markovalgorithms::prelude::*;
+    ///
+    /// let scheme = AlgorithmSchemeBuilder::new()
+    ///     .with_alphabet(str::parse("ab").unwrap())
+    ///     .build_with_formula_definitions(["a→b", "b→a"].into_iter())
+    ///     .unwrap();
+    ///
+    /// let error = scheme.apply_with_cycle_detection("ba", 1_000).unwrap_err();
+    ///
+    /// let cycle = if let AlgorithmSchemeFullApplicationError::CycleDetected(cycle) = error {
+    ///     Some(cycle)
+    /// } else {
+    ///     None
+    /// }.unwrap();
+    ///
+    /// assert_eq!(1, cycle.start_step());
+    /// assert_eq!(2, cycle.period());
+    /// assert_eq!(["bb", "ab"], cycle.words());
+    /// ```
+    pub fn apply_with_cycle_detection<L>(
         &self,
         word: &str,
-        steps_limit: u32,
-    ) -> Result<FullApplicationResult, AlgorithmSchemeFullApplicationError> {
-        Self::assert_non_zero_limit(steps_limit)?;
+        limits: L,
+    ) -> Result<FullApplicationResult, AlgorithmSchemeFullApplicationError>
+    where
+        L: Into<ExecutionLimits>,
+    {
+        let limits = limits.into();
+
+        Self::assert_non_zero_limit(&limits)?;
 
         self.assert_valid_word(word).map_err(|error| {
             AlgorithmSchemeFullApplicationError::InputValidationError { source: error }
         })?;
 
-        let mut word = word.to_owned();
-        let mut steps_done = 0;
+        if self.rules.seed().is_some() {
+            return self.run(word, 0, &limits, |_| None);
+        }
 
-        while steps_done < steps_limit {
-            let result = self.apply_once_unsafe(&word);
+        let mut detector = CycleDetector::new(&self.encoding.encode(word));
 
-            steps_done += 1;
+        self.run(word, 0, &limits, |execution| {
+            detector
+                .observe(&execution.word)
+                .map(|period| self.locate_cycle(word, period))
+        })
+    }
 
-            match result {
-                SingleApplicationResult::Final(SingleApplicationData {
-                    word,
-                    applied_formula_definition: _,
-                }) => return Ok(FullApplicationResult { word, steps_done }),
-                SingleApplicationResult::Intermediate(SingleApplicationData {
-                    word: current_word,
-                    applied_formula_definition: _,
-                }) => {
-                    word = current_word;
-                }
-            }
+    /// Applies the algorithm scheme to each of the input strings, spreading the inputs across the available threads.
+    ///
+    /// The results are returned in the order of the inputs, an error of one input does not affect the others.
+    ///
+    /// # Arguments
+    /// - `words` — the input strings.
+    /// - `limits` — the [limits](ExecutionLimits) of each application, the steps and the word length are counted
+    ///   separately for every input, while the deadline and the cancellation token are shared by the whole batch.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::str;
+    /// use markovalgorithms::prelude::*;
+    ///
+    /// let scheme = AlgorithmSchemeBuilder::new()
+    ///     .with_alphabet(str::parse("ab").unwrap())
+    ///     .build_with_formula_definitions(["a→b"].into_iter())
+    ///     .unwrap();
+    ///
+    /// let results = scheme.apply_batch(&["a", "aaa", "c"], 2);
+    ///
+    /// assert_eq!("b", results[0].as_ref().unwrap().word());
+    /// assert!(matches!(
+    ///     results[1],
+    ///     Err(AlgorithmSchemeFullApplicationError::HitTheStepsLimit(_))
+    /// ));
+    /// assert!(matches!(
+    ///     results[2],
+    ///     Err(AlgorithmSchemeFullApplicationError::InputValidationError { .. })
+    /// ));
+    /// ```
+    pub fn apply_batch<W, L>(
+        &self,
+        words: &[W],
+        limits: L,
+    ) -> Vec<Result<FullApplicationResult, AlgorithmSchemeFullApplicationError>>
+    where
+        W: AsRef<str> + Sync,
+        L: Into<ExecutionLimits>,
+    {
+        let limits = limits.into();
+
+        let threads = thread::available_parallelism()
+            .map_or(1, NonZeroUsize::get)
+            .min(words.len());
+
+        if threads <= 1 {
+            return words
+                .iter()
+                .map(|word| self.apply(word.as_ref(), limits.clone()))
+                .collect();
         }
 
-        Err(AlgorithmSchemeFullApplicationError::HitTheStepsLimit(
-            steps_done,
-        ))
+        // The inputs are taken one by one, so a few long applications do not hold up a whole shard.
+        let next_input = AtomicUsize::new(0);
+
+        let shards: Vec<_> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut shard = Vec::new();
+
+                        loop {
+                            let input_index = next_input.fetch_add(1, Ordering::Relaxed);
+
+                            let Some(word) = words.get(input_index) else {
+                                break shard;
+                            };
+
+                            shard.push((input_index, self.apply(word.as_ref(), limits.clone())));
+                        }
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .map(|worker| {
+                    worker
+                        .join()
+                        .unwrap_or_else(|panic| panic::resume_unwind(panic))
+                })
+                .collect()
+        });
+
+        let mut results: Vec<_> = shards.into_iter().flatten().collect();
+        results.sort_unstable_by_key(|&(input_index, _)| input_index);
+
+        results.into_iter().map(|(_, result)| result).collect()
     }
 
     /// Gets an iterator that applies the algorithm scheme once to the input string on each iterator's step.
     pub fn get_application_iterator(
         &self,
         word: &str,
-    ) -> Result<ApplicationIterator, AlgorithmSchemeInputValidationError> {
+    ) -> Result<ApplicationIterator<'_>, AlgorithmSchemeInputValidationError> {
+        self.get_application_iterator_with_limits(word, ExecutionLimits::new())
+    }
+
+    /// Gets an iterator that applies the algorithm scheme once to the input string on each iterator's step,
+    /// until the algorithm is completed or one of the limits is exhausted.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::str;
+    /// use markovalgorithms::prelude::*;
+    ///
+    /// let scheme = AlgorithmSchemeBuilder::new()
+    ///     .with_alphabet(str::parse("ab").unwrap())
+    ///     .build_with_formula_definitions(["a→b"].into_iter())
+    ///     .unwrap();
+    ///
+    /// let mut iterator = scheme.get_application_iterator_with_limits("aaa", 2).unwrap();
+    ///
+    /// assert_eq!(2, iterator.by_ref().count());
+    /// assert!(matches!(
+    ///     iterator.interruption(),
+    ///     Some(AlgorithmSchemeFullApplicationError::HitTheStepsLimit(_))
+    /// ));
+    /// ```
+    pub fn get_application_iterator_with_limits<L>(
+        &self,
+        word: &str,
+        limits: L,
+    ) -> Result<ApplicationIterator<'_>, AlgorithmSchemeInputValidationError>
+    where
+        L: Into<ExecutionLimits>,
+    {
         self.assert_valid_word(word)?;
 
-        Ok(ApplicationIterator::new(self, word))
-    }
-
-    /// Applies the algorithm scheme once without checking the input.
-    fn apply_once_unsafe(&self, word: &str) -> SingleApplicationResult {
-        for (formula_index, formula) in self.substitution_formulas.iter().enumerate() {
-            match formula.apply(&self.store, word) {
-                Some(SubstitutionFormulaApplicationResult::Final(word)) => {
-                    return SingleApplicationResult::Final(SingleApplicationData {
-                        word,
-                        applied_formula_definition: Some(
-                            self.substitution_formulas[formula_index]
-                                .view()
-                                .peek_definition(&self.store),
-                        ),
-                    })
-                }
-                Some(SubstitutionFormulaApplicationResult::Intermediate(word)) => {
-                    return SingleApplicationResult::Intermediate(SingleApplicationData {
-                        word,
-                        applied_formula_definition: Some(
-                            self.substitution_formulas[formula_index]
-                                .view()
-                                .peek_definition(&self.store),
-                        ),
-                    })
+        Ok(ApplicationIterator::new(self, word, limits.into()))
+    }
+
+    /// Gets an iterator that shares the ownership of the scheme, so it can be moved to another thread
+    /// or outlive the current borrow of the scheme.
+    ///
+    /// # Arguments
+    /// - `word` — the input string.
+    /// - `limits` — the [limits](ExecutionLimits) of the application, or simply the maximum number of steps to do.
+    pub fn get_owned_application_iterator<L>(
+        self: &Arc<Self>,
+        word: &str,
+        limits: L,
+    ) -> Result<OwnedApplicationIterator, AlgorithmSchemeInputValidationError>
+    where
+        L: Into<ExecutionLimits>,
+    {
+        self.assert_valid_word(word)?;
+
+        Ok(OwnedApplicationIterator {
+            state: IterationState::new(self, word, limits.into()),
+            scheme: Arc::clone(self),
+        })
+    }
+
+    /// Gets an iterator that can also move the application backwards, see [ReversibleApplicationIterator](ReversibleApplicationIterator).
+    ///
+    /// # Arguments
+    /// - `word` — the input string.
+    /// - `limits` — the [limits](ExecutionLimits) of the application, or simply the maximum number of steps to do.
+    pub fn get_reversible_application_iterator<L>(
+        &self,
+        word: &str,
+        limits: L,
+    ) -> Result<ReversibleApplicationIterator<'_>, AlgorithmSchemeInputValidationError>
+    where
+        L: Into<ExecutionLimits>,
+    {
+        self.assert_valid_word(word)?;
+
+        Ok(ReversibleApplicationIterator {
+            state: IterationState::new(self, word, limits.into()),
+            scheme: self,
+            history: VecDeque::new(),
+            history_limit: None,
+            last_forgotten_step: None,
+        })
+    }
+
+    /// Restores an iterator from the snapshot of another iterator of the same scheme.
+    ///
+    /// # Arguments
+    /// - `snapshot` — the state of the application.
+    /// - `limits` — the [limits](ExecutionLimits) of the restored application, the steps are counted anew.
+    ///
+    /// # Errors
+    /// Returns an [error](ApplicationSnapshotRestorationError) if the snapshot has been taken from a different scheme
+    /// or its word contains unknown characters.
+    pub fn restore_application_iterator<L>(
+        &self,
+        snapshot: &ApplicationSnapshot,
+        limits: L,
+    ) -> Result<ApplicationIterator<'_>, ApplicationSnapshotRestorationError>
+    where
+        L: Into<ExecutionLimits>,
+    {
+        Ok(ApplicationIterator {
+            state: IterationState::restore(self, snapshot, limits.into())?,
+            scheme: self,
+        })
+    }
+
+    /// Restores an [owned iterator](OwnedApplicationIterator) from the snapshot of another iterator of the same scheme.
+    ///
+    /// # Errors
+    /// Returns an [error](ApplicationSnapshotRestorationError) if the snapshot has been taken from a different scheme
+    /// or its word contains unknown characters.
+    pub fn restore_owned_application_iterator<L>(
+        self: &Arc<Self>,
+        snapshot: &ApplicationSnapshot,
+        limits: L,
+    ) -> Result<OwnedApplicationIterator, ApplicationSnapshotRestorationError>
+    where
+        L: Into<ExecutionLimits>,
+    {
+        Ok(OwnedApplicationIterator {
+            state: IterationState::restore(self, snapshot, limits.into())?,
+            scheme: Arc::clone(self),
+        })
+    }
+
+    /// Gets an explorer of every word the formulas can rewrite a word into,
+    /// as if they were the rules of a semi-Thue system.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::str;
+    /// use markovalgorithms::prelude::*;
+    ///
+    /// let scheme = AlgorithmSchemeBuilder::new()
+    ///     .with_alphabet(str::parse("ab").unwrap())
+    ///     .build_with_formula_definitions(["a→⋅b", "b→a"].into_iter())
+    ///     .unwrap();
+    ///
+    /// assert_eq!("bb", scheme.apply("ab", 10).unwrap().word());
+    ///
+    /// let reachability = scheme
+    ///     .explorer()
+    ///     .can_reach("ab", "aa", ExplorationLimits::new())
+    ///     .unwrap();
+    ///
+    /// assert!(reachability.is_reachable());
+    /// ```
+    pub fn explorer(&self) -> SemiThueExplorer<'_> {
+        SemiThueExplorer::new(self)
+    }
+
+    /// Turns the formulas into the rules of a string rewriting system, in order to check or complete it.
+    ///
+    /// The order of the formulas and their finality are ignored, as any rule of a system
+    /// can rewrite the word at any occurrence of its left side.
+    ///
+    /// # Errors
+    /// Returns an [error](RewritingSystemDefinitionError) if a formula has variables or is anchored,
+    /// since it is not a rewriting rule of a fixed word.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::str;
+    /// use markovalgorithms::prelude::*;
+    ///
+    /// let scheme = AlgorithmSchemeBuilder::new()
+    ///     .with_alphabet(str::parse("ab").unwrap())
+    ///     .build_with_formula_definitions(["ba→ab", "aa→⋅"].into_iter())
+    ///     .unwrap();
+    ///
+    /// let system = scheme.rewriting_system().unwrap();
+    ///
+    /// assert!(system.check_confluence(&LengthLexicographic::new()).is_confluent());
+    /// assert_eq!("b", system.normal_form("aba"));
+    /// ```
+    pub fn rewriting_system(&self) -> Result<RewritingSystem, RewritingSystemDefinitionError> {
+        let rules = self
+            .substitution_formulas
+            .iter()
+            .map(|formula| {
+                let definition = || formula.view.peek_definition(&self.store).to_owned();
+
+                if formula.variables.is_some() {
+                    Err(RewritingSystemDefinitionError::FormulaWithVariables(
+                        definition(),
+                    ))
+                } else if formula.anchors.is_any() {
+                    Err(RewritingSystemDefinitionError::AnchoredFormula(definition()))
+                } else {
+                    Ok((
+                        formula.view.get_left(&self.store),
+                        formula.view.get_right(&self.store),
+                    ))
                 }
-                None => continue,
-            };
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(RewritingSystem::new(rules))
+    }
+
+    /// A hash of the alphabet, the special characters and the formulas of the scheme,
+    /// which stays the same across platforms and runs.
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    /// Does the steps until the algorithm is completed, a limit is exhausted, or a cycle is detected.
+    fn run<F>(
+        &self,
+        word: &str,
+        steps_done_before: u64,
+        limits: &ExecutionLimits,
+        detect_cycle: F,
+    ) -> Result<FullApplicationResult, AlgorithmSchemeFullApplicationError>
+    where
+        F: FnMut(&Execution<u8>) -> Option<ApplicationCycle>,
+    {
+        let mut execution = Execution::incremental(&self.rules, &self.encoding.encode(word))
+            .after_steps(steps_done_before);
+
+        match engine::run(&self.rules, &mut execution, limits, detect_cycle) {
+            RunOutcome::Completed { steps_done } => Ok(FullApplicationResult {
+                word: self.encoding.decode(&execution.word),
+                steps_done: steps_done_before.saturating_add(steps_done),
+            }),
+            RunOutcome::Interrupted {
+                limit,
+                steps_done,
+                last_applied_formula,
+            } => Err(limit.into_error(self.interrupt(
+                &execution,
+                steps_done_before.saturating_add(steps_done),
+                last_applied_formula,
+            ))),
+            RunOutcome::CycleDetected(cycle) => {
+                Err(AlgorithmSchemeFullApplicationError::CycleDetected(cycle))
+            }
         }
+    }
 
-        SingleApplicationResult::Final(SingleApplicationData {
-            word: word.to_owned(),
-            applied_formula_definition: None,
-        })
+    /// Captures the state of an execution that has to be stopped.
+    fn interrupt(
+        &self,
+        execution: &Execution<u8>,
+        steps_done: u64,
+        last_applied_formula: Option<usize>,
+    ) -> PartialApplicationResult {
+        PartialApplicationResult {
+            word: self.encoding.decode(&execution.word),
+            steps_done,
+            last_applied_formula_definition: last_applied_formula.map(|formula_index| {
+                self.substitution_formulas[formula_index]
+                    .view()
+                    .peek_definition(&self.store)
+                    .to_owned()
+            }),
+        }
+    }
+
+    /// Finds the first step of a cycle with the known period and collects the words of the cycle.
+    fn locate_cycle(&self, word: &str, period: u64) -> ApplicationCycle {
+        let (start_step, words) =
+            engine::locate_cycle(&self.rules, &self.encoding.encode(word), period);
+
+        ApplicationCycle {
+            start_step,
+            period,
+            words: words
+                .iter()
+                .map(|word| self.encoding.decode(word))
+                .collect(),
+        }
+    }
+
+    /// Materializes the result of a step that has just been done by the execution.
+    fn describe_step(
+        &self,
+        outcome: &StepOutcome<u8>,
+        step: u64,
+        execution: &Execution<u8>,
+    ) -> SingleApplicationResult<'_> {
+        let data = self.describe_word(Some(outcome), step, execution);
+
+        if outcome.is_final {
+            SingleApplicationResult::Final(data)
+        } else {
+            SingleApplicationResult::Intermediate(data)
+        }
+    }
+
+    /// Materializes the current word of the execution and the step that has produced it, if any.
+    fn describe_word(
+        &self,
+        outcome: Option<&StepOutcome<u8>>,
+        step: u64,
+        execution: &Execution<u8>,
+    ) -> SingleApplicationData<'_> {
+        let applied_formula = outcome.and_then(|outcome| outcome.applied_formula);
+
+        SingleApplicationData {
+            word: self.encoding.decode(&execution.word),
+            applied_formula_definition: applied_formula.map(|formula_index| {
+                self.substitution_formulas[formula_index]
+                    .view()
+                    .peek_definition(&self.store)
+            }),
+            substitution: outcome.and_then(|outcome| {
+                outcome.applied_formula.map(|formula_index| {
+                    self.describe_substitution(formula_index, outcome, execution)
+                })
+            }),
+            choice: outcome.and_then(|outcome| {
+                let seed = self.rules.seed()?;
+                let alternatives = outcome.alternatives.clone()?;
+
+                outcome.applied_formula.map(|chosen| StochasticChoice {
+                    seed,
+                    alternatives,
+                    chosen,
+                })
+            }),
+            step,
+            is_final: outcome.is_some_and(|outcome| outcome.is_final),
+        }
+    }
+
+    /// Locates the substitution in the word before and after it has been made.
+    fn describe_substitution(
+        &self,
+        formula_index: usize,
+        outcome: &StepOutcome<u8>,
+        execution: &Execution<u8>,
+    ) -> AppliedSubstitution {
+        let start = outcome.start;
+
+        // The lengths of the matched and the inserted parts in bytes and in characters.
+        let (matched, inserted) = match &outcome.bound {
+            Some(BoundSubstitution { matched, inserted }) => {
+                let inserted = start..start + inserted;
+
+                (
+                    (
+                        self.rules.byte_len(matched),
+                        matched
+                            .iter()
+                            .filter(|unit| unit.starts_character())
+                            .count(),
+                    ),
+                    (
+                        execution.byte_len_in(&self.rules, inserted.clone()),
+                        execution.word.char_count_in(inserted),
+                    ),
+                )
+            }
+            None => {
+                let view = self.substitution_formulas[formula_index].view();
+                let left = view.get_left(&self.store);
+                let right = view.get_right(&self.store);
+
+                (
+                    (left.len(), left.chars().count()),
+                    (right.len(), right.chars().count()),
+                )
+            }
+        };
+
+        // The part of the word before the substitution is the same in both words.
+        let start_byte = execution.byte_len_before(&self.rules, start);
+        let start_character = execution.word.char_count_in(0..start);
+
+        AppliedSubstitution {
+            formula_index,
+            matched_bytes: start_byte..start_byte + matched.0,
+            matched_characters: start_character..start_character + matched.1,
+            inserted_bytes: start_byte..start_byte + inserted.0,
+            inserted_characters: start_character..start_character + inserted.1,
+        }
     }
 
     fn assert_valid_word(&self, word: &str) -> Result<(), AlgorithmSchemeInputValidationError> {
@@ -224,8 +824,27 @@ impl AlgorithmScheme {
         }
     }
 
-    fn assert_non_zero_limit(steps_limit: u32) -> Result<(), AlgorithmSchemeFullApplicationError> {
-        if steps_limit == 0 {
+    /// Checks that an intermediate word, which may contain extension characters, belongs to the scheme.
+    fn assert_known_characters(
+        &self,
+        word: &str,
+    ) -> Result<(), AlgorithmSchemeInputValidationError> {
+        let unknown: String = word
+            .chars()
+            .filter(|&character| !self.properties.alphabet.contains_extended(character))
+            .collect();
+
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(AlgorithmSchemeInputValidationError::UnknownCharactersEncountered(unknown))
+        }
+    }
+
+    fn assert_non_zero_limit(
+        limits: &ExecutionLimits,
+    ) -> Result<(), AlgorithmSchemeFullApplicationError> {
+        if limits.steps() == Some(0) {
             Err(AlgorithmSchemeFullApplicationError::ZeroStepsLimit)
         } else {
             Ok(())
@@ -250,8 +869,23 @@ pub enum AlgorithmSchemeInputValidationError {
 #[derive(Error, Debug, PartialEq, Eq)]
 pub enum AlgorithmSchemeFullApplicationError {
     /// The executor stops after the limit of applications is reached.
-    #[error("the application is not completed after reaching step {0}")]
-    HitTheStepsLimit(u32),
+    #[error("the application is not completed after reaching step {}", .0.steps_done())]
+    HitTheStepsLimit(PartialApplicationResult),
+    /// The executor stops after an intermediate word exceeds the length limit.
+    #[error(
+        "the application is stopped at step {} because the word exceeds the length limit",
+        .0.steps_done()
+    )]
+    HitTheWordLengthLimit(PartialApplicationResult),
+    /// The executor stops after the deadline passes.
+    #[error(
+        "the application is not completed before the deadline, stopped at step {}",
+        .0.steps_done()
+    )]
+    HitTheDeadline(PartialApplicationResult),
+    /// The executor stops after the cancellation is requested.
+    #[error("the application is cancelled at step {}", .0.steps_done())]
+    Cancelled(PartialApplicationResult),
     /// Zero is not a valid steps limit.
     #[error("the algorithm should be allowed to do at least one step")]
     ZeroStepsLimit,
@@ -259,187 +893,939 @@ pub enum AlgorithmSchemeFullApplicationError {
     InputValidationError {
         source: AlgorithmSchemeInputValidationError,
     },
+    /// The word repeats itself, so the algorithm never completes.
+    #[error(
+        "the application has entered a cycle of {} steps that starts at step {}",
+        .0.period(),
+        .0.start_step()
+    )]
+    CycleDetected(ApplicationCycle),
 }
 
 /// An iterator that yields the results of the algorithm scheme application, one step at a time.
+///
+/// The iterator ends after the algorithm is completed or after one of the [limits](ExecutionLimits) is exhausted,
+/// in the latter case the reason is reported by [interruption](ApplicationIterator::interruption).
 #[derive(Debug)]
 pub struct ApplicationIterator<'a> {
-    word: String,
+    state: IterationState,
     scheme: &'a AlgorithmScheme,
-    is_completed: bool,
 }
 
 impl<'a> ApplicationIterator<'a> {
-    fn new(scheme: &'a AlgorithmScheme, word: &str) -> Self {
+    fn new(scheme: &'a AlgorithmScheme, word: &str, limits: ExecutionLimits) -> Self {
         ApplicationIterator {
-            word: word.to_owned(),
+            state: IterationState::new(scheme, word, limits),
             scheme,
-            is_completed: false,
         }
     }
+
+    /// The reason the iterator has stopped before the algorithm is completed, if any.
+    pub fn interruption(&self) -> Option<&AlgorithmSchemeFullApplicationError> {
+        self.state.interruption.as_ref()
+    }
+
+    /// Captures the state of the application, so it can be persisted and [restored](AlgorithmScheme::restore_application_iterator) later.
+    pub fn snapshot(&self) -> ApplicationSnapshot {
+        self.state.snapshot(self.scheme)
+    }
 }
 
 impl<'a> Iterator for ApplicationIterator<'a> {
     type Item = SingleApplicationData<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.is_completed {
-            None
-        } else {
-            let result = self
-                .scheme
-                .apply_once(&self.word)
-                .expect("All checks are applied prior to this point.");
-
-            Some(match result {
-                SingleApplicationResult::Final(data) => {
-                    self.is_completed = true;
-                    self.word = data.word.to_owned();
-                    data
-                }
-                SingleApplicationResult::Intermediate(data) => {
-                    self.word = data.word.to_owned();
-                    data
-                }
-            })
-        }
+        let outcome = self.state.advance(self.scheme)?;
+
+        Some(
+            self.scheme
+                .describe_step(
+                    &outcome,
+                    self.state.total_steps_done(),
+                    self.state.execution(),
+                )
+                .into_data(),
+        )
     }
 }
 
-/// An error in the definition of a substitution formula.
-#[derive(Debug, Error, PartialEq, Eq)]
-pub enum SubstitutionFormulaDefinitionError {
-    /// No delimiters are found in the formula definition.
-    #[error("no delimiters are found in the substitution formula \"{0}\"")]
-    NoDelimiterFound(String),
-    /// Multiple delimiters are found in the formula definition.
-    #[error("multiple delimiters are found in the substitution formula \"{0}\"")]
-    MultipleDelimitersFound(String, usize),
-    /// Final marker is on the left side of the subsstitution formula.
-    #[error("final marker is on the left side of the substitution formula \"{0}\"")]
-    FinalMarkerOnTheLeft(String),
-    /// Final marker is on the right side of the subsstitution formula.
-    #[error("Final marker is on the right side of the substitution formula \"{0}\"")]
-    FinalMarkerOnTheRight(String),
+/// An iterator that owns a shared reference to the scheme and yields owned results of the application,
+/// one step at a time.
+///
+/// Unlike [ApplicationIterator](ApplicationIterator), it can be moved to another thread or stored for later use.
+///
+/// # Example
+/// ```rust
+/// # use std::{str, sync::Arc, thread};
+/// use markovalgorithms::prelude::*;
+///
+/// let scheme = Arc::new(
+///     AlgorithmSchemeBuilder::new()
+///         .with_alphabet(str::parse("ab").unwrap())
+///         .build_with_formula_definitions(["a→b"].into_iter())
+///         .unwrap(),
+/// );
+///
+/// let iterator = scheme.get_owned_application_iterator("aa", 10).unwrap();
+///
+/// let words: Vec<_> = thread::spawn(move || iterator.map(|data| data.word().to_owned()).collect())
+///     .join()
+///     .unwrap();
+///
+/// assert_eq!(["ba", "bb", "bb"], words.as_slice());
+/// ```
+#[derive(Debug)]
+pub struct OwnedApplicationIterator {
+    state: IterationState,
+    scheme: Arc<AlgorithmScheme>,
 }
 
-/// The result of full algorithm scheme application.
-#[derive(Debug, PartialEq, Eq)]
-pub struct FullApplicationResult {
-    word: String,
-    steps_done: u32,
-}
+impl OwnedApplicationIterator {
+    /// The scheme the iterator applies.
+    pub fn scheme(&self) -> &Arc<AlgorithmScheme> {
+        &self.scheme
+    }
 
-impl FullApplicationResult {
-    /// Gets the output string.
-    pub fn word(&self) -> &str {
-        &self.word
+    /// The reason the iterator has stopped before the algorithm is completed, if any.
+    pub fn interruption(&self) -> Option<&AlgorithmSchemeFullApplicationError> {
+        self.state.interruption.as_ref()
     }
 
-    /// Reports the number of steps it took the algorithm to finish.
-    pub fn steps_done(&self) -> u32 {
-        self.steps_done
+    /// Captures the state of the application, so it can be persisted and [restored](AlgorithmScheme::restore_owned_application_iterator) later.
+    pub fn snapshot(&self) -> ApplicationSnapshot {
+        self.state.snapshot(&self.scheme)
     }
 }
 
-/// The result of a single algorithm scheme application.
-#[derive(Debug, PartialEq, Eq)]
-pub enum SingleApplicationResult<'a> {
-    /// The final result, the algorithm is finished.
-    Final(SingleApplicationData<'a>),
-    /// The intermediate result, the word can be processed again.
-    Intermediate(SingleApplicationData<'a>),
+impl Iterator for OwnedApplicationIterator {
+    type Item = OwnedSingleApplicationData;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let outcome = self.state.advance(&self.scheme)?;
+
+        Some(
+            self.scheme
+                .describe_step(
+                    &outcome,
+                    self.state.total_steps_done(),
+                    self.state.execution(),
+                )
+                .into_data()
+                .into(),
+        )
+    }
 }
 
-/// The data about a single algorithm scheme application.
-#[derive(Debug, PartialEq, Eq)]
-pub struct SingleApplicationData<'a> {
-    word: String,
-    applied_formula_definition: Option<&'a str>,
+/// An iterator that yields the results of the algorithm scheme application, one step at a time,
+/// and remembers the steps, so the application can be moved backwards.
+///
+/// Only the index of the applied formula and the position of the substitution are stored for each step,
+/// the previous words are recreated by reverting the substitutions.
+///
+/// # Example
+/// ```rust
+/// # use std::str;
+/// use markovalgorithms::prelude::*;
+///
+/// let scheme = AlgorithmSchemeBuilder::new()
+///     .with_alphabet(str::parse("abc").unwrap())
+///     .build_with_formula_definitions(["a→b", "bbb→⋅c"].into_iter())
+///     .unwrap();
+///
+/// let mut iterator = scheme
+///     .get_reversible_application_iterator("aaa", ExecutionLimits::new())
+///     .unwrap();
+///
+/// assert_eq!("c", iterator.by_ref().last().unwrap().word());
+/// assert_eq!("bbb", iterator.step_back().unwrap().word());
+/// assert_eq!("aaa", iterator.rewind_to(0).unwrap().word());
+/// assert_eq!("baa", iterator.next().unwrap().word());
+/// ```
+#[derive(Debug)]
+pub struct ReversibleApplicationIterator<'a> {
+    state: IterationState,
+    scheme: &'a AlgorithmScheme,
+    history: VecDeque<StepOutcome<u8>>,
+    history_limit: Option<usize>,
+    last_forgotten_step: Option<StepOutcome<u8>>,
 }
 
-impl<'a> SingleApplicationData<'a> {
-    /// The output word of a single application.
-    pub fn word(&self) -> &str {
-        &self.word
+impl<'a> ReversibleApplicationIterator<'a> {
+    /// Limits the number of steps that are remembered, the oldest steps are forgotten first.
+    ///
+    /// May be called multiple times in order to replace the prior limit.
+    pub fn with_history_limit(mut self, history_limit: usize) -> Self {
+        _ = self.history_limit.insert(history_limit);
+        self.forget_excess_steps();
+        self
     }
 
-    /// The substitution formula that has been used, if any.
-    pub fn applied_formula_definition(&self) -> Option<&'a str> {
-        self.applied_formula_definition
+    /// The number of steps done so far.
+    pub fn steps_done(&self) -> u64 {
+        self.state.total_steps_done()
+    }
+
+    /// The earliest step the iterator can be rewound to.
+    pub fn earliest_step(&self) -> u64 {
+        self.steps_done() - self.history.len() as u64
+    }
+
+    /// The reason the iterator has stopped before the algorithm is completed, if any.
+    pub fn interruption(&self) -> Option<&AlgorithmSchemeFullApplicationError> {
+        self.state.interruption.as_ref()
+    }
+
+    /// Reverts the last step.
+    ///
+    /// # Returns
+    /// The word before the last step and the formula that has produced it,
+    /// or `None` if there are no remembered steps.
+    pub fn step_back(&mut self) -> Option<SingleApplicationData<'a>> {
+        let outcome = self.history.pop_back()?;
+
+        let previous_outcome = self.history.back().or(self.last_forgotten_step.as_ref());
+
+        self.state
+            .step_back(self.scheme, &outcome, previous_outcome);
+
+        Some(self.describe_current_word(previous_outcome))
+    }
+
+    /// Reverts the steps until the number of the steps done equals to the requested one.
+    ///
+    /// # Returns
+    /// The word after the requested step and the formula that has produced it.
+    ///
+    /// # Errors
+    /// Returns an [error](ApplicationRewindError) if the step is not done yet or is already forgotten.
+    pub fn rewind_to(
+        &mut self,
+        step: u64,
+    ) -> Result<SingleApplicationData<'a>, ApplicationRewindError> {
+        if step > self.steps_done() {
+            return Err(ApplicationRewindError::StepIsAhead {
+                step,
+                steps_done: self.steps_done(),
+            });
+        }
+
+        if step < self.earliest_step() {
+            return Err(ApplicationRewindError::StepIsForgotten {
+                step,
+                earliest_step: self.earliest_step(),
+            });
+        }
+
+        while self.steps_done() > step {
+            _ = self.step_back();
+        }
+
+        let previous_outcome = self.history.back().or(self.last_forgotten_step.as_ref());
+
+        Ok(self.describe_current_word(previous_outcome))
+    }
+
+    fn describe_current_word(
+        &self,
+        outcome: Option<&StepOutcome<u8>>,
+    ) -> SingleApplicationData<'a> {
+        self.scheme
+            .describe_word(outcome, self.steps_done(), self.state.execution())
+    }
+
+    fn forget_excess_steps(&mut self) {
+        if let Some(history_limit) = self.history_limit {
+            while self.history.len() > history_limit {
+                self.last_forgotten_step = self.history.pop_front();
+            }
+        }
     }
 }
 
+impl<'a> Iterator for ReversibleApplicationIterator<'a> {
+    type Item = SingleApplicationData<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let outcome = self.state.advance(self.scheme)?;
+
+        let data = self
+            .scheme
+            .describe_step(
+                &outcome,
+                self.state.total_steps_done(),
+                self.state.execution(),
+            )
+            .into_data();
+
+        self.history.push_back(outcome);
+        self.forget_excess_steps();
+
+        Some(data)
+    }
+}
+
+/// An error of moving an application backwards.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ApplicationRewindError {
+    /// The requested step is not done yet.
+    #[error("cannot rewind to step {step}, only {steps_done} steps are done")]
+    StepIsAhead {
+        /// The requested step.
+        step: u64,
+        /// The number of steps done.
+        steps_done: u64,
+    },
+    /// The requested step is no longer remembered.
+    #[error("cannot rewind to step {step}, the earliest remembered step is {earliest_step}")]
+    StepIsForgotten {
+        /// The requested step.
+        step: u64,
+        /// The earliest step the application can be rewound to.
+        earliest_step: u64,
+    },
+}
+
+/// The progress of a step-by-step application of a string scheme, shared by the iterators.
 #[derive(Debug)]
-enum SubstitutionFormulaApplicationResult {
-    Final(String),
-    Intermediate(String),
+struct IterationState {
+    progress: Progress<u8>,
+    interruption: Option<AlgorithmSchemeFullApplicationError>,
+}
+
+impl IterationState {
+    fn new(scheme: &AlgorithmScheme, word: &str, limits: ExecutionLimits) -> Self {
+        Self {
+            progress: Progress::new(&scheme.rules, &scheme.encoding.encode(word), limits),
+            interruption: None,
+        }
+    }
+
+    /// Recreates the state from a snapshot, the limits are applied as if the application has just started.
+    fn restore(
+        scheme: &AlgorithmScheme,
+        snapshot: &ApplicationSnapshot,
+        limits: ExecutionLimits,
+    ) -> Result<Self, ApplicationSnapshotRestorationError> {
+        if snapshot.scheme_fingerprint != scheme.fingerprint {
+            return Err(ApplicationSnapshotRestorationError::SchemeMismatch {
+                expected: scheme.fingerprint,
+                found: snapshot.scheme_fingerprint,
+            });
+        }
+
+        scheme.assert_known_characters(&snapshot.word)?;
+
+        let mut state = Self::new(scheme, &snapshot.word, limits);
+
+        state.progress.is_completed = snapshot.is_completed;
+        state.progress.resume_after(snapshot.steps_done);
+        state.progress.last_applied_formula = snapshot
+            .last_applied_formula
+            .filter(|&formula_index| formula_index < scheme.rules.len());
+
+        Ok(state)
+    }
+
+    fn snapshot(&self, scheme: &AlgorithmScheme) -> ApplicationSnapshot {
+        ApplicationSnapshot {
+            scheme_fingerprint: scheme.fingerprint,
+            word: scheme.encoding.decode(&self.execution().word),
+            steps_done: self.total_steps_done(),
+            is_completed: self.progress.is_completed,
+            last_applied_formula: self.progress.last_applied_formula,
+        }
+    }
+
+    fn execution(&self) -> &Execution<u8> {
+        &self.progress.execution
+    }
+
+    fn total_steps_done(&self) -> u64 {
+        self.progress.total_steps_done()
+    }
+
+    /// Does the next step unless the algorithm is completed or a limit is exhausted.
+    fn advance(&mut self, scheme: &AlgorithmScheme) -> Option<StepOutcome<u8>> {
+        let outcome = self.progress.advance(&scheme.rules);
+
+        if let (None, Some(limit)) = (&self.interruption, self.progress.exhausted_limit) {
+            self.interruption = Some(limit.into_error(scheme.interrupt(
+                self.execution(),
+                self.total_steps_done(),
+                self.progress.last_applied_formula,
+            )));
+        }
+
+        outcome
+    }
+
+    /// Reverts the last step, the outcome of the step before it is needed to restore the last applied formula.
+    fn step_back(
+        &mut self,
+        scheme: &AlgorithmScheme,
+        outcome: &StepOutcome<u8>,
+        previous_outcome: Option<&StepOutcome<u8>>,
+    ) {
+        self.progress
+            .step_back(&scheme.rules, outcome, previous_outcome);
+        self.interruption = None;
+    }
+}
+
+/// An error in the definition of a substitution formula.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SubstitutionFormulaDefinitionError {
+    /// No delimiters are found in the formula definition.
+    #[error("no delimiters are found in the substitution formula \"{0}\"")]
+    NoDelimiterFound(String),
+    /// Multiple delimiters are found in the formula definition.
+    #[error("multiple delimiters are found in the substitution formula \"{0}\"")]
+    MultipleDelimitersFound(String, usize),
+    /// Final marker is on the left side of the subsstitution formula.
+    #[error("final marker is on the left side of the substitution formula \"{0}\"")]
+    FinalMarkerOnTheLeft(String),
+    /// Final marker is on the right side of the subsstitution formula.
+    #[error("Final marker is on the right side of the substitution formula \"{0}\"")]
+    FinalMarkerOnTheRight(String),
+    /// The escape character starts an unknown or incomplete escape sequence.
+    #[error("invalid escape sequence \"{1}\" is found in the substitution formula \"{0}\"")]
+    InvalidEscapeSequence(String, String),
+    /// The template bracket starts a binding that is not of the form `[name:characters]`, or closes no binding.
+    #[error("invalid template binding \"{1}\" is found in the substitution formula \"{0}\"")]
+    InvalidTemplateBinding(String, String),
+    /// The same variable is bound more than once in a template.
+    #[error("the variable '{1}' is bound more than once in the substitution formula \"{0}\"")]
+    DuplicateTemplateVariable(String, char),
+    /// The variable is malformed or is used with another kind than the one it is bound with.
+    #[error("invalid variable \"{1}\" is found in the substitution formula \"{0}\"")]
+    InvalidVariable(String, String),
+    /// The variable on the right side is not bound on the left side.
+    #[error(
+        "the variable \"{1}\" is not bound on the left side of the substitution formula \"{0}\""
+    )]
+    UnboundVariable(String, String),
+    /// The anchor is found elsewhere than at the start or at the end of the left side.
+    #[error("misplaced anchor '{1}' is found in the substitution formula \"{0}\"")]
+    MisplacedAnchor(String, char),
+    /// The marker of the group is found elsewhere than at the start of the formula definition.
+    #[error("misplaced group marker is found in the substitution formula \"{0}\"")]
+    MisplacedGroupMarker(String),
+}
+
+impl SubstitutionFormulaDefinitionError {
+    /// Replaces the formula definition the error points at, so the errors in the formulas generated from a template
+    /// point at the template.
+    fn with_definition(self, definition: &str) -> Self {
+        let definition = definition.to_owned();
+
+        match self {
+            Self::NoDelimiterFound(_) => Self::NoDelimiterFound(definition),
+            Self::MultipleDelimitersFound(_, count) => {
+                Self::MultipleDelimitersFound(definition, count)
+            }
+            Self::FinalMarkerOnTheLeft(_) => Self::FinalMarkerOnTheLeft(definition),
+            Self::FinalMarkerOnTheRight(_) => Self::FinalMarkerOnTheRight(definition),
+            Self::InvalidEscapeSequence(_, sequence) => {
+                Self::InvalidEscapeSequence(definition, sequence)
+            }
+            Self::InvalidTemplateBinding(_, binding) => {
+                Self::InvalidTemplateBinding(definition, binding)
+            }
+            Self::DuplicateTemplateVariable(_, name) => {
+                Self::DuplicateTemplateVariable(definition, name)
+            }
+            Self::InvalidVariable(_, variable) => Self::InvalidVariable(definition, variable),
+            Self::UnboundVariable(_, variable) => Self::UnboundVariable(definition, variable),
+            Self::MisplacedAnchor(_, anchor) => Self::MisplacedAnchor(definition, anchor),
+            Self::MisplacedGroupMarker(_) => Self::MisplacedGroupMarker(definition),
+        }
+    }
+}
+
+/// The result of full algorithm scheme application.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FullApplicationResult {
+    word: String,
+    steps_done: u64,
+}
+
+impl FullApplicationResult {
+    /// Gets the output string.
+    pub fn word(&self) -> &str {
+        &self.word
+    }
+
+    /// Reports the number of steps it took the algorithm to finish.
+    pub fn steps_done(&self) -> u64 {
+        self.steps_done
+    }
+}
+
+/// The state of an application that has been interrupted before the algorithm is completed.
+///
+/// The state can be passed to [resume](AlgorithmScheme::resume) to continue the application.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialApplicationResult {
+    word: String,
+    steps_done: u64,
+    last_applied_formula_definition: Option<String>,
+}
+
+impl PartialApplicationResult {
+    /// Gets the last intermediate string.
+    pub fn word(&self) -> &str {
+        &self.word
+    }
+
+    /// Reports the number of steps done before the application has been interrupted.
+    pub fn steps_done(&self) -> u64 {
+        self.steps_done
+    }
+
+    /// The substitution formula that has been applied on the last step, if any.
+    pub fn last_applied_formula_definition(&self) -> Option<&str> {
+        self.last_applied_formula_definition.as_deref()
+    }
+}
+
+/// A cycle of words that the application of a scheme never leaves.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ApplicationCycle {
+    start_step: u64,
+    period: u64,
+    words: Vec<String>,
+}
+
+impl ApplicationCycle {
+    /// The number of steps done before the first word of the cycle is reached.
+    pub fn start_step(&self) -> u64 {
+        self.start_step
+    }
+
+    /// The number of steps it takes to return to the same word.
+    pub fn period(&self) -> u64 {
+        self.period
+    }
+
+    /// The words of the cycle, starting with the first word that is repeated.
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+}
+
+/// The result of a single algorithm scheme application.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SingleApplicationResult<'a> {
+    /// The final result, the algorithm is finished.
+    Final(SingleApplicationData<'a>),
+    /// The intermediate result, the word can be processed again.
+    Intermediate(SingleApplicationData<'a>),
+}
+
+impl<'a> SingleApplicationResult<'a> {
+    fn into_data(self) -> SingleApplicationData<'a> {
+        match self {
+            Self::Final(data) | Self::Intermediate(data) => data,
+        }
+    }
+}
+
+/// The data about a single algorithm scheme application.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SingleApplicationData<'a> {
+    word: String,
+    applied_formula_definition: Option<&'a str>,
+    substitution: Option<AppliedSubstitution>,
+    choice: Option<StochasticChoice>,
+    step: u64,
+    is_final: bool,
+}
+
+impl<'a> SingleApplicationData<'a> {
+    /// The output word of a single application.
+    pub fn word(&self) -> &str {
+        &self.word
+    }
+
+    /// The substitution formula that has been used, if any.
+    pub fn applied_formula_definition(&self) -> Option<&'a str> {
+        self.applied_formula_definition
+    }
+
+    /// The index of the substitution formula that has been used, if any.
+    pub fn applied_formula_index(&self) -> Option<usize> {
+        self.substitution
+            .as_ref()
+            .map(|substitution| substitution.formula_index)
+    }
+
+    /// The details of the substitution that has been made, if any.
+    pub fn substitution(&self) -> Option<&AppliedSubstitution> {
+        self.substitution.as_ref()
+    }
+
+    /// The random choice of the applied formula, if the scheme chooses the formulas at random.
+    pub fn choice(&self) -> Option<&StochasticChoice> {
+        self.choice.as_ref()
+    }
+
+    /// The number of the step, counting from one.
+    pub fn step(&self) -> u64 {
+        self.step
+    }
+
+    /// Reports whether the algorithm has halted after the step.
+    pub fn is_final(&self) -> bool {
+        self.is_final
+    }
+}
+
+/// The data about a single algorithm scheme application that does not borrow the scheme.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedSingleApplicationData {
+    word: String,
+    applied_formula_definition: Option<String>,
+    substitution: Option<AppliedSubstitution>,
+    choice: Option<StochasticChoice>,
+    step: u64,
+    is_final: bool,
+}
+
+impl OwnedSingleApplicationData {
+    /// The output word of a single application.
+    pub fn word(&self) -> &str {
+        &self.word
+    }
+
+    /// The substitution formula that has been used, if any.
+    pub fn applied_formula_definition(&self) -> Option<&str> {
+        self.applied_formula_definition.as_deref()
+    }
+
+    /// The index of the substitution formula that has been used, if any.
+    pub fn applied_formula_index(&self) -> Option<usize> {
+        self.substitution
+            .as_ref()
+            .map(|substitution| substitution.formula_index)
+    }
+
+    /// The details of the substitution that has been made, if any.
+    pub fn substitution(&self) -> Option<&AppliedSubstitution> {
+        self.substitution.as_ref()
+    }
+
+    /// The random choice of the applied formula, if the scheme chooses the formulas at random.
+    pub fn choice(&self) -> Option<&StochasticChoice> {
+        self.choice.as_ref()
+    }
+
+    /// The number of the step, counting from one.
+    pub fn step(&self) -> u64 {
+        self.step
+    }
+
+    /// Reports whether the algorithm has halted after the step.
+    pub fn is_final(&self) -> bool {
+        self.is_final
+    }
+}
+
+impl From<SingleApplicationData<'_>> for OwnedSingleApplicationData {
+    fn from(data: SingleApplicationData<'_>) -> Self {
+        Self {
+            word: data.word,
+            applied_formula_definition: data.applied_formula_definition.map(str::to_owned),
+            substitution: data.substitution,
+            choice: data.choice,
+            step: data.step,
+            is_final: data.is_final,
+        }
+    }
+}
+
+/// The location of a substitution made by a single application.
+///
+/// The matched ranges point into the word before the substitution,
+/// and the inserted ranges point into the word after it.
+///
+/// # Example
+/// ```rust
+/// # use std::str;
+/// use markovalgorithms::prelude::*;
+///
+/// let scheme = AlgorithmSchemeBuilder::new()
+///     .with_alphabet(str::parse("abн").unwrap())
+///     .build_with_formula_definitions(["b→", "a→нн"].into_iter())
+///     .unwrap();
+///
+/// let result = scheme.apply_once("нaa").unwrap();
+///
+/// let result = if let SingleApplicationResult::Intermediate(result) = result {
+///     Some(result)
+/// } else {
+///     None
+/// }.unwrap();
+///
+/// let substitution = result.substitution().unwrap();
+///
+/// assert_eq!("нннa", result.word());
+/// assert_eq!(1, substitution.formula_index());
+/// assert_eq!(2..3, substitution.matched_bytes());
+/// assert_eq!(1..2, substitution.matched_characters());
+/// assert_eq!(2..6, substitution.inserted_bytes());
+/// assert_eq!(1..3, substitution.inserted_characters());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedSubstitution {
+    formula_index: usize,
+    matched_bytes: Range<usize>,
+    matched_characters: Range<usize>,
+    inserted_bytes: Range<usize>,
+    inserted_characters: Range<usize>,
+}
+
+impl AppliedSubstitution {
+    /// The index of the substitution formula in the order of the definitions.
+    pub fn formula_index(&self) -> usize {
+        self.formula_index
+    }
+
+    /// The range of bytes of the input word that has been replaced.
+    pub fn matched_bytes(&self) -> Range<usize> {
+        self.matched_bytes.clone()
+    }
+
+    /// The range of characters of the input word that has been replaced.
+    pub fn matched_characters(&self) -> Range<usize> {
+        self.matched_characters.clone()
+    }
+
+    /// The range of bytes of the output word that has been inserted.
+    pub fn inserted_bytes(&self) -> Range<usize> {
+        self.inserted_bytes.clone()
+    }
+
+    /// The range of characters of the output word that has been inserted.
+    pub fn inserted_characters(&self) -> Range<usize> {
+        self.inserted_characters.clone()
+    }
+}
+
+/// The random choice of the formula applied by a single application.
+///
+/// # Example
+/// ```rust
+/// # use std::str;
+/// use markovalgorithms::prelude::*;
+///
+/// let scheme = AlgorithmSchemeBuilder::new()
+///     .with_alphabet(str::parse("abc").unwrap())
+///     .with_stochastic_selection(7)
+///     .build_with_weighted_formula_definitions([("a→b", 1), ("a→c", 3), ("b→c", 1)].into_iter())
+///     .unwrap();
+///
+/// let data = scheme.get_application_iterator("aa").unwrap().next().unwrap();
+/// let choice = data.choice().unwrap();
+///
+/// assert_eq!(7, choice.seed());
+/// assert_eq!([0, 1], choice.alternatives());
+/// assert_eq!(data.applied_formula_index(), Some(choice.chosen()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StochasticChoice {
+    seed: u64,
+    alternatives: Vec<usize>,
+    chosen: usize,
+}
+
+impl StochasticChoice {
+    /// The seed of the randomness of the scheme.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The indices of the applicable formulas the applied one has been chosen from, in the order of the definitions.
+    pub fn alternatives(&self) -> &[usize] {
+        &self.alternatives
+    }
+
+    /// The index of the chosen formula.
+    pub fn chosen(&self) -> usize {
+        self.chosen
+    }
 }
 
 #[derive(Debug)]
 struct SchemeProperties {
-    delimiter: char,
-    final_marker: char,
+    delimiter: String,
+    final_marker: String,
+    escape_character: Option<char>,
+    template_brackets: Option<(char, char)>,
+    variables: bool,
+    anchors: bool,
+    match_strategy: MatchStrategy,
+    /// The seed of the randomness, if the formula to apply is chosen at random.
+    stochastic_seed: Option<u64>,
+    /// The way the ties inside the groups of formulas are broken, if the formulas may be grouped.
+    priority_groups: Option<GroupTieBreak>,
     alphabet: Alphabet,
 }
 
+impl SchemeProperties {
+    /// Checks whether the character is a part of the delimiter, the final marker, a template bracket,
+    /// the separator of the variables, an anchor or the marker of the group,
+    /// so it has to be escaped to stand for itself.
+    fn is_reserved(&self, character: char) -> bool {
+        self.delimiter.contains(character)
+            || self.final_marker.contains(character)
+            || self
+                .template_brackets
+                .is_some_and(|(open, close)| character == open || character == close)
+            || self.variables && character == FormulaVariables::SEPARATOR
+            || self.anchors && SubstitutionFormula::ANCHORS.contains(&character)
+            || self.priority_groups.is_some() && character == SubstitutionFormula::GROUP_MARKER
+    }
+
+    /// Writes the text so that it is read back as the same characters, escaping the ones with a special meaning.
+    ///
+    /// # Returns
+    /// `None` if the text contains the reserved characters and there is no escape character.
+    fn escape(&self, text: &str) -> Option<String> {
+        let mut escaped = String::with_capacity(text.len());
+
+        for character in text.chars() {
+            // Escaping every character of the delimiter and the final marker is more than enough,
+            // but it is the simplest way to never form them across the sides of a formula.
+            let is_special = self.is_reserved(character);
+
+            match self.escape_character {
+                None if is_special => return None,
+                None => escaped.push(character),
+                Some(escape) => {
+                    if is_special || character == escape {
+                        escaped.push(escape);
+                        escaped.push(character);
+                    } else if let Some(name) = FormulaLexer::name_of(character) {
+                        escaped.push(escape);
+                        escaped.push(name);
+                    } else if character.is_control() {
+                        escaped.push(escape);
+                        escaped.push_str(&format!("u{{{:x}}}", u32::from(character)));
+                    } else {
+                        escaped.push(character);
+                    }
+                }
+            }
+        }
+
+        Some(escaped)
+    }
+}
+
 #[derive(Debug)]
 struct SubstitutionFormula {
     view: FormulaView,
     is_final: bool,
+    /// The variables of the formula, if it has any.
+    variables: Option<FormulaVariables>,
+    /// The boundaries of the word the left side is anchored to.
+    anchors: Anchors,
+    /// The weight of the formula when the formula to apply is chosen at random.
+    weight: u32,
+    /// Whether the formula has the same priority as the formula before it.
+    joins_group: bool,
 }
 
 impl SubstitutionFormula {
+    /// The characters that anchor the left side to the start and to the end of the word.
+    const ANCHORS: [char; 2] = ['^', '$'];
+
+    /// The character that starts the definition of a formula with the same priority as the formula before it.
+    const GROUP_MARKER: char = '|';
+
+    /// Parses the definition that occupies the range of the store,
+    /// the sides that contain escape sequences are unescaped into the end of the store.
     fn new(
         properties: &SchemeProperties,
-        store: &str,
+        store: &mut String,
         range: Range<usize>,
     ) -> Result<Self, SubstitutionFormulaDefinitionError> {
         let formula_definition = &store[range.clone()];
 
-        let assertions = FormulaAssertions {
+        let lexer = FormulaLexer {
             formula_definition,
             properties,
         };
 
-        assertions.assert_single_simple_delimiter()?;
+        let characters = lexer.tokenize()?;
 
-        let parser = FormulaParser {
+        let assertions = FormulaAssertions {
             formula_definition,
             properties,
         };
 
+        let delimiter_index = assertions.assert_single_simple_delimiter(&characters)?;
+
+        let parser = FormulaParser { properties };
+
         let ParseResult {
             is_final,
-            left_end,
-            right_start,
-        } = parser.parse();
+            joins_group,
+            anchors,
+            left,
+            right,
+        } = parser.parse(&characters, delimiter_index);
 
-        let view = FormulaView::new(range, left_end, right_start);
+        assertions.assert_no_more_final_markers(left, right)?;
 
-        assertions.assert_no_more_final_markers(view.get_left(store), view.get_right(store))?;
+        if properties.anchors {
+            assertions.assert_no_more_anchors(left, right)?;
+        }
 
-        Ok(SubstitutionFormula { view, is_final })
-    }
+        if properties.priority_groups.is_some() {
+            assertions.assert_no_more_group_markers(left, right)?;
+        }
 
-    pub fn apply(&self, store: &str, word: &str) -> Option<SubstitutionFormulaApplicationResult> {
-        let left = self.view.get_left(store);
-        let right = self.view.get_right(store);
+        let variables = if properties.variables {
+            FormulaVariables::parse(formula_definition, properties, left, right)?
+        } else {
+            None
+        };
 
-        if word.contains(left) {
-            let substitution_result = word.replacen(left, right, 1);
+        let start = range.start;
+        let left = Self::store_side(store, start, left);
+        let right = Self::store_side(store, start, right);
 
-            if self.is_final {
-                Some(SubstitutionFormulaApplicationResult::Final(
-                    substitution_result,
-                ))
-            } else {
-                Some(SubstitutionFormulaApplicationResult::Intermediate(
-                    substitution_result,
-                ))
-            }
+        Ok(SubstitutionFormula {
+            view: FormulaView {
+                definition: range,
+                left,
+                right,
+            },
+            is_final,
+            variables,
+            anchors,
+            weight: 1,
+            joins_group,
+        })
+    }
+
+    /// Finds the side of the formula in the store, appending it to the store if it has to be unescaped.
+    fn store_side(
+        store: &mut String,
+        definition_start: usize,
+        side: &[DefinitionCharacter],
+    ) -> Range<usize> {
+        if side.iter().any(|character| character.is_escaped) {
+            let start = store.len();
+            store.extend(side.iter().map(|character| character.value));
+            start..store.len()
         } else {
-            None
+            let start = side.first().map_or(0, |character| character.position.start);
+            let end = side
+                .last()
+                .map_or(start, |character| character.position.end);
+            definition_start + start..definition_start + end
         }
     }
 
@@ -448,103 +1834,309 @@ impl SubstitutionFormula {
     }
 }
 
+/// A character of a formula definition, either written as is or as an escape sequence.
+#[derive(Debug, Clone)]
+struct DefinitionCharacter {
+    value: char,
+    /// The bytes of the definition the character is written with.
+    position: Range<usize>,
+    is_escaped: bool,
+}
+
+impl DefinitionCharacter {
+    fn is(&self, character: char) -> bool {
+        !self.is_escaped && self.value == character
+    }
+
+    /// Checks whether the characters start with the text, written without escape sequences.
+    fn starts_with(characters: &[Self], text: &str) -> bool {
+        let mut characters = characters.iter();
+
+        text.chars().all(|expected| {
+            characters
+                .next()
+                .is_some_and(|character| character.is(expected))
+        })
+    }
+
+    /// Checks whether the text, written without escape sequences, occurs among the characters.
+    fn contains(characters: &[Self], text: &str) -> bool {
+        (0..characters.len()).any(|start| Self::starts_with(&characters[start..], text))
+    }
+}
+
+struct FormulaLexer<'a> {
+    formula_definition: &'a str,
+    properties: &'a SchemeProperties,
+}
+
+impl<'a> FormulaLexer<'a> {
+    /// The named escape sequences and the characters they stand for.
+    const NAMED_SEQUENCES: [(char, char); 3] = [('n', '\n'), ('r', '\r'), ('t', '\t')];
+
+    fn name_of(character: char) -> Option<char> {
+        Self::NAMED_SEQUENCES
+            .iter()
+            .find(|&&(_, value)| value == character)
+            .map(|&(name, _)| name)
+    }
+
+    /// Splits the definition into characters, translating the escape sequences.
+    ///
+    /// The escape character followed by itself or by a character of the delimiter, the final marker
+    /// or a template bracket stands for that character.
+    /// It can also be followed by `n`, `r`, `t` or `u{...}` with a hexadecimal code point.
+    fn tokenize(&self) -> Result<Vec<DefinitionCharacter>, SubstitutionFormulaDefinitionError> {
+        let mut characters = Vec::new();
+        let mut iterator = self.formula_definition.char_indices().peekable();
+
+        while let Some((start, character)) = iterator.next() {
+            if Some(character) != self.properties.escape_character {
+                characters.push(DefinitionCharacter {
+                    value: character,
+                    position: start..start + character.len_utf8(),
+                    is_escaped: false,
+                });
+                continue;
+            }
+
+            let invalid = |end: usize| {
+                SubstitutionFormulaDefinitionError::InvalidEscapeSequence(
+                    self.formula_definition.to_owned(),
+                    self.formula_definition[start..end].to_owned(),
+                )
+            };
+
+            let Some((name_start, name)) = iterator.next() else {
+                return Err(invalid(self.formula_definition.len()));
+            };
+
+            let mut end = name_start + name.len_utf8();
+
+            let value = if name == character || self.properties.is_reserved(name) {
+                name
+            } else if let Some(&(_, value)) =
+                Self::NAMED_SEQUENCES.iter().find(|&&(key, _)| key == name)
+            {
+                value
+            } else if name == 'u' && iterator.next_if(|&(_, next)| next == '{').is_some() {
+                let digits_start = end + 1;
+
+                while iterator
+                    .next_if(|&(_, next)| next.is_ascii_hexdigit())
+                    .is_some()
+                {}
+
+                let Some((closing, _)) = iterator.next_if(|&(_, next)| next == '}') else {
+                    let end = iterator
+                        .peek()
+                        .map_or(self.formula_definition.len(), |&(end, _)| end);
+                    return Err(invalid(end));
+                };
+
+                end = closing + 1;
+
+                u32::from_str_radix(&self.formula_definition[digits_start..closing], 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| invalid(end))?
+            } else {
+                return Err(invalid(end));
+            };
+
+            characters.push(DefinitionCharacter {
+                value,
+                position: start..end,
+                is_escaped: true,
+            });
+        }
+
+        Ok(characters)
+    }
+}
+
 struct FormulaAssertions<'a> {
     formula_definition: &'a str,
     properties: &'a SchemeProperties,
 }
 
 impl<'a> FormulaAssertions<'a> {
-    fn assert_single_simple_delimiter(&self) -> Result<(), SubstitutionFormulaDefinitionError> {
-        match self
-            .formula_definition
-            .match_indices(self.properties.delimiter)
-            .count()
-        {
-            0 => Err(SubstitutionFormulaDefinitionError::NoDelimiterFound(
+    /// Checks that there is exactly one delimiter that is not escaped, the occurrences are counted without overlaps.
+    ///
+    /// # Returns
+    /// The index of the first character of the delimiter among the characters.
+    fn assert_single_simple_delimiter(
+        &self,
+        characters: &[DefinitionCharacter],
+    ) -> Result<usize, SubstitutionFormulaDefinitionError> {
+        let delimiter = &self.properties.delimiter;
+        let delimiter_length = delimiter.chars().count();
+
+        let mut delimiters = Vec::new();
+        let mut index = 0;
+
+        while index < characters.len() {
+            if DefinitionCharacter::starts_with(&characters[index..], delimiter) {
+                delimiters.push(index);
+                index += delimiter_length;
+            } else {
+                index += 1;
+            }
+        }
+
+        let mut delimiters = delimiters.into_iter();
+
+        match (delimiters.next(), delimiters.count()) {
+            (None, _) => Err(SubstitutionFormulaDefinitionError::NoDelimiterFound(
                 self.formula_definition.to_owned(),
             )),
-            1 => Ok(()),
-            n => Err(SubstitutionFormulaDefinitionError::MultipleDelimitersFound(
+            (Some(index), 0) => Ok(index),
+            (Some(_), n) => Err(SubstitutionFormulaDefinitionError::MultipleDelimitersFound(
                 self.formula_definition.to_owned(),
-                n,
+                n + 1,
             )),
         }
     }
 
     fn assert_no_more_final_markers(
         &self,
-        left: &str,
-        right: &str,
+        left: &[DefinitionCharacter],
+        right: &[DefinitionCharacter],
     ) -> Result<(), SubstitutionFormulaDefinitionError> {
-        if left.contains(self.properties.final_marker) {
+        let contains_final_marker = |side: &[DefinitionCharacter]| {
+            DefinitionCharacter::contains(side, &self.properties.final_marker)
+        };
+
+        if contains_final_marker(left) {
             return Err(SubstitutionFormulaDefinitionError::FinalMarkerOnTheLeft(
                 self.formula_definition.to_owned(),
             ));
         }
-        if right.contains(self.properties.final_marker) {
+        if contains_final_marker(right) {
             return Err(SubstitutionFormulaDefinitionError::FinalMarkerOnTheRight(
                 self.formula_definition.to_owned(),
             ));
         }
         Ok(())
     }
+
+    /// Checks that the anchors that remain after the parsing are escaped.
+    fn assert_no_more_anchors(
+        &self,
+        left: &[DefinitionCharacter],
+        right: &[DefinitionCharacter],
+    ) -> Result<(), SubstitutionFormulaDefinitionError> {
+        left.iter()
+            .chain(right)
+            .find_map(|character| {
+                SubstitutionFormula::ANCHORS
+                    .into_iter()
+                    .find(|&anchor| character.is(anchor))
+            })
+            .map_or(Ok(()), |anchor| {
+                Err(SubstitutionFormulaDefinitionError::MisplacedAnchor(
+                    self.formula_definition.to_owned(),
+                    anchor,
+                ))
+            })
+    }
+
+    /// Checks that the markers of the group that remain after the parsing are escaped.
+    fn assert_no_more_group_markers(
+        &self,
+        left: &[DefinitionCharacter],
+        right: &[DefinitionCharacter],
+    ) -> Result<(), SubstitutionFormulaDefinitionError> {
+        if left
+            .iter()
+            .chain(right)
+            .any(|character| character.is(SubstitutionFormula::GROUP_MARKER))
+        {
+            Err(SubstitutionFormulaDefinitionError::MisplacedGroupMarker(
+                self.formula_definition.to_owned(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
 }
 
 struct FormulaParser<'a> {
-    formula_definition: &'a str,
     properties: &'a SchemeProperties,
 }
 
 impl<'a> FormulaParser<'a> {
-    fn parse(&self) -> ParseResult {
-        let mut final_delimiter = String::new();
-        final_delimiter.push(self.properties.delimiter);
-        final_delimiter.push(self.properties.final_marker);
-
-        let is_final = self.formula_definition.contains(&final_delimiter);
+    fn parse<'c>(
+        &self,
+        characters: &'c [DefinitionCharacter],
+        delimiter_index: usize,
+    ) -> ParseResult<'c> {
+        let (left, rest) = characters.split_at(delimiter_index);
+        let rest = &rest[self.properties.delimiter.chars().count()..];
+
+        let is_final = DefinitionCharacter::starts_with(rest, &self.properties.final_marker);
+
+        let [start_anchor, end_anchor] = SubstitutionFormula::ANCHORS;
+        let mut anchors = Anchors::default();
+        let mut joins_group = false;
+        let mut left = left;
+
+        if self.properties.priority_groups.is_some() {
+            if let Some((first, rest)) = left.split_first() {
+                if first.is(SubstitutionFormula::GROUP_MARKER) {
+                    joins_group = true;
+                    left = rest;
+                }
+            }
+        }
 
-        let splitted: Vec<_> = if is_final {
-            self.formula_definition.split(&final_delimiter).collect()
-        } else {
-            self.formula_definition
-                .split(self.properties.delimiter)
-                .collect()
-        };
+        if self.properties.anchors {
+            if let Some((first, rest)) = left.split_first() {
+                if first.is(start_anchor) {
+                    anchors.start = true;
+                    left = rest;
+                }
+            }
+            if let Some((last, rest)) = left.split_last() {
+                if last.is(end_anchor) {
+                    anchors.end = true;
+                    left = rest;
+                }
+            }
+        }
 
         ParseResult {
             is_final,
-            left_end: splitted[0].len(),
-            right_start: self
-                .formula_definition
-                .rfind(splitted[1])
-                .expect("The splitted substring is definitely in the original slice."),
+            joins_group,
+            anchors,
+            left,
+            right: if is_final {
+                &rest[self.properties.final_marker.chars().count()..]
+            } else {
+                rest
+            },
         }
     }
 }
 
 #[derive(Debug)]
-struct ParseResult {
+struct ParseResult<'c> {
     is_final: bool,
-    left_end: usize,
-    right_start: usize,
+    /// Whether the definition starts with the marker of the group.
+    joins_group: bool,
+    anchors: Anchors,
+    left: &'c [DefinitionCharacter],
+    right: &'c [DefinitionCharacter],
 }
 
 #[derive(Debug)]
 struct FormulaView {
+    definition: Range<usize>,
     left: Range<usize>,
     right: Range<usize>,
 }
 
 impl FormulaView {
-    fn new(range: Range<usize>, left_end: usize, right_start: usize) -> Self {
-        let (start, end) = (range.start, range.end);
-
-        Self {
-            left: start..start + left_end,
-            right: start + right_start..end,
-        }
-    }
-
     fn get_left<'a>(&'a self, store: &'a str) -> &'a str {
         &store[self.left.clone()]
     }
@@ -554,6 +2146,6 @@ impl FormulaView {
     }
 
     fn peek_definition<'a>(&'a self, store: &'a str) -> &'a str {
-        &store[self.left.clone().start..self.right.clone().end]
+        &store[self.definition.clone()]
     }
 }
@@ -0,0 +1,312 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! [Pattern](Pattern), the sides of a substitution formula with variables.
+
+#[cfg(test)]
+mod tests;
+
+use std::ops::Range;
+
+use super::word_buffer::{Unit, WordBuffer};
+
+/// The sides of a formula with variables, the variables of the left side are bound to the parts of the word
+/// and the right side is built from their values.
+///
+/// A symbol variable stands for a single character and a segment variable stands for any number of characters.
+/// A variable that occurs several times on the left side stands for the same characters each time.
+/// The left side may be anchored to the start or to the end of the word.
+#[derive(Debug)]
+pub(crate) struct Pattern<U> {
+    left: Vec<PatternItem<U>>,
+    right: Vec<PatternItem<U>>,
+    variables: usize,
+    anchors: Anchors,
+}
+
+/// The boundaries of the word the left side of a formula should touch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Anchors {
+    pub(crate) start: bool,
+    pub(crate) end: bool,
+}
+
+impl Anchors {
+    /// Checks whether the left side is anchored to any boundary.
+    pub(crate) fn is_any(self) -> bool {
+        self.start || self.end
+    }
+}
+
+/// An item of a side of a pattern, the variables are numbered from zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PatternItem<U> {
+    Unit(U),
+    Symbol(usize),
+    Segment(usize),
+}
+
+/// The occurrence of the left side of a pattern and the right side built for it.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct PatternOccurrence<U> {
+    pub(crate) range: Range<usize>,
+    pub(crate) replacement: Vec<U>,
+}
+
+impl<U: Unit> Pattern<U> {
+    /// Creates a pattern, every variable of the right side is expected to occur on the left side.
+    pub(crate) fn new(left: Vec<PatternItem<U>>, right: Vec<PatternItem<U>>) -> Self {
+        let variables = left
+            .iter()
+            .chain(&right)
+            .filter_map(|item| match item {
+                PatternItem::Unit(_) => None,
+                PatternItem::Symbol(variable) | PatternItem::Segment(variable) => {
+                    Some(variable + 1)
+                }
+            })
+            .max()
+            .unwrap_or(0);
+
+        Self {
+            left,
+            right,
+            variables,
+            anchors: Anchors::default(),
+        }
+    }
+
+    /// Creates a pattern without variables.
+    pub(crate) fn literal(left: &[U], right: &[U]) -> Self {
+        let items = |side: &[U]| side.iter().map(|&unit| PatternItem::Unit(unit)).collect();
+
+        Self::new(items(left), items(right))
+    }
+
+    /// Anchors the left side to the boundaries of the word.
+    pub(crate) fn with_anchors(mut self, anchors: Anchors) -> Self {
+        self.anchors = anchors;
+        self
+    }
+
+    /// Finds the leftmost occurrence of the left side.
+    ///
+    /// Among the occurrences that start at the same position, the segment variables are bound
+    /// from left to right, each to the shortest segment that lets the rest of the left side match.
+    pub(crate) fn find(&self, word: &WordBuffer<U>) -> Option<PatternOccurrence<U>> {
+        self.find_from(word, false)
+    }
+
+    /// Finds the rightmost occurrence of the left side, the segment variables are bound the same way.
+    pub(crate) fn find_last(&self, word: &WordBuffer<U>) -> Option<PatternOccurrence<U>> {
+        self.find_from(word, true)
+    }
+
+    /// Finds every occurrence of the left side with every binding of the variables,
+    /// ordered by their starts and then by the order the bindings are tried in.
+    pub(crate) fn find_all(&self, word: &WordBuffer<U>) -> Vec<PatternOccurrence<U>> {
+        let matching = Matching {
+            pattern: self,
+            word,
+        };
+        let mut bindings = vec![0..0; self.variables];
+        let mut occurrences = Vec::new();
+
+        for start in self.starts(word) {
+            if !matching.is_boundary(start) {
+                continue;
+            }
+
+            _ = matching.match_items(&self.left, start, &mut bindings, &mut |end, bindings| {
+                occurrences.push(PatternOccurrence {
+                    range: start..end,
+                    replacement: self.build_right(word, bindings),
+                });
+                false
+            });
+        }
+
+        occurrences
+    }
+
+    fn find_from(&self, word: &WordBuffer<U>, from_end: bool) -> Option<PatternOccurrence<U>> {
+        let matching = Matching {
+            pattern: self,
+            word,
+        };
+        let mut bindings = vec![0..0; self.variables];
+
+        let mut occurrence_at = |start| {
+            let end = matching.match_items(&self.left, start, &mut bindings, &mut |_, _| true)?;
+
+            Some(PatternOccurrence {
+                range: start..end,
+                replacement: self.build_right(word, &bindings),
+            })
+        };
+
+        let mut starts = self
+            .starts(word)
+            .filter(|&start| matching.is_boundary(start));
+
+        if from_end {
+            starts.rev().find_map(&mut occurrence_at)
+        } else {
+            starts.find_map(&mut occurrence_at)
+        }
+    }
+
+    /// The positions the left side may start at, some of them may fall inside the characters.
+    fn starts(&self, word: &WordBuffer<U>) -> Range<usize> {
+        if self.anchors.start {
+            0..1
+        } else if let (true, Some(length)) = (self.anchors.end, self.fixed_length()) {
+            // The left side without variables can only start at a single position.
+            match word.len().checked_sub(length) {
+                Some(start) => start..start + 1,
+                None => 0..0,
+            }
+        } else {
+            0..word.len() + 1
+        }
+    }
+
+    /// The length of the left side in units, if it has no variables.
+    fn fixed_length(&self) -> Option<usize> {
+        self.left
+            .iter()
+            .all(|item| matches!(item, PatternItem::Unit(_)))
+            .then_some(self.left.len())
+    }
+
+    fn build_right(&self, word: &WordBuffer<U>, bindings: &[Range<usize>]) -> Vec<U> {
+        let mut replacement = Vec::new();
+
+        for item in &self.right {
+            match *item {
+                PatternItem::Unit(unit) => replacement.push(unit),
+                PatternItem::Symbol(variable) | PatternItem::Segment(variable) => {
+                    replacement.extend(word.units_in(bindings[variable].clone()))
+                }
+            }
+        }
+
+        replacement
+    }
+}
+
+/// A backtracking search for an occurrence of the left side that starts at a position.
+struct Matching<'a, U> {
+    pattern: &'a Pattern<U>,
+    word: &'a WordBuffer<U>,
+}
+
+impl<'a, U: Unit> Matching<'a, U> {
+    /// Checks whether a character starts at the position, the end of the word is a boundary too.
+    fn is_boundary(&self, position: usize) -> bool {
+        position == self.word.len() || self.word.unit(position).starts_character()
+    }
+
+    /// The position where the character that starts at the position ends.
+    fn next_boundary(&self, position: usize) -> usize {
+        (position + 1..=self.word.len())
+            .find(|&next| self.is_boundary(next))
+            .unwrap_or(self.word.len())
+    }
+
+    /// Matches the items from the position, the bindings of the variables that first occur among them are overwritten.
+    ///
+    /// Each match is offered to the callback with the position where the items end and the bindings,
+    /// the search backtracks to the next match unless the callback accepts the offered one.
+    ///
+    /// # Returns
+    /// The position where the items of the accepted match end.
+    fn match_items(
+        &self,
+        items: &[PatternItem<U>],
+        position: usize,
+        bindings: &mut [Range<usize>],
+        accept: &mut dyn FnMut(usize, &[Range<usize>]) -> bool,
+    ) -> Option<usize> {
+        let Some((item, rest)) = items.split_first() else {
+            return (!self.pattern.anchors.end || position == self.word.len())
+                .then_some(position)
+                .filter(|&end| accept(end, bindings));
+        };
+
+        match *item {
+            PatternItem::Unit(unit) => (position < self.word.len()
+                && self.word.unit(position) == unit)
+                .then(|| self.match_items(rest, position + 1, bindings, accept))
+                .flatten(),
+            PatternItem::Symbol(variable) | PatternItem::Segment(variable)
+                if self.is_bound(variable, items) =>
+            {
+                let end = self.match_bound(bindings[variable].clone(), position)?;
+                self.match_items(rest, end, bindings, accept)
+            }
+            PatternItem::Symbol(variable) => {
+                if position == self.word.len() {
+                    return None;
+                }
+
+                bindings[variable] = position..self.next_boundary(position);
+                self.match_items(rest, bindings[variable].end, bindings, accept)
+            }
+            PatternItem::Segment(variable) => {
+                let mut end = position;
+
+                loop {
+                    bindings[variable] = position..end;
+
+                    if let Some(rest_end) = self.match_items(rest, end, bindings, accept) {
+                        return Some(rest_end);
+                    }
+
+                    if end == self.word.len() {
+                        return None;
+                    }
+
+                    end = self.next_boundary(end);
+                }
+            }
+        }
+    }
+
+    /// Checks whether the variable occurs on the left side before the items.
+    fn is_bound(&self, variable: usize, items: &[PatternItem<U>]) -> bool {
+        let left = &self.pattern.left;
+
+        left[..left.len() - items.len()].iter().any(|item| {
+            matches!(*item, PatternItem::Symbol(bound) | PatternItem::Segment(bound) if bound == variable)
+        })
+    }
+
+    /// Matches the units the variable is bound to at the position.
+    fn match_bound(&self, binding: Range<usize>, position: usize) -> Option<usize> {
+        let end = position + binding.len();
+
+        (end <= self.word.len()
+            && self
+                .word
+                .units_in(binding)
+                .eq(self.word.units_in(position..end)))
+        .then_some(end)
+    }
+}
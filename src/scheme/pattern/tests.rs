@@ -0,0 +1,225 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::*;
+
+use PatternItem::{Segment, Symbol};
+
+fn units(text: &str) -> impl Iterator<Item = PatternItem<u8>> + '_ {
+    text.bytes().map(PatternItem::Unit)
+}
+
+fn side<const N: usize>(parts: [Side; N]) -> Vec<PatternItem<u8>> {
+    parts
+        .into_iter()
+        .flat_map(|part| match part {
+            Side::Text(text) => units(text).collect(),
+            Side::Item(item) => vec![item],
+        })
+        .collect()
+}
+
+enum Side {
+    Text(&'static str),
+    Item(PatternItem<u8>),
+}
+
+fn apply(pattern: &Pattern<u8>, word: &str) -> Option<(Range<usize>, String)> {
+    pattern.find(&word.into()).map(|occurrence| {
+        (
+            occurrence.range,
+            String::from_utf8(occurrence.replacement).unwrap(),
+        )
+    })
+}
+
+fn apply_last(pattern: &Pattern<u8>, word: &str) -> Option<(Range<usize>, String)> {
+    pattern.find_last(&word.into()).map(|occurrence| {
+        (
+            occurrence.range,
+            String::from_utf8(occurrence.replacement).unwrap(),
+        )
+    })
+}
+
+#[test]
+fn the_literal_pattern_is_found_at_its_leftmost_occurrence() {
+    let pattern = Pattern::new(side([Side::Text("ab")]), side([Side::Text("c")]));
+
+    assert_eq!(Some((2..4, "c".to_owned())), apply(&pattern, "baabab"));
+    assert_eq!(None, apply(&pattern, "ba"));
+}
+
+#[test]
+fn the_segments_are_bound_to_the_shortest_parts_of_the_word() {
+    let pattern = Pattern::new(
+        side([
+            Side::Item(Segment(0)),
+            Side::Text("*"),
+            Side::Item(Segment(1)),
+        ]),
+        side([
+            Side::Item(Segment(1)),
+            Side::Text("*"),
+            Side::Item(Segment(0)),
+        ]),
+    );
+
+    assert_eq!(Some((0..3, "*ab".to_owned())), apply(&pattern, "ab*cd*"));
+}
+
+#[test]
+fn the_segments_are_extended_until_the_rest_matches() {
+    let pattern = Pattern::new(
+        side([Side::Text("<"), Side::Item(Segment(0)), Side::Text(">")]),
+        side([Side::Item(Segment(0))]),
+    );
+
+    assert_eq!(Some((1..7, "ab<c".to_owned())), apply(&pattern, "a<ab<c>>"));
+}
+
+#[test]
+fn the_symbols_are_bound_to_whole_characters() {
+    let pattern = Pattern::new(
+        side([Side::Text("_"), Side::Item(Symbol(0))]),
+        side([Side::Item(Symbol(0)), Side::Text("_")]),
+    );
+
+    assert_eq!(Some((1..4, "ж_".to_owned())), apply(&pattern, "a_жb"));
+    assert_eq!(None, apply(&pattern, "a_"));
+}
+
+#[test]
+fn the_repeated_variables_stand_for_the_same_characters() {
+    let pattern = Pattern::new(
+        side([
+            Side::Item(Symbol(0)),
+            Side::Item(Segment(1)),
+            Side::Item(Symbol(0)),
+        ]),
+        side([Side::Item(Segment(1))]),
+    );
+
+    assert_eq!(Some((0..5, "bcb".to_owned())), apply(&pattern, "abcba"));
+    assert_eq!(Some((1..3, "".to_owned())), apply(&pattern, "caab"));
+    assert_eq!(None, apply(&pattern, "abcd"));
+}
+
+#[test]
+fn the_empty_pattern_is_found_at_the_start() {
+    let pattern = Pattern::new(side([Side::Item(Segment(0))]), side([Side::Text("a")]));
+
+    assert_eq!(Some((0..0, "a".to_owned())), apply(&pattern, ""));
+    assert_eq!(Some((0..0, "a".to_owned())), apply(&pattern, "bc"));
+}
+
+#[test]
+fn the_anchored_pattern_only_touches_the_boundaries_of_the_word() {
+    let start = Anchors {
+        start: true,
+        end: false,
+    };
+    let end = Anchors {
+        start: false,
+        end: true,
+    };
+    let both = Anchors {
+        start: true,
+        end: true,
+    };
+
+    let literal = |anchors| Pattern::literal(b"ab", b"c").with_anchors(anchors);
+
+    assert_eq!(Some((0..2, "c".to_owned())), apply(&literal(start), "abab"));
+    assert_eq!(None, apply(&literal(start), "bab"));
+    assert_eq!(Some((2..4, "c".to_owned())), apply(&literal(end), "abab"));
+    assert_eq!(None, apply(&literal(end), "aba"));
+    assert_eq!(None, apply(&literal(end), "b"));
+    assert_eq!(Some((0..2, "c".to_owned())), apply(&literal(both), "ab"));
+    assert_eq!(None, apply(&literal(both), "abab"));
+
+    let empty = Pattern::literal(b"", b"#").with_anchors(end);
+
+    assert_eq!(Some((3..3, "#".to_owned())), apply(&empty, "abc"));
+}
+
+#[test]
+fn the_segment_of_the_pattern_anchored_to_the_end_takes_the_rest_of_the_word() {
+    let pattern = Pattern::new(
+        side([Side::Text("*"), Side::Item(Segment(0))]),
+        side([Side::Item(Segment(0))]),
+    )
+    .with_anchors(Anchors {
+        start: false,
+        end: true,
+    });
+
+    assert_eq!(Some((1..6, "b*cd".to_owned())), apply(&pattern, "a*b*cd"));
+}
+
+#[test]
+fn the_rightmost_occurrence_binds_the_segments_the_same_way() {
+    let pattern = Pattern::new(
+        side([Side::Text("*"), Side::Item(Segment(0)), Side::Text("*")]),
+        side([Side::Item(Segment(0))]),
+    );
+
+    assert_eq!(
+        Some((2..6, "cd".to_owned())),
+        apply_last(&pattern, "*a*cd*")
+    );
+    assert_eq!(Some((0..3, "a".to_owned())), apply(&pattern, "*a*cd*"));
+}
+
+#[test]
+fn every_occurrence_is_found_with_every_binding_of_the_segments() {
+    let pattern = Pattern::new(
+        side([
+            Side::Item(Segment(0)),
+            Side::Text("*"),
+            Side::Item(Segment(1)),
+        ]),
+        side([Side::Item(Segment(1)), Side::Item(Segment(0))]),
+    )
+    .with_anchors(Anchors {
+        start: true,
+        end: false,
+    });
+
+    let occurrences: Vec<_> = pattern
+        .find_all(&"a*b*".into())
+        .into_iter()
+        .map(|occurrence| {
+            (
+                occurrence.range,
+                String::from_utf8(occurrence.replacement).unwrap(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        vec![
+            (0..2, "a".to_owned()),
+            (0..3, "ba".to_owned()),
+            (0..4, "b*a".to_owned()),
+            (0..4, "a*b".to_owned()),
+        ],
+        occurrences
+    );
+}
@@ -0,0 +1,64 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! [SplitMix64](SplitMix64), a seeded source of randomness that stays the same across platforms and versions.
+
+#[cfg(test)]
+mod tests;
+
+/// The SplitMix64 generator, any value of its sequence can be drawn without drawing the ones before it.
+///
+/// The randomness of each step of an application is drawn at the position of the step,
+/// so a step can be redone after the application is rewound or restored without replaying the steps before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SplitMix64 {
+    seed: u64,
+}
+
+impl SplitMix64 {
+    const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    /// The seed the sequence is generated from.
+    pub(crate) fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The value of the sequence at the position, counting from zero.
+    pub(crate) fn value_at(&self, position: u64) -> u64 {
+        let mut value = self
+            .seed
+            .wrapping_add(position.wrapping_add(1).wrapping_mul(Self::GOLDEN_GAMMA));
+
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        value ^ (value >> 31)
+    }
+
+    /// A value below the bound, drawn at the position.
+    ///
+    /// The value is scaled instead of being reduced modulo the bound,
+    /// so the bias is negligible for the bounds far below `2^64`.
+    pub(crate) fn below_at(&self, position: u64, bound: u64) -> u64 {
+        ((u128::from(self.value_at(position)) * u128::from(bound)) >> 64) as u64
+    }
+}
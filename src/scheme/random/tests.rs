@@ -0,0 +1,47 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::*;
+
+#[test]
+fn the_sequence_matches_the_reference_implementation() {
+    let random = SplitMix64::new(1234567);
+
+    assert_eq!(
+        [
+            6457827717110365317,
+            3203168211198807973,
+            9817491932198370423
+        ],
+        [random.value_at(0), random.value_at(1), random.value_at(2)]
+    );
+}
+
+#[test]
+fn the_values_below_the_bound_are_spread_over_the_whole_range() {
+    let random = SplitMix64::new(42);
+
+    let mut counts = [0; 4];
+
+    for position in 0..4_000 {
+        counts[random.below_at(position, 4) as usize] += 1;
+    }
+
+    assert!(counts.iter().all(|&count| (900..1_100).contains(&count)));
+}
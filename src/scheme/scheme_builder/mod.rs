@@ -31,7 +31,15 @@ use crate::{
     scheme::{AlgorithmScheme, SchemeProperties, SubstitutionFormulaDefinitionError},
 };
 
-use super::SubstitutionFormula;
+use super::{
+    encoding::WordEncoding,
+    engine::{GroupTieBreak, MatchStrategy, RuleSides, Rules},
+    pattern::Pattern,
+    snapshot::Fingerprint,
+    template::FormulaTemplate,
+    variables::FormulaVariables,
+    SubstitutionFormula,
+};
 
 /// A builder to configure an algorithm scheme.
 ///
@@ -62,13 +70,20 @@ use super::SubstitutionFormula;
 #[derive(Clone)]
 pub struct AlgorithmSchemeBuilder {
     alphabet: Option<Alphabet>,
-    delimiter: Option<char>,
-    final_marker: Option<char>,
+    delimiter: Option<String>,
+    final_marker: Option<String>,
+    escape_character: Option<char>,
+    template_brackets: Option<(char, char)>,
+    variables: bool,
+    anchors: bool,
+    match_strategy: MatchStrategy,
+    stochastic_seed: Option<u64>,
+    priority_groups: Option<GroupTieBreak>,
 }
 
 impl AlgorithmSchemeBuilder {
-    const DEFAULT_DELIMITER: char = '→';
-    const DEFAULT_FINAL_MARKER: char = '⋅';
+    const DEFAULT_DELIMITER: &'static str = "→";
+    const DEFAULT_FINAL_MARKER: &'static str = "⋅";
 
     /// Creates a new builder.
     pub fn new() -> Self {
@@ -76,25 +91,298 @@ impl AlgorithmSchemeBuilder {
             alphabet: None,
             delimiter: None,
             final_marker: None,
+            escape_character: None,
+            template_brackets: None,
+            variables: false,
+            anchors: false,
+            match_strategy: MatchStrategy::Leftmost,
+            stochastic_seed: None,
+            priority_groups: None,
         }
     }
 
-    /// Adds a delimiter to the builder.
+    /// Adds a delimiter to the builder, either a character or a string.
     ///
     /// May be called multiple times in order to replace the prior delimiter.
-    pub fn with_delimiter(mut self, delimiter: char) -> Self {
-        _ = self.delimiter.insert(delimiter);
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::str;
+    /// use markovalgorithms::prelude::*;
+    ///
+    /// let scheme = AlgorithmSchemeBuilder::new()
+    ///     .with_alphabet(str::parse("ab").unwrap())
+    ///     .with_delimiter("->")
+    ///     .with_final_marker('.')
+    ///     .build_with_formula_definitions(["ab->.a", "a->ab"].into_iter())
+    ///     .unwrap();
+    ///
+    /// assert_eq!("a", scheme.apply("a", 10).unwrap().word());
+    /// ```
+    pub fn with_delimiter<D>(mut self, delimiter: D) -> Self
+    where
+        D: Into<String>,
+    {
+        _ = self.delimiter.insert(delimiter.into());
         self
     }
 
-    /// Adds a final marker to the builder.
+    /// Adds a final marker to the builder, either a character or a string.
+    /// A formula is final if the final marker immediately follows the delimiter.
     ///
     /// May be called multiple times in order to replace the prior final marker.
-    pub fn with_final_marker(mut self, final_marker: char) -> Self {
-        _ = self.final_marker.insert(final_marker);
+    pub fn with_final_marker<F>(mut self, final_marker: F) -> Self
+    where
+        F: Into<String>,
+    {
+        _ = self.final_marker.insert(final_marker.into());
+        self
+    }
+
+    /// Enables the escape sequences in the formula definitions, introduced by the escape character.
+    ///
+    /// The escape character followed by itself or by a character of the delimiter or the final marker
+    /// stands for that character, so the delimiter and the final marker may share characters with the alphabet.
+    /// The escape character followed by `n`, `r` or `t` stands for a new line, a carriage return or a tab,
+    /// and followed by `u{...}` stands for the character with the hexadecimal code point.
+    /// The escape character itself may belong to the alphabet too.
+    ///
+    /// May be called multiple times in order to replace the prior escape character.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::str;
+    /// use markovalgorithms::prelude::*;
+    ///
+    /// let alphabet = str::parse::<Alphabet>("a→").unwrap().extend('\n').unwrap();
+    ///
+    /// let scheme = AlgorithmSchemeBuilder::new()
+    ///     .with_alphabet(alphabet)
+    ///     .with_escape_character('\\')
+    ///     .build_with_formula_definitions([r"a\→→\n", r"\u{a}→⋅a"].into_iter())
+    ///     .unwrap();
+    ///
+    /// assert_eq!("a", scheme.apply("a→", 10).unwrap().word());
+    /// ```
+    pub fn with_escape_character(mut self, escape_character: char) -> Self {
+        _ = self.escape_character.insert(escape_character);
+        self
+    }
+
+    /// Enables the rule templates in the formula definitions, the bindings of the variables are enclosed in the brackets.
+    ///
+    /// A binding `[name:characters]` on the left side makes the formula stand for a formula per character of the class,
+    /// where the binding and every other occurrence of the name are replaced with that character.
+    /// The formulas generated from a template take its place in the scheme, in the order of the characters,
+    /// and the errors in them point at the template.
+    /// A template may bind several variables, the ones bound earlier change slower.
+    ///
+    /// The brackets cannot belong to the alphabet unless the escape character is set.
+    ///
+    /// May be called multiple times in order to replace the prior brackets.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::str;
+    /// use markovalgorithms::prelude::*;
+    ///
+    /// let scheme = AlgorithmSchemeBuilder::new()
+    ///     .with_alphabet(str::parse("_abAB").unwrap())
+    ///     .with_template_brackets('[', ']')
+    ///     .build_with_formula_definitions(["_[x:abAB]→x_", "_→⋅"].into_iter())
+    ///     .unwrap();
+    ///
+    /// assert_eq!("bAa", scheme.apply("_bAa", 10).unwrap().word());
+    ///
+    /// let result = scheme.get_application_iterator("_b").unwrap().next().unwrap();
+    ///
+    /// assert_eq!(Some("_b→b_"), result.applied_formula_definition());
+    /// ```
+    pub fn with_template_brackets(mut self, open: char, close: char) -> Self {
+        _ = self.template_brackets.insert((open, close));
+        self
+    }
+
+    /// Enables the Refal-style variables in the formula definitions.
+    ///
+    /// The variable `s.x` stands for a single character and the variable `e.x` stands for a segment of any length,
+    /// where `x` is any single character that is not reserved.
+    /// The variables are bound on the left side and may be reused on the right side,
+    /// a variable that occurs on the left side several times stands for the same characters each time.
+    ///
+    /// The formula is applied at the leftmost position where its left side matches,
+    /// the segments are bound from left to right, each to the shortest part of the word that lets the rest match.
+    /// The formulas without variables keep their usual meaning.
+    ///
+    /// The separator `.` cannot be a part of the delimiter or the final marker,
+    /// and cannot belong to the alphabet unless the escape character is set.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::str;
+    /// use markovalgorithms::prelude::*;
+    ///
+    /// let scheme = AlgorithmSchemeBuilder::new()
+    ///     .with_alphabet(str::parse("ab*#").unwrap())
+    ///     .with_variables()
+    ///     .build_with_formula_definitions(["e.x*e.y#→⋅e.y*e.x"].into_iter())
+    ///     .unwrap();
+    ///
+    /// assert_eq!("bb*aa", scheme.apply("aa*bb#", 10).unwrap().word());
+    /// ```
+    pub fn with_variables(mut self) -> Self {
+        self.variables = true;
+        self
+    }
+
+    /// Enables the anchors in the formula definitions.
+    ///
+    /// The left side that starts with `^` only matches at the start of the word,
+    /// and the left side that ends with `$` only matches at the end of the word.
+    /// The anchors cannot occur anywhere else unless they are escaped.
+    ///
+    /// The anchors cannot be a part of the delimiter, the final marker or the template brackets,
+    /// and cannot belong to the alphabet unless the escape character is set.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::str;
+    /// use markovalgorithms::prelude::*;
+    ///
+    /// let scheme = AlgorithmSchemeBuilder::new()
+    ///     .with_alphabet(str::parse("ab").unwrap())
+    ///     .with_anchors()
+    ///     .build_with_formula_definitions(["^a→b", "a$→⋅b"].into_iter())
+    ///     .unwrap();
+    ///
+    /// assert_eq!("bab", scheme.apply("aaa", 10).unwrap().word());
+    /// ```
+    pub fn with_anchors(mut self) -> Self {
+        self.anchors = true;
+        self
+    }
+
+    /// Sets the way the formula to apply and its occurrence are selected on each step,
+    /// the leftmost occurrence of the first applicable formula is rewritten by default.
+    ///
+    /// May be called multiple times in order to replace the prior strategy.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::str;
+    /// use markovalgorithms::prelude::*;
+    ///
+    /// let scheme = AlgorithmSchemeBuilder::new()
+    ///     .with_alphabet(str::parse("ab").unwrap())
+    ///     .with_match_strategy(MatchStrategy::Rightmost)
+    ///     .build_with_formula_definitions(["a→⋅b"].into_iter())
+    ///     .unwrap();
+    ///
+    /// assert_eq!("aab", scheme.apply("aaa", 10).unwrap().word());
+    /// ```
+    pub fn with_match_strategy(mut self, match_strategy: MatchStrategy) -> Self {
+        self.match_strategy = match_strategy;
+        self
+    }
+
+    /// Makes the scheme choose the formula to apply at random among the applicable ones,
+    /// with the probabilities proportional to the weights of the formulas.
+    ///
+    /// The formulas weigh the same unless they are built with [weights](AlgorithmSchemeBuilder::build_with_weighted_formula_definitions).
+    /// The occurrence of the chosen formula is still selected by the [strategy](AlgorithmSchemeBuilder::with_match_strategy).
+    /// The randomness of each step only depends on the seed and the number of the step,
+    /// so the applications with the same seed, including the resumed and the restored ones, make the same choices.
+    ///
+    /// May be called multiple times in order to replace the prior seed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::str;
+    /// use markovalgorithms::prelude::*;
+    ///
+    /// let build = || {
+    ///     AlgorithmSchemeBuilder::new()
+    ///         .with_alphabet(str::parse("abc").unwrap())
+    ///         .with_stochastic_selection(42)
+    ///         .build_with_formula_definitions(["a→b", "a→c"].into_iter())
+    ///         .unwrap()
+    /// };
+    ///
+    /// let word = build().apply("aaaaaaaa", 10).unwrap().word().to_owned();
+    ///
+    /// assert!(word.chars().all(|character| character == 'b' || character == 'c'));
+    /// assert_eq!(word, build().apply("aaaaaaaa", 10).unwrap().word());
+    /// ```
+    pub fn with_stochastic_selection(mut self, seed: u64) -> Self {
+        _ = self.stochastic_seed.insert(seed);
+        self
+    }
+
+    /// Allows the formulas to form groups of equal priority instead of a strict order.
+    ///
+    /// The formula definition that starts with `|` joins the group of the formula before it,
+    /// the marker cannot occur anywhere else unless it is escaped.
+    /// The first group that has an applicable formula wins, as the first applicable formula does without the groups,
+    /// but inside the group the occurrence selected by the [strategy](AlgorithmSchemeBuilder::with_match_strategy)
+    /// is the one that comes first by its position, and the ties are broken as declared.
+    /// The [randomly chosen](AlgorithmSchemeBuilder::with_stochastic_selection) formula is chosen among
+    /// the applicable formulas of the first applicable group.
+    ///
+    /// The marker cannot be a part of the delimiter, the final marker or the template brackets,
+    /// and cannot belong to the alphabet unless the escape character is set.
+    ///
+    /// May be called multiple times in order to replace the prior tie-break.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::str;
+    /// use markovalgorithms::prelude::*;
+    ///
+    /// let scheme = AlgorithmSchemeBuilder::new()
+    ///     .with_alphabet(str::parse("abc").unwrap())
+    ///     .with_priority_groups(GroupTieBreak::FirstFormula)
+    ///     .build_with_formula_definitions(["c→⋅", "b→⋅c", "|a→⋅c"].into_iter())
+    ///     .unwrap();
+    ///
+    /// assert_eq!("cbb", scheme.apply("abb", 1).unwrap().word());
+    /// ```
+    pub fn with_priority_groups(mut self, tie_break: GroupTieBreak) -> Self {
+        _ = self.priority_groups.insert(tie_break);
         self
     }
 
+    /// Writes a formula definition that the built scheme reads as the given sides,
+    /// escaping the characters that would be misread.
+    ///
+    /// # Returns
+    /// `None` if a side contains the delimiter or the final marker and there is no escape character.
+    ///
+    /// # Example
+    /// ```rust
+    /// use markovalgorithms::prelude::*;
+    ///
+    /// let builder = AlgorithmSchemeBuilder::new().with_escape_character('\\');
+    ///
+    /// assert_eq!(Some(r"a\→→⋅\n".to_owned()), builder.format_formula_definition("a→", "\n", true));
+    /// ```
+    pub fn format_formula_definition(
+        &self,
+        left: &str,
+        right: &str,
+        is_final: bool,
+    ) -> Option<String> {
+        let properties = self.clone().finalize_properties();
+
+        let mut definition = properties.escape(left)?;
+        definition.push_str(&properties.delimiter);
+        if is_final {
+            definition.push_str(&properties.final_marker);
+        }
+        definition.push_str(&properties.escape(right)?);
+
+        Some(definition)
+    }
+
     /// Adds an alphabet to the builder.
     ///
     /// May be called multiple times in order to replace the prior final marker.
@@ -105,6 +393,9 @@ impl AlgorithmSchemeBuilder {
 
     /// Builds an algorithm scheme based on the provided definitions.
     ///
+    /// If the alphabet and its extension contain at most 128 characters, each character is encoded as a single byte,
+    /// so the words are matched and rewritten as byte slices and converted to strings only when they are returned.
+    ///
     /// # Example
     /// Basic usage:
     /// ```rust
@@ -121,6 +412,39 @@ impl AlgorithmSchemeBuilder {
     ) -> Result<AlgorithmScheme, AlgorithmSchemeDefinitionError>
     where
         I: Iterator<Item = &'a str>,
+    {
+        self.build_with_weighted_formula_definitions(
+            formula_definitions.map(|formula_definition| (formula_definition, 1)),
+        )
+    }
+
+    /// Builds an algorithm scheme based on the provided definitions and the weights of the formulas.
+    ///
+    /// The weights only matter if the scheme [chooses the formulas at random](AlgorithmSchemeBuilder::with_stochastic_selection),
+    /// the formulas generated from a template share its weight.
+    ///
+    /// # Errors
+    /// Returns an [error](AlgorithmSchemeDefinitionError) if a definition is not valid or its weight is zero.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::str;
+    /// use markovalgorithms::prelude::*;
+    ///
+    /// let scheme = AlgorithmSchemeBuilder::new()
+    ///     .with_alphabet(str::parse("abc").unwrap())
+    ///     .with_stochastic_selection(1)
+    ///     .build_with_weighted_formula_definitions([("a→⋅b", 1), ("a→⋅c", 9)].into_iter())
+    ///     .unwrap();
+    ///
+    /// assert_eq!("c", scheme.apply("a", 1).unwrap().word());
+    /// ```
+    pub fn build_with_weighted_formula_definitions<'a, I>(
+        self,
+        formula_definitions: I,
+    ) -> Result<AlgorithmScheme, AlgorithmSchemeDefinitionError>
+    where
+        I: Iterator<Item = (&'a str, u32)>,
     {
         let properties = self.finalize_properties();
 
@@ -130,10 +454,34 @@ impl AlgorithmSchemeBuilder {
 
         let mut collection_builder = SubstitutionFormulaCollectionBuilder::new(&properties);
 
-        for formula_definition in formula_definitions {
-            assertions.assert_definition_conforms_to_properties(formula_definition)?;
+        for (formula_definition, weight) in formula_definitions {
+            if weight == 0 {
+                return Err(AlgorithmSchemeDefinitionError::ZeroWeight(
+                    formula_definition.to_owned(),
+                ));
+            }
+
+            if properties.template_brackets.is_none() {
+                collection_builder.try_add_checked_formula(
+                    &assertions,
+                    formula_definition,
+                    weight,
+                )?;
+                continue;
+            }
+
+            let template =
+                FormulaTemplate::parse(formula_definition, &properties).map_err(|source| {
+                    AlgorithmSchemeDefinitionError::FormulaCreationError { source }
+                })?;
 
-            collection_builder.try_add_formula(formula_definition)?;
+            assertions.assert_characters_belong_to_alphabet(template.characters())?;
+
+            for generated_definition in template.expand() {
+                collection_builder
+                    .try_add_checked_formula(&assertions, &generated_definition, weight)
+                    .map_err(|error| error.pointing_at(formula_definition))?;
+            }
         }
 
         let SubstitutionFormulaCollectionBuilder {
@@ -142,28 +490,176 @@ impl AlgorithmSchemeBuilder {
             substitution_formulas,
         } = collection_builder;
 
+        let encoding = WordEncoding::new(&properties.alphabet);
+
+        // The sides of the formulas with variables are compiled to patterns instead.
+        let encoded: Vec<_> = substitution_formulas
+            .iter()
+            .map(|formula| match &formula.variables {
+                Some(_) => (encoding.encode(""), encoding.encode("")),
+                None => (
+                    encoding.encode(formula.view().get_left(&store)),
+                    encoding.encode(formula.view().get_right(&store)),
+                ),
+            })
+            .collect();
+
+        let rules = Rules::with_sides(encoded.iter().zip(&substitution_formulas).map(
+            |((left, right), formula)| {
+                let sides = match &formula.variables {
+                    Some(variables) => RuleSides::Pattern(
+                        variables.compile(&encoding).with_anchors(formula.anchors),
+                    ),
+                    // The anchored formulas are matched apart from the others as well.
+                    None if formula.anchors.is_any() => RuleSides::Pattern(
+                        Pattern::literal(left, right).with_anchors(formula.anchors),
+                    ),
+                    None => RuleSides::Literal(left, right),
+                };
+
+                (sides, formula.is_final)
+            },
+        ));
+
+        let rules = rules.with_strategy(properties.match_strategy);
+
+        let rules = match properties.stochastic_seed {
+            Some(seed) => rules.with_stochastic_selection(
+                seed,
+                substitution_formulas
+                    .iter()
+                    .map(|formula| formula.weight.into())
+                    .collect(),
+            ),
+            None => rules,
+        };
+
+        let rules = match properties.priority_groups {
+            Some(tie_break) => rules.with_priority_groups(
+                substitution_formulas
+                    .iter()
+                    .scan(0, |group, formula| {
+                        if !formula.joins_group {
+                            *group += 1;
+                        }
+                        Some(*group)
+                    })
+                    .collect(),
+                tie_break,
+            ),
+            None => rules,
+        };
+
+        let rules = match encoding.unit_widths() {
+            Some(unit_widths) => rules.with_dense_units(unit_widths),
+            None => rules,
+        };
+
+        let fingerprint = Self::compute_fingerprint(&properties, &store, &substitution_formulas);
+
         Ok(AlgorithmScheme {
             properties,
             store,
             substitution_formulas,
+            encoding,
+            rules,
+            fingerprint,
         })
     }
 
+    /// Hashes everything that affects the application of the scheme.
+    fn compute_fingerprint(
+        properties: &SchemeProperties,
+        store: &str,
+        substitution_formulas: &[SubstitutionFormula],
+    ) -> u64 {
+        let mut fingerprint = Fingerprint::new();
+
+        fingerprint.write_str(&properties.delimiter);
+        fingerprint.write_str(&properties.final_marker);
+
+        // Every optional property is preceded by the byte that tells whether it is set,
+        // so the properties that are written with the same characters cannot be mistaken for each other.
+        fingerprint.write_presence(properties.escape_character.is_some());
+        if let Some(escape_character) = properties.escape_character {
+            fingerprint.write_char(escape_character);
+        }
+
+        fingerprint.write_presence(properties.template_brackets.is_some());
+        if let Some((open, close)) = properties.template_brackets {
+            fingerprint.write_char(open);
+            fingerprint.write_char(close);
+        }
+
+        fingerprint.write_presence(properties.variables);
+        if properties.variables {
+            fingerprint.write_char(FormulaVariables::SEPARATOR);
+        }
+
+        fingerprint.write_presence(properties.anchors);
+        if properties.anchors {
+            SubstitutionFormula::ANCHORS
+                .into_iter()
+                .for_each(|anchor| fingerprint.write_char(anchor));
+        }
+
+        fingerprint.write(&[properties.match_strategy as u8]);
+
+        fingerprint.write_presence(properties.priority_groups.is_some());
+        if let Some(tie_break) = properties.priority_groups {
+            fingerprint.write_char(SubstitutionFormula::GROUP_MARKER);
+            fingerprint.write(&[tie_break as u8]);
+        }
+
+        fingerprint.write_presence(properties.stochastic_seed.is_some());
+        if let Some(seed) = properties.stochastic_seed {
+            fingerprint.write(&seed.to_le_bytes());
+            substitution_formulas
+                .iter()
+                .for_each(|formula| fingerprint.write(&formula.weight.to_le_bytes()));
+        }
+
+        let (main, extension) = properties.alphabet.sorted_characters();
+
+        for characters in [main, extension] {
+            fingerprint.write(&(characters.len() as u64).to_le_bytes());
+            characters
+                .into_iter()
+                .for_each(|character| fingerprint.write_char(character));
+        }
+
+        fingerprint.write(&(substitution_formulas.len() as u64).to_le_bytes());
+
+        for formula in substitution_formulas {
+            fingerprint.write_str(formula.view().peek_definition(store));
+        }
+
+        fingerprint.finish()
+    }
+
     /// Creates a struct with properties to no longer use options.
-    fn finalize_properties(self) -> SchemeProperties {
+    pub(super) fn finalize_properties(self) -> SchemeProperties {
         SchemeProperties {
-            delimiter: self.delimiter.unwrap_or(Self::DEFAULT_DELIMITER),
-            final_marker: self.final_marker.unwrap_or(Self::DEFAULT_FINAL_MARKER),
+            delimiter: self
+                .delimiter
+                .unwrap_or_else(|| Self::DEFAULT_DELIMITER.to_owned()),
+            final_marker: self
+                .final_marker
+                .unwrap_or_else(|| Self::DEFAULT_FINAL_MARKER.to_owned()),
+            escape_character: self.escape_character,
+            template_brackets: self.template_brackets,
+            variables: self.variables,
+            anchors: self.anchors,
+            match_strategy: self.match_strategy,
+            stochastic_seed: self.stochastic_seed,
+            priority_groups: self.priority_groups,
             alphabet: self.alphabet.unwrap_or_else(Self::create_default_alphabet),
         }
     }
 
     fn create_default_alphabet() -> Alphabet {
-        let default_alphabet_set: HashSet<_> = ('a'..='z')
-            .into_iter()
-            .chain(('A'..='Z').into_iter())
-            .chain(('0'..='9').into_iter())
-            .collect();
+        let default_alphabet_set: HashSet<_> =
+            ('a'..='z').chain('A'..='Z').chain('0'..='9').collect();
 
         Alphabet::try_from(&default_alphabet_set).unwrap()
     }
@@ -193,8 +689,8 @@ impl<'a> PropertyAssertions<'a> {
         let invalid_characters = formula_definition
             .matches(|character| {
                 !self.properties.alphabet.contains_extended(character)
-                    && character != self.properties.delimiter
-                    && character != self.properties.final_marker
+                    && !self.properties.delimiter.contains(character)
+                    && !self.properties.final_marker.contains(character)
             })
             .fold(String::new(), |mut accumulator, character| {
                 accumulator.push_str(character);
@@ -208,31 +704,125 @@ impl<'a> PropertyAssertions<'a> {
         }
     }
 
+    /// Checks the unescaped sides of a formula, which may contain the escaped delimiter and final marker.
+    fn assert_sides_conform_to_alphabet(
+        &self,
+        left: &str,
+        right: &str,
+    ) -> Result<(), AlgorithmSchemeDefinitionError> {
+        self.assert_characters_belong_to_alphabet(left.chars().chain(right.chars()))
+    }
+
+    fn assert_characters_belong_to_alphabet<I>(
+        &self,
+        characters: I,
+    ) -> Result<(), AlgorithmSchemeDefinitionError>
+    where
+        I: Iterator<Item = char>,
+    {
+        let invalid_characters: String = characters
+            .filter(|&character| !self.properties.alphabet.contains_extended(character))
+            .collect();
+
+        if invalid_characters.is_empty() {
+            Ok(())
+        } else {
+            Err(AlgorithmSchemeDefinitionError::UnknownCharactersEncountered(invalid_characters))
+        }
+    }
+
     fn assert_all_properties_are_valid(&self) -> Result<(), AlgorithmSchemeDefinitionError> {
-        if self.properties.delimiter == self.properties.final_marker {
+        let SchemeProperties {
+            delimiter,
+            final_marker,
+            escape_character,
+            template_brackets,
+            variables,
+            anchors,
+            match_strategy: _,
+            stochastic_seed: _,
+            priority_groups,
+            alphabet,
+        } = self.properties;
+
+        let final_form = format!("{delimiter}{final_marker}");
+        let shares_characters_with_alphabet = |text: &str| {
+            text.chars()
+                .any(|character| alphabet.contains_extended(character))
+        };
+        // The separator of the variables, the anchors and the marker of the group should be distinguishable
+        // from the rest of the syntax.
+        let is_reserved_for_syntax = |character: char| {
+            final_form.contains(character)
+                || *escape_character == Some(character)
+                || template_brackets
+                    .is_some_and(|(open, close)| character == open || character == close)
+                || escape_character.is_none() && alphabet.contains_extended(character)
+        };
+        let has_reserved_template_bracket = |(open, close): (char, char)| {
+            open == close
+                || [open, close].into_iter().any(|bracket| {
+                    final_form.contains(bracket)
+                        || *escape_character == Some(bracket)
+                        || escape_character.is_none() && alphabet.contains_extended(bracket)
+                })
+        };
+
+        if delimiter.is_empty() {
+            Err(AlgorithmSchemeDefinitionError::EmptyDelimiter)
+        } else if final_marker.is_empty() {
+            Err(AlgorithmSchemeDefinitionError::EmptyFinalMarker)
+        } else if delimiter == final_marker {
             Err(
                 AlgorithmSchemeDefinitionError::DelimiterAndFinalMarkerAreTheSame(
-                    self.properties.delimiter,
+                    delimiter.clone(),
                 ),
             )
-        } else if self
-            .properties
-            .alphabet
-            .contains_extended(self.properties.delimiter)
+        } else if final_form
+            .char_indices()
+            .skip(1)
+            .any(|(start, _)| final_form[start..].starts_with(delimiter.as_str()))
         {
-            Err(
-                AlgorithmSchemeDefinitionError::DelimiterBelongsToTheAlphabet(
-                    self.properties.delimiter,
-                ),
-            )
-        } else if self
-            .properties
-            .alphabet
-            .contains_extended(self.properties.final_marker)
+            Err(AlgorithmSchemeDefinitionError::AmbiguousFinalMarker {
+                delimiter: delimiter.clone(),
+                final_marker: final_marker.clone(),
+            })
+        } else if let Some(escape_character) =
+            escape_character.filter(|&escape_character| final_form.contains(escape_character))
+        {
+            Err(AlgorithmSchemeDefinitionError::EscapeCharacterIsReserved(
+                escape_character,
+            ))
+        } else if let Some((open, close)) =
+            template_brackets.filter(|&brackets| has_reserved_template_bracket(brackets))
         {
+            Err(AlgorithmSchemeDefinitionError::TemplateBracketsAreReserved(
+                open, close,
+            ))
+        } else if *variables && is_reserved_for_syntax(FormulaVariables::SEPARATOR) {
+            Err(AlgorithmSchemeDefinitionError::VariableSeparatorIsReserved(
+                FormulaVariables::SEPARATOR,
+            ))
+        } else if let Some(anchor) = SubstitutionFormula::ANCHORS
+            .into_iter()
+            .find(|&anchor| *anchors && is_reserved_for_syntax(anchor))
+        {
+            Err(AlgorithmSchemeDefinitionError::AnchorIsReserved(anchor))
+        } else if priority_groups.is_some()
+            && is_reserved_for_syntax(SubstitutionFormula::GROUP_MARKER)
+        {
+            Err(AlgorithmSchemeDefinitionError::GroupMarkerIsReserved(
+                SubstitutionFormula::GROUP_MARKER,
+            ))
+        } else if escape_character.is_some() {
+            // The delimiter and the final marker can be escaped, so they may share characters with the alphabet.
+            Ok(())
+        } else if shares_characters_with_alphabet(delimiter) {
+            Err(AlgorithmSchemeDefinitionError::DelimiterBelongsToTheAlphabet(delimiter.clone()))
+        } else if shares_characters_with_alphabet(final_marker) {
             Err(
                 AlgorithmSchemeDefinitionError::FinalMarkerBelongsToTheAlphabet(
-                    self.properties.final_marker,
+                    final_marker.clone(),
                 ),
             )
         } else {
@@ -258,16 +848,54 @@ impl<'a> SubstitutionFormulaCollectionBuilder<'a> {
         }
     }
 
+    /// Adds the formula after checking that its definition and its sides only contain the known characters.
+    fn try_add_checked_formula(
+        &mut self,
+        assertions: &PropertyAssertions,
+        formula_definition: &str,
+        weight: u32,
+    ) -> Result<(), AlgorithmSchemeDefinitionError> {
+        // The variables, the anchors and the marker of the group are written with the characters
+        // that may not belong to the alphabet, so only the text of their sides is checked.
+        if self.properties.escape_character.is_none()
+            && !self.properties.variables
+            && !self.properties.anchors
+            && self.properties.priority_groups.is_none()
+        {
+            assertions.assert_definition_conforms_to_properties(formula_definition)?;
+        }
+
+        self.try_add_formula(formula_definition, weight)?;
+
+        match self.substitution_formulas.last() {
+            Some(SubstitutionFormula {
+                variables: Some(variables),
+                ..
+            }) => assertions
+                .assert_characters_belong_to_alphabet(variables.text().flat_map(str::chars))?,
+            Some(formula) => assertions.assert_sides_conform_to_alphabet(
+                formula.view().get_left(&self.store),
+                formula.view().get_right(&self.store),
+            )?,
+            None => {}
+        }
+
+        Ok(())
+    }
+
     fn try_add_formula(
         &mut self,
         formula_definition: &str,
+        weight: u32,
     ) -> Result<(), AlgorithmSchemeDefinitionError> {
         let start = self.store.len();
         self.store.push_str(formula_definition);
         let end = self.store.len();
 
-        match SubstitutionFormula::new(self.properties, &self.store, start..end) {
-            Ok(formula) => self.substitution_formulas.push(formula),
+        match SubstitutionFormula::new(self.properties, &mut self.store, start..end) {
+            Ok(formula) => self
+                .substitution_formulas
+                .push(SubstitutionFormula { weight, ..formula }),
             Err(error) => {
                 return Err(AlgorithmSchemeDefinitionError::FormulaCreationError { source: error })
             }
@@ -280,19 +908,57 @@ impl<'a> SubstitutionFormulaCollectionBuilder<'a> {
 /// An error in the algorithm definition.
 #[derive(Debug, Error, PartialEq, Eq)]
 pub enum AlgorithmSchemeDefinitionError {
-    /// The same character cannot be used as a delimiter and as a final marker.
-    #[error("the same character '{0}' cannot be used as a delimiter and as a final marker")]
-    DelimiterAndFinalMarkerAreTheSame(char),
-    /// The delimiter cannot belong to the alphabet.
+    /// The delimiter cannot be empty.
+    #[error("the delimiter cannot be empty")]
+    EmptyDelimiter,
+    /// The final marker cannot be empty.
+    #[error("the final marker cannot be empty")]
+    EmptyFinalMarker,
+    /// The same string cannot be used as a delimiter and as a final marker.
+    #[error("the same string \"{0}\" cannot be used as a delimiter and as a final marker")]
+    DelimiterAndFinalMarkerAreTheSame(String),
+    /// The delimiter followed by the final marker cannot contain another delimiter.
+    #[error("the delimiter \"{delimiter}\" followed by the final marker \"{final_marker}\" contains another delimiter")]
+    AmbiguousFinalMarker {
+        delimiter: String,
+        final_marker: String,
+    },
+    /// The delimiter cannot share characters with the alphabet.
     #[error(
-        "the character '{0}' cannot be used as a delimiter because it belongs to the alphabet"
+        "the string \"{0}\" cannot be used as a delimiter because it shares characters with the alphabet"
     )]
-    DelimiterBelongsToTheAlphabet(char),
-    /// The final marker cannot belong to the alphabet.
+    DelimiterBelongsToTheAlphabet(String),
+    /// The final marker cannot share characters with the alphabet.
     #[error(
-        "the character '{0}' cannot be used as a final marker because it belongs to the alphabet"
+        "the string \"{0}\" cannot be used as a final marker because it shares characters with the alphabet"
     )]
-    FinalMarkerBelongsToTheAlphabet(char),
+    FinalMarkerBelongsToTheAlphabet(String),
+    /// The escape character cannot be a part of the delimiter or the final marker.
+    #[error("the character '{0}' cannot be used as an escape character because it is a part of the delimiter or the final marker")]
+    EscapeCharacterIsReserved(char),
+    /// The template brackets should differ, cannot be a part of the delimiter or the final marker,
+    /// or be the escape character, and cannot belong to the alphabet unless they can be escaped.
+    #[error("the characters '{0}' and '{1}' cannot be used as template brackets because they are the same, \
+    are reserved, or belong to the alphabet while there is no escape character")]
+    TemplateBracketsAreReserved(char, char),
+    /// The separator of the variables cannot be a part of the delimiter, the final marker, or the template brackets,
+    /// or be the escape character, and cannot belong to the alphabet unless it can be escaped.
+    #[error("the character '{0}' separates the kinds of the variables from their names, so it cannot be reserved \
+    or belong to the alphabet while there is no escape character")]
+    VariableSeparatorIsReserved(char),
+    /// The anchors cannot be a part of the delimiter, the final marker, or the template brackets,
+    /// or be the escape character, and cannot belong to the alphabet unless they can be escaped.
+    #[error("the character '{0}' anchors the formulas to the boundaries of the word, so it cannot be reserved \
+    or belong to the alphabet while there is no escape character")]
+    AnchorIsReserved(char),
+    /// The marker of the group cannot be a part of the delimiter, the final marker, or the template brackets,
+    /// or be the escape character, and cannot belong to the alphabet unless it can be escaped.
+    #[error("the character '{0}' joins the formulas into the groups of equal priority, so it cannot be reserved \
+    or belong to the alphabet while there is no escape character")]
+    GroupMarkerIsReserved(char),
+    /// The weight of a formula cannot be zero.
+    #[error("the weight of the substitution formula \"{0}\" cannot be zero")]
+    ZeroWeight(String),
     /// An error encountered during the creation of substitution formulas.
     #[error("encountered an issue during the creation of substitution formulas: {source}")]
     FormulaCreationError {
@@ -304,3 +970,15 @@ pub enum AlgorithmSchemeDefinitionError {
     nor are delimiter or final marker (unknown characters: \"{0}\")")]
     UnknownCharactersEncountered(String),
 }
+
+impl AlgorithmSchemeDefinitionError {
+    /// Makes the error in a formula generated from the template point at the template.
+    fn pointing_at(self, template: &str) -> Self {
+        match self {
+            Self::FormulaCreationError { source } => Self::FormulaCreationError {
+                source: source.with_definition(template),
+            },
+            error => error,
+        }
+    }
+}
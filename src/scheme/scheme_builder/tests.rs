@@ -17,6 +17,8 @@
 *    along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use std::iter;
+
 use super::*;
 
 #[test]
@@ -73,7 +75,8 @@ fn the_scheme_cannot_be_built_if_delimiter_and_final_marker_are_the_same() {
         .build_with_formula_definitions(["a→b"].into_iter())
         .unwrap_err();
 
-    let expected_error = AlgorithmSchemeDefinitionError::DelimiterAndFinalMarkerAreTheSame('→');
+    let expected_error =
+        AlgorithmSchemeDefinitionError::DelimiterAndFinalMarkerAreTheSame("→".to_owned());
 
     assert_eq!(expected_error, error);
 }
@@ -90,7 +93,7 @@ fn an_error_is_reported_if_delimiter_and_final_marker_are_the_same() {
         .unwrap_err();
 
     assert_eq!(
-        "the same character '→' cannot be used as a delimiter and as a final marker",
+        "the same string \"→\" cannot be used as a delimiter and as a final marker",
         format!("{error}")
     );
 }
@@ -106,7 +109,8 @@ fn the_scheme_cannot_be_built_if_the_delimiter_belongs_to_the_alphabet() {
         .build_with_formula_definitions(["a→b"].into_iter())
         .unwrap_err();
 
-    let expected_error = AlgorithmSchemeDefinitionError::DelimiterBelongsToTheAlphabet('→');
+    let expected_error =
+        AlgorithmSchemeDefinitionError::DelimiterBelongsToTheAlphabet("→".to_owned());
 
     assert_eq!(expected_error, error);
 }
@@ -124,7 +128,8 @@ fn the_scheme_cannot_be_built_if_the_delimiter_belongs_to_the_alphabet_extension
         .build_with_formula_definitions(["a→b"].into_iter())
         .unwrap_err();
 
-    let expected_error = AlgorithmSchemeDefinitionError::DelimiterBelongsToTheAlphabet('→');
+    let expected_error =
+        AlgorithmSchemeDefinitionError::DelimiterBelongsToTheAlphabet("→".to_owned());
 
     assert_eq!(expected_error, error);
 }
@@ -141,7 +146,7 @@ fn an_error_is_reported_if_the_delimiter_belongs_to_the_alphabet() {
         .unwrap_err();
 
     assert_eq!(
-        "the character '→' cannot be used as a delimiter because it belongs to the alphabet",
+        "the string \"→\" cannot be used as a delimiter because it shares characters with the alphabet",
         format!("{error}")
     );
 }
@@ -157,7 +162,8 @@ fn the_scheme_cannot_be_built_if_the_final_marker_belongs_to_the_alphabet() {
         .build_with_formula_definitions(["a→⋅b"].into_iter())
         .unwrap_err();
 
-    let expected_error = AlgorithmSchemeDefinitionError::FinalMarkerBelongsToTheAlphabet('⋅');
+    let expected_error =
+        AlgorithmSchemeDefinitionError::FinalMarkerBelongsToTheAlphabet("⋅".to_owned());
 
     assert_eq!(expected_error, error);
 }
@@ -175,7 +181,8 @@ fn the_scheme_cannot_be_built_if_the_final_marker_belongs_to_the_alphabet_extens
         .build_with_formula_definitions(["a→⋅b"].into_iter())
         .unwrap_err();
 
-    let expected_error = AlgorithmSchemeDefinitionError::FinalMarkerBelongsToTheAlphabet('⋅');
+    let expected_error =
+        AlgorithmSchemeDefinitionError::FinalMarkerBelongsToTheAlphabet("⋅".to_owned());
 
     assert_eq!(expected_error, error);
 }
@@ -192,7 +199,7 @@ fn an_error_is_reported_if_the_final_marker_belongs_to_the_alphabet() {
         .unwrap_err();
 
     assert_eq!(
-        "the character '⋅' cannot be used as a final marker because it belongs to the alphabet",
+        "the string \"⋅\" cannot be used as a final marker because it shares characters with the alphabet",
         format!("{error}")
     );
 }
@@ -271,3 +278,754 @@ fn the_scheme_builder_can_be_cloned() {
     #[allow(clippy::redundant_clone)]
     let _clone = builder.clone();
 }
+
+fn create_escaping_builder(alphabet: &str) -> AlgorithmSchemeBuilder {
+    AlgorithmSchemeBuilder::new()
+        .with_alphabet(alphabet.try_into().unwrap())
+        .with_delimiter('→')
+        .with_final_marker('⋅')
+        .with_escape_character('\\')
+}
+
+#[test]
+fn the_escaped_delimiter_and_final_marker_belong_to_the_sides_of_the_formula() {
+    let scheme = create_escaping_builder("a→⋅")
+        .build_with_formula_definitions([r"a\→→\⋅", r"\⋅→⋅\→"].into_iter())
+        .unwrap();
+
+    let result = scheme.apply("aa→", 10).unwrap();
+
+    assert_eq!("a→", result.word());
+    assert_eq!(2, result.steps_done());
+}
+
+#[test]
+fn the_escape_sequences_stand_for_the_whitespace_and_code_points() {
+    let scheme = create_escaping_builder("a \n\t\\")
+        .build_with_formula_definitions([r"\n\t→\\", r"\u{20}→\u{61}", r"\\\\→⋅"].into_iter())
+        .unwrap();
+
+    let result = scheme.apply("\n\t\n\t ", 10).unwrap();
+
+    assert_eq!("a", result.word());
+    assert_eq!(
+        Some(r"\\\\→⋅"),
+        scheme
+            .apply_once("\\\\")
+            .unwrap()
+            .into_data()
+            .applied_formula_definition()
+    );
+}
+
+#[test]
+fn the_escaped_definitions_are_reported_as_written() {
+    let scheme = create_escaping_builder("ab→")
+        .build_with_formula_definitions([r"a\u{2192}→b"].into_iter())
+        .unwrap();
+
+    let data = scheme.apply_once("a→b").unwrap().into_data();
+
+    assert_eq!(Some(r"a\u{2192}→b"), data.applied_formula_definition());
+    assert_eq!(
+        Some(0..4),
+        data.substitution()
+            .map(|substitution| substitution.matched_bytes())
+    );
+}
+
+#[test]
+fn the_scheme_cannot_be_built_if_an_escape_sequence_is_invalid() {
+    for (definition, sequence) in [
+        (r"a\x→b", r"\x"),
+        (r"a→b\", r"\"),
+        (r"a→\u{110000}", r"\u{110000}"),
+        (r"a→\u{61", r"\u{61"),
+        (r"a\u→b", r"\u"),
+    ] {
+        let error = create_escaping_builder("ab")
+            .build_with_formula_definitions([definition].into_iter())
+            .unwrap_err();
+
+        assert_eq!(
+            AlgorithmSchemeDefinitionError::FormulaCreationError {
+                source: SubstitutionFormulaDefinitionError::InvalidEscapeSequence(
+                    definition.to_owned(),
+                    sequence.to_owned()
+                )
+            },
+            error
+        );
+    }
+}
+
+#[test]
+fn the_unescaped_characters_of_the_sides_should_belong_to_the_alphabet() {
+    let error = create_escaping_builder("ab")
+        .build_with_formula_definitions([r"a\→→\u{431}"].into_iter())
+        .unwrap_err();
+
+    assert_eq!(
+        AlgorithmSchemeDefinitionError::UnknownCharactersEncountered("→б".to_owned()),
+        error
+    );
+}
+
+#[test]
+fn the_escape_character_cannot_be_the_delimiter_or_the_final_marker() {
+    for escape_character in ['→', '⋅'] {
+        let error = create_escaping_builder("ab")
+            .with_escape_character(escape_character)
+            .build_with_formula_definitions(["a→b"].into_iter())
+            .unwrap_err();
+
+        assert_eq!(
+            AlgorithmSchemeDefinitionError::EscapeCharacterIsReserved(escape_character),
+            error
+        );
+    }
+}
+
+#[test]
+fn the_formatted_definitions_are_read_back_as_the_same_formulas() {
+    let builder = create_escaping_builder("a→⋅\\\n\u{7}");
+
+    let sides = [("a→⋅", "\\\n"), ("\u{7}", ""), ("", "a")];
+
+    let definitions: Vec<_> = sides
+        .iter()
+        .map(|&(left, right)| {
+            builder
+                .format_formula_definition(left, right, false)
+                .unwrap()
+        })
+        .collect();
+
+    assert_eq!([r"a\→\⋅→\\\n", r"\u{7}→", "→a"], definitions.as_slice());
+
+    let scheme = builder
+        .build_with_formula_definitions(definitions.iter().map(String::as_str))
+        .unwrap();
+
+    for (formula, (left, right)) in scheme.substitution_formulas.iter().zip(sides) {
+        assert_eq!(left, formula.view().get_left(&scheme.store));
+        assert_eq!(right, formula.view().get_right(&scheme.store));
+    }
+}
+
+#[test]
+fn the_definitions_with_special_characters_cannot_be_formatted_without_escaping() {
+    let builder = AlgorithmSchemeBuilder::new();
+
+    assert_eq!(
+        Some("a→⋅b".to_owned()),
+        builder.format_formula_definition("a", "b", true)
+    );
+    assert_eq!(None, builder.format_formula_definition("a→", "b", false));
+}
+
+#[test]
+fn the_scheme_can_be_built_with_a_string_delimiter_and_final_marker() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet("ab".try_into().unwrap())
+        .with_delimiter("->")
+        .with_final_marker(".")
+        .build_with_formula_definitions(["aa->.b", "b->a"].into_iter())
+        .unwrap();
+
+    let result = scheme.apply("ab", 10).unwrap();
+
+    assert_eq!("b", result.word());
+    assert_eq!(Some("aa->.b"), {
+        let data = scheme.apply_once("aa").unwrap().into_data();
+        data.applied_formula_definition()
+    });
+}
+
+#[test]
+fn the_final_marker_may_be_longer_than_the_delimiter() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet("ab".try_into().unwrap())
+        .with_delimiter("=")
+        .with_final_marker(">>")
+        .build_with_formula_definitions(["a=>>b", "b=a"].into_iter())
+        .unwrap();
+
+    let formulas = &scheme.substitution_formulas;
+
+    assert!(formulas[0].is_final && !formulas[1].is_final);
+    assert_eq!("b", formulas[0].view().get_right(&scheme.store));
+}
+
+#[test]
+fn the_formula_with_several_string_delimiters_is_not_well_formed() {
+    let error = AlgorithmSchemeBuilder::new()
+        .with_alphabet("ab".try_into().unwrap())
+        .with_delimiter("->")
+        .build_with_formula_definitions(["a->b->a"].into_iter())
+        .unwrap_err();
+
+    assert_eq!(
+        AlgorithmSchemeDefinitionError::FormulaCreationError {
+            source: SubstitutionFormulaDefinitionError::MultipleDelimitersFound(
+                "a->b->a".to_owned(),
+                2
+            )
+        },
+        error
+    );
+}
+
+#[test]
+fn the_string_final_marker_cannot_appear_elsewhere() {
+    let builder = AlgorithmSchemeBuilder::new()
+        .with_alphabet("ab".try_into().unwrap())
+        .with_delimiter("->")
+        .with_final_marker("!!");
+
+    let error = builder
+        .build_with_formula_definitions(["a->b!!"].into_iter())
+        .unwrap_err();
+
+    assert_eq!(
+        AlgorithmSchemeDefinitionError::FormulaCreationError {
+            source: SubstitutionFormulaDefinitionError::FinalMarkerOnTheRight("a->b!!".to_owned())
+        },
+        error
+    );
+}
+
+#[test]
+fn the_delimiter_and_the_final_marker_cannot_be_empty() {
+    let builder = AlgorithmSchemeBuilder::new().with_alphabet("ab".try_into().unwrap());
+
+    assert_eq!(
+        Some(AlgorithmSchemeDefinitionError::EmptyDelimiter),
+        builder
+            .clone()
+            .with_delimiter("")
+            .build_with_formula_definitions(iter::empty())
+            .err()
+    );
+    assert_eq!(
+        Some(AlgorithmSchemeDefinitionError::EmptyFinalMarker),
+        builder
+            .with_final_marker("")
+            .build_with_formula_definitions(iter::empty())
+            .err()
+    );
+}
+
+#[test]
+fn the_final_form_cannot_contain_another_delimiter() {
+    for (delimiter, final_marker) in [("-", "->"), ("->", "x->"), ("--", "-")] {
+        let error = AlgorithmSchemeBuilder::new()
+            .with_alphabet("ab".try_into().unwrap())
+            .with_delimiter(delimiter)
+            .with_final_marker(final_marker)
+            .build_with_formula_definitions(iter::empty())
+            .unwrap_err();
+
+        assert_eq!(
+            AlgorithmSchemeDefinitionError::AmbiguousFinalMarker {
+                delimiter: delimiter.to_owned(),
+                final_marker: final_marker.to_owned(),
+            },
+            error
+        );
+    }
+}
+
+#[test]
+fn the_string_delimiter_cannot_share_characters_with_the_alphabet() {
+    let error = AlgorithmSchemeBuilder::new()
+        .with_alphabet("ab".try_into().unwrap())
+        .with_delimiter("-b-")
+        .build_with_formula_definitions(iter::empty())
+        .unwrap_err();
+
+    assert_eq!(
+        AlgorithmSchemeDefinitionError::DelimiterBelongsToTheAlphabet("-b-".to_owned()),
+        error
+    );
+}
+
+#[test]
+fn the_string_delimiter_can_be_escaped_character_by_character() {
+    let builder = AlgorithmSchemeBuilder::new()
+        .with_alphabet("a->".try_into().unwrap())
+        .with_delimiter("->")
+        .with_final_marker(">")
+        .with_escape_character('\\');
+
+    let definition = builder
+        .format_formula_definition("a->", ">-", false)
+        .unwrap();
+
+    assert_eq!(r"a\-\>->\>\-", definition);
+
+    let scheme = builder
+        .build_with_formula_definitions([definition.as_str(), r"a\-->>\-"].into_iter())
+        .unwrap();
+
+    assert_eq!(">-", scheme.apply("a->", 10).unwrap().word());
+    assert!(scheme.substitution_formulas[1].is_final);
+    assert_eq!("-", scheme.apply("a-", 10).unwrap().word());
+}
+
+fn create_templating_builder() -> AlgorithmSchemeBuilder {
+    AlgorithmSchemeBuilder::new()
+        .with_alphabet("_abAB".try_into().unwrap())
+        .with_template_brackets('[', ']')
+}
+
+#[test]
+fn the_templates_are_expanded_in_place() {
+    let scheme = create_templating_builder()
+        .build_with_formula_definitions(["_→⋅", "[x:ab]_→_x", "[y:AB]→⋅y"].into_iter())
+        .unwrap();
+
+    let definitions: Vec<_> = scheme
+        .substitution_formulas
+        .iter()
+        .map(|formula| formula.view().peek_definition(&scheme.store))
+        .collect();
+
+    assert_eq!(vec!["_→⋅", "a_→_a", "b_→_b", "A→⋅A", "B→⋅B"], definitions);
+}
+
+#[test]
+fn the_scheme_with_templates_is_applied_as_the_expanded_one() {
+    let expanded = AlgorithmSchemeBuilder::new()
+        .with_alphabet("_abAB".try_into().unwrap())
+        .build_with_formula_definitions(["_a→a_", "_b→b_", "_A→A_", "_B→B_", "_→⋅"].into_iter())
+        .unwrap();
+    let templated = create_templating_builder()
+        .build_with_formula_definitions(["_[x:abAB]→x_", "_→⋅"].into_iter())
+        .unwrap();
+
+    for word in ["_", "_abAB", "_BbaA"] {
+        assert_eq!(expanded.apply(word, 10), templated.apply(word, 10));
+    }
+}
+
+#[test]
+fn the_errors_in_the_generated_formulas_point_at_the_template() {
+    let error = create_templating_builder()
+        .build_with_formula_definitions(["[x:ab]→x→"].into_iter())
+        .unwrap_err();
+
+    assert_eq!(
+        AlgorithmSchemeDefinitionError::FormulaCreationError {
+            source: SubstitutionFormulaDefinitionError::MultipleDelimitersFound(
+                "[x:ab]→x→".to_owned(),
+                2
+            )
+        },
+        error
+    );
+}
+
+#[test]
+fn the_invalid_template_is_not_expanded() {
+    let error = create_templating_builder()
+        .build_with_formula_definitions(["_[x:ab→x_"].into_iter())
+        .unwrap_err();
+
+    assert_eq!(
+        AlgorithmSchemeDefinitionError::FormulaCreationError {
+            source: SubstitutionFormulaDefinitionError::InvalidTemplateBinding(
+                "_[x:ab→x_".to_owned(),
+                "[x:ab→x_".to_owned()
+            )
+        },
+        error
+    );
+}
+
+#[test]
+fn the_classes_of_the_templates_should_belong_to_the_alphabet() {
+    let error = create_templating_builder()
+        .build_with_formula_definitions(["_[x:abc→]→x_"].into_iter())
+        .unwrap_err();
+
+    assert_eq!(
+        AlgorithmSchemeDefinitionError::UnknownCharactersEncountered("c→".to_owned()),
+        error
+    );
+}
+
+#[test]
+fn the_template_brackets_can_belong_to_the_alphabet_if_they_can_be_escaped() {
+    let builder = AlgorithmSchemeBuilder::new()
+        .with_alphabet("a[]".try_into().unwrap())
+        .with_template_brackets('[', ']');
+
+    assert_eq!(
+        Some(AlgorithmSchemeDefinitionError::TemplateBracketsAreReserved(
+            '[', ']'
+        )),
+        builder
+            .clone()
+            .build_with_formula_definitions(iter::empty())
+            .err()
+    );
+
+    let builder = builder.with_escape_character('\\');
+
+    assert_eq!(
+        Some(r"\[→\]".to_owned()),
+        builder.format_formula_definition("[", "]", false)
+    );
+
+    let scheme = builder
+        .build_with_formula_definitions([r"[x:\[\]]a→x"].into_iter())
+        .unwrap();
+
+    assert_eq!("]", scheme.apply("]a", 10).unwrap().word());
+}
+
+#[test]
+fn the_template_brackets_should_not_be_reserved() {
+    for (open, close, escape_character) in [
+        ('[', '[', None),
+        ('→', ']', None),
+        ('[', '⋅', None),
+        ('[', ']', Some(']')),
+    ] {
+        let builder = AlgorithmSchemeBuilder::new().with_template_brackets(open, close);
+        let builder = match escape_character {
+            Some(escape_character) => builder.with_escape_character(escape_character),
+            None => builder,
+        };
+
+        assert_eq!(
+            Some(AlgorithmSchemeDefinitionError::TemplateBracketsAreReserved(
+                open, close
+            )),
+            builder.build_with_formula_definitions(iter::empty()).err()
+        );
+    }
+}
+
+#[test]
+fn the_variables_are_not_read_unless_enabled() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet("ex.a".try_into().unwrap())
+        .build_with_formula_definitions(["e.x→a"].into_iter())
+        .unwrap();
+
+    assert!(scheme.substitution_formulas[0].variables.is_none());
+    assert_eq!("aa", scheme.apply("e.xa", 10).unwrap().word());
+}
+
+#[test]
+fn the_variable_separator_should_not_be_reserved() {
+    let cases = [
+        AlgorithmSchemeBuilder::new().with_final_marker('.'),
+        AlgorithmSchemeBuilder::new().with_delimiter("->."),
+        AlgorithmSchemeBuilder::new().with_escape_character('.'),
+        AlgorithmSchemeBuilder::new().with_template_brackets('.', ']'),
+        AlgorithmSchemeBuilder::new().with_alphabet("a.".try_into().unwrap()),
+    ];
+
+    for builder in cases {
+        assert_eq!(
+            Some(AlgorithmSchemeDefinitionError::VariableSeparatorIsReserved(
+                '.'
+            )),
+            builder
+                .with_variables()
+                .build_with_formula_definitions(iter::empty())
+                .err()
+        );
+    }
+}
+
+#[test]
+fn the_variable_separator_can_belong_to_the_alphabet_if_it_can_be_escaped() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet("a.".try_into().unwrap())
+        .with_variables()
+        .with_escape_character('\\')
+        .build_with_formula_definitions([r"e.x\.→⋅\.e.x"].into_iter())
+        .unwrap();
+
+    assert_eq!(".aa", scheme.apply("aa.", 10).unwrap().word());
+}
+
+#[test]
+fn the_text_of_the_formulas_with_variables_should_belong_to_the_alphabet() {
+    let error = AlgorithmSchemeBuilder::new()
+        .with_alphabet("ab".try_into().unwrap())
+        .with_variables()
+        .build_with_formula_definitions(["s.xc→s.xd"].into_iter())
+        .unwrap_err();
+
+    assert_eq!(
+        AlgorithmSchemeDefinitionError::UnknownCharactersEncountered("cd".to_owned()),
+        error
+    );
+}
+
+#[test]
+fn the_invalid_variables_are_reported_with_the_formula() {
+    let error = AlgorithmSchemeBuilder::new()
+        .with_alphabet("ab".try_into().unwrap())
+        .with_variables()
+        .build_with_formula_definitions(["s.x→a", "e.x→e.y"].into_iter())
+        .unwrap_err();
+
+    assert_eq!(
+        AlgorithmSchemeDefinitionError::FormulaCreationError {
+            source: SubstitutionFormulaDefinitionError::UnboundVariable(
+                "e.x→e.y".to_owned(),
+                "e.y".to_owned()
+            )
+        },
+        error
+    );
+}
+
+#[test]
+fn the_templates_can_contain_variables() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet("ab".try_into().unwrap())
+        .with_template_brackets('[', ']')
+        .with_variables()
+        .build_with_formula_definitions(["[c:ab]s.xc→s.x"].into_iter())
+        .unwrap();
+
+    assert_eq!("bb", scheme.apply("abab", 10).unwrap().word());
+}
+
+#[test]
+fn the_anchors_are_not_read_unless_enabled() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet("a^$".try_into().unwrap())
+        .build_with_formula_definitions(["^a$→a"].into_iter())
+        .unwrap();
+
+    assert!(!scheme.substitution_formulas[0].anchors.is_any());
+    assert_eq!("aa", scheme.apply("a^a$", 10).unwrap().word());
+}
+
+#[test]
+fn the_anchors_are_read_at_the_boundaries_of_the_left_side() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet("ab".try_into().unwrap())
+        .with_anchors()
+        .build_with_formula_definitions(["^a→b", "a$→b", "^b$→⋅a", "^→⋅"].into_iter())
+        .unwrap();
+
+    let anchors: Vec<_> = scheme
+        .substitution_formulas
+        .iter()
+        .map(|formula| (formula.anchors.start, formula.anchors.end))
+        .collect();
+
+    assert_eq!(
+        vec![(true, false), (false, true), (true, true), (true, false)],
+        anchors
+    );
+    assert_eq!(
+        "a",
+        scheme.substitution_formulas[1]
+            .view()
+            .get_left(&scheme.store)
+    );
+    assert_eq!("bbb", scheme.apply("aba", 10).unwrap().word());
+    assert_eq!("a", scheme.apply("b", 10).unwrap().word());
+}
+
+#[test]
+fn the_misplaced_anchors_are_reported_with_the_formula() {
+    for (definition, anchor) in [("a^→b", '^'), ("$a→b", '$'), ("a→^b", '^'), ("a→b$", '$')]
+    {
+        assert_eq!(
+            Some(AlgorithmSchemeDefinitionError::FormulaCreationError {
+                source: SubstitutionFormulaDefinitionError::MisplacedAnchor(
+                    definition.to_owned(),
+                    anchor
+                )
+            }),
+            AlgorithmSchemeBuilder::new()
+                .with_alphabet("ab".try_into().unwrap())
+                .with_anchors()
+                .build_with_formula_definitions([definition].into_iter())
+                .err()
+        );
+    }
+}
+
+#[test]
+fn the_anchors_should_not_be_reserved() {
+    let cases = [
+        (AlgorithmSchemeBuilder::new().with_final_marker('^'), '^'),
+        (AlgorithmSchemeBuilder::new().with_delimiter("$>"), '$'),
+        (
+            AlgorithmSchemeBuilder::new().with_escape_character('$'),
+            '$',
+        ),
+        (
+            AlgorithmSchemeBuilder::new().with_template_brackets('[', '^'),
+            '^',
+        ),
+        (
+            AlgorithmSchemeBuilder::new().with_alphabet("a$".try_into().unwrap()),
+            '$',
+        ),
+    ];
+
+    for (builder, anchor) in cases {
+        assert_eq!(
+            Some(AlgorithmSchemeDefinitionError::AnchorIsReserved(anchor)),
+            builder
+                .with_anchors()
+                .build_with_formula_definitions(iter::empty())
+                .err()
+        );
+    }
+}
+
+#[test]
+fn the_anchors_can_belong_to_the_alphabet_if_they_can_be_escaped() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet("a^$".try_into().unwrap())
+        .with_anchors()
+        .with_escape_character('\\')
+        .build_with_formula_definitions([r"^\^→⋅\$", r"a\^$→⋅a"].into_iter())
+        .unwrap();
+
+    assert_eq!("$a", scheme.apply("^a", 10).unwrap().word());
+    assert_eq!("a^a", scheme.apply("a^a^", 10).unwrap().word());
+}
+
+#[test]
+fn the_anchors_can_be_combined_with_the_variables() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet("ab*".try_into().unwrap())
+        .with_variables()
+        .with_anchors()
+        .build_with_formula_definitions(["^s.xe.y*$→⋅e.ys.x"].into_iter())
+        .unwrap();
+
+    assert_eq!("baa", scheme.apply("aba*", 10).unwrap().word());
+    assert_eq!("*ba", scheme.apply("a*b*", 10).unwrap().word());
+}
+
+#[test]
+fn the_weight_of_a_formula_cannot_be_zero() {
+    assert_eq!(
+        Some(AlgorithmSchemeDefinitionError::ZeroWeight("b→c".to_owned())),
+        AlgorithmSchemeBuilder::new()
+            .with_stochastic_selection(0)
+            .build_with_weighted_formula_definitions([("a→b", 1), ("b→c", 0)].into_iter())
+            .err()
+    );
+}
+
+#[test]
+fn the_formulas_generated_from_a_template_share_its_weight() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet("abc".try_into().unwrap())
+        .with_template_brackets('[', ']')
+        .with_stochastic_selection(0)
+        .build_with_weighted_formula_definitions([("[x:ab]→c", 3), ("c→a", 2)].into_iter())
+        .unwrap();
+
+    let weights: Vec<_> = scheme
+        .substitution_formulas
+        .iter()
+        .map(|formula| formula.weight)
+        .collect();
+
+    assert_eq!(vec![3, 3, 2], weights);
+}
+
+#[test]
+fn the_group_markers_are_read_at_the_start_of_the_definitions() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet("ab".try_into().unwrap())
+        .with_anchors()
+        .with_priority_groups(GroupTieBreak::FirstFormula)
+        .build_with_formula_definitions(["|a→b", "|^b→a", "b→⋅", "|→a"].into_iter())
+        .unwrap();
+
+    let joins_group: Vec<_> = scheme
+        .substitution_formulas
+        .iter()
+        .map(|formula| formula.joins_group)
+        .collect();
+
+    assert_eq!(vec![true, true, false, true], joins_group);
+    assert!(scheme.substitution_formulas[1].anchors.start);
+    assert_eq!(
+        "b",
+        scheme.substitution_formulas[1]
+            .view()
+            .get_left(&scheme.store)
+    );
+}
+
+#[test]
+fn the_group_markers_are_not_read_unless_enabled() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet("a|".try_into().unwrap())
+        .build_with_formula_definitions(["|a→a"].into_iter())
+        .unwrap();
+
+    assert!(!scheme.substitution_formulas[0].joins_group);
+    assert_eq!("aa", scheme.apply("a|a", 10).unwrap().word());
+}
+
+#[test]
+fn the_misplaced_group_markers_are_reported_with_the_formula() {
+    for definition in ["a|→b", "||a→b", "a→|b"] {
+        assert_eq!(
+            Some(AlgorithmSchemeDefinitionError::FormulaCreationError {
+                source: SubstitutionFormulaDefinitionError::MisplacedGroupMarker(
+                    definition.to_owned()
+                )
+            }),
+            AlgorithmSchemeBuilder::new()
+                .with_alphabet("ab".try_into().unwrap())
+                .with_priority_groups(GroupTieBreak::FirstFormula)
+                .build_with_formula_definitions([definition].into_iter())
+                .err()
+        );
+    }
+}
+
+#[test]
+fn the_group_marker_should_not_be_reserved() {
+    let cases = [
+        AlgorithmSchemeBuilder::new().with_final_marker('|'),
+        AlgorithmSchemeBuilder::new().with_escape_character('|'),
+        AlgorithmSchemeBuilder::new().with_alphabet("a|".try_into().unwrap()),
+    ];
+
+    for builder in cases {
+        assert_eq!(
+            Some(AlgorithmSchemeDefinitionError::GroupMarkerIsReserved('|')),
+            builder
+                .with_priority_groups(GroupTieBreak::LongestMatch)
+                .build_with_formula_definitions(iter::empty())
+                .err()
+        );
+    }
+}
+
+#[test]
+fn the_group_marker_can_belong_to_the_alphabet_if_it_can_be_escaped() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet("a|".try_into().unwrap())
+        .with_priority_groups(GroupTieBreak::FirstFormula)
+        .with_escape_character('\\')
+        .build_with_formula_definitions([r"\|→⋅a", r"|a\|→⋅\|"].into_iter())
+        .unwrap();
+
+    assert!(!scheme.substitution_formulas[0].joins_group);
+    assert!(scheme.substitution_formulas[1].joins_group);
+    assert_eq!("||", scheme.apply("a||", 10).unwrap().word());
+}
@@ -0,0 +1,453 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! [SchemeFile](SchemeFile) structure, a text that fully describes an algorithm scheme.
+
+#[cfg(test)]
+mod tests;
+
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::alphabet::{Alphabet, AlphabetDefinitionError};
+
+use super::{
+    scheme_builder::{AlgorithmSchemeBuilder, AlgorithmSchemeDefinitionError},
+    AlgorithmScheme, FormulaLexer, GroupTieBreak, SchemeProperties,
+    SubstitutionFormulaDefinitionError,
+};
+
+/// A parsed scheme file: the builder configured by the header directives and the formula definitions.
+///
+/// Each line of the file is one of the following:
+/// - a blank line, which is ignored;
+/// - a comment that starts with `#`, possibly after whitespace, which is ignored;
+/// - a directive that starts with `@`, followed by the value after whitespace:
+///   `@alphabet`, `@extension`, `@delimiter`, `@final`, `@escape`, `@templates`, followed by
+///   the two template brackets, `@variables` and `@anchors`, followed by `on` or `off`,
+///   or `@groups`, followed by the [tie-break](GroupTieBreak) inside the groups, `first` or `longest`, or by `off`;
+/// - a formula definition.
+///
+/// The directives form the header and cannot follow the formula definitions.
+/// If `@escape` is present, the values of `@alphabet` and `@extension` may contain the same escape sequences
+/// as the formula definitions, so the alphabet can contain whitespace or new lines.
+/// A formula definition that starts with `@`, or with `#` after any whitespace, has to write that character
+/// with an escape sequence. Without `@escape`, a comment that contains the delimiter and starts with the characters
+/// of the alphabet is reported as ambiguous instead of being ignored.
+///
+/// # Example
+/// ```rust
+/// # use std::str;
+/// use markovalgorithms::prelude::*;
+///
+/// let file: SchemeFile = "\
+/// ## Replaces the letters with the digits.
+/// @alphabet ab01
+/// @delimiter ->
+/// @final .
+///
+/// a->0
+/// b->.1
+/// ".parse().unwrap();
+///
+/// let scheme = file.build().unwrap();
+///
+/// assert_eq!("010", scheme.apply("aba", 10).unwrap().word());
+/// ```
+#[derive(Clone)]
+pub struct SchemeFile {
+    builder: AlgorithmSchemeBuilder,
+    formulas: Vec<FormulaLine>,
+}
+
+#[derive(Debug, Clone)]
+struct FormulaLine {
+    line: usize,
+    definition: String,
+}
+
+impl SchemeFile {
+    const COMMENT: char = '#';
+    const DIRECTIVE: char = '@';
+
+    /// The builder configured by the directives of the file.
+    pub fn builder(&self) -> &AlgorithmSchemeBuilder {
+        &self.builder
+    }
+
+    /// The formula definitions in the order of the file.
+    pub fn formula_definitions(&self) -> impl Iterator<Item = &str> {
+        self.formulas
+            .iter()
+            .map(|formula| formula.definition.as_str())
+    }
+
+    /// The number of the line, counting from one, the formula is defined at.
+    pub fn line_of_formula(&self, formula_index: usize) -> Option<usize> {
+        self.formulas.get(formula_index).map(|formula| formula.line)
+    }
+
+    /// Replaces the builder, for example to override the directives of the file.
+    ///
+    /// # Example
+    /// ```rust
+    /// use markovalgorithms::prelude::*;
+    ///
+    /// let file: SchemeFile = "@delimiter >\na>b".parse().unwrap();
+    /// let builder = file.builder().clone().with_final_marker('!');
+    ///
+    /// let scheme = file.with_builder(builder).build().unwrap();
+    ///
+    /// assert_eq!("b", scheme.apply("a", 10).unwrap().word());
+    /// ```
+    pub fn with_builder(mut self, builder: AlgorithmSchemeBuilder) -> Self {
+        self.builder = builder;
+        self
+    }
+
+    /// Builds the scheme described by the file.
+    ///
+    /// # Errors
+    /// Returns an [error](AlgorithmSchemeDefinitionError) if the scheme cannot be built.
+    pub fn build(self) -> Result<AlgorithmScheme, AlgorithmSchemeDefinitionError> {
+        self.builder.build_with_formula_definitions(
+            self.formulas
+                .iter()
+                .map(|formula| formula.definition.as_str()),
+        )
+    }
+}
+
+impl FromStr for SchemeFile {
+    type Err = SchemeFileParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut header = Header::default();
+        let mut formulas = Vec::new();
+        let mut skipped_lines = Vec::new();
+
+        for (index, content) in text.lines().enumerate() {
+            let line = index + 1;
+
+            if content.trim().is_empty() || content.trim_start().starts_with(Self::COMMENT) {
+                skipped_lines.push((line, content));
+                continue;
+            }
+
+            if let Some(directive) = content.strip_prefix(Self::DIRECTIVE) {
+                if !formulas.is_empty() {
+                    return Err(SchemeFileParseError::DirectiveAfterFormulas { line });
+                }
+
+                header.add(line, directive)?;
+            } else {
+                formulas.push(FormulaLine {
+                    line,
+                    definition: content.to_owned(),
+                });
+            }
+        }
+
+        let builder = header.into_builder()?;
+        let properties = builder.clone().finalize_properties();
+
+        if let Some(&(line, _)) = skipped_lines
+            .iter()
+            .find(|(_, content)| Self::may_be_formula(&properties, content))
+        {
+            return Err(SchemeFileParseError::AmbiguousSkippedLine { line });
+        }
+
+        Ok(Self { builder, formulas })
+    }
+}
+
+impl SchemeFile {
+    /// Checks whether the skipped line may also be read as a formula definition,
+    /// which is the case if it contains the delimiter, the escape character is not set,
+    /// and the whitespace and the comment character it starts with belong to the alphabet.
+    fn may_be_formula(properties: &SchemeProperties, content: &str) -> bool {
+        let indented = content.trim_start();
+        let prefix_length = content.len() - indented.len()
+            + indented
+                .strip_prefix(Self::COMMENT)
+                .map_or(0, |_| Self::COMMENT.len_utf8());
+
+        properties.escape_character.is_none()
+            && content.contains(properties.delimiter.as_str())
+            && content[..prefix_length]
+                .chars()
+                .all(|character| properties.alphabet.contains_extended(character))
+    }
+}
+
+/// The values of the directives and the lines they are defined at.
+#[derive(Default)]
+struct Header<'a> {
+    alphabet: Option<(usize, &'a str)>,
+    extension: Option<(usize, &'a str)>,
+    delimiter: Option<(usize, &'a str)>,
+    final_marker: Option<(usize, &'a str)>,
+    escape_character: Option<(usize, &'a str)>,
+    template_brackets: Option<(usize, &'a str)>,
+    variables: Option<(usize, &'a str)>,
+    anchors: Option<(usize, &'a str)>,
+    groups: Option<(usize, &'a str)>,
+}
+
+impl<'a> Header<'a> {
+    fn add(&mut self, line: usize, directive: &'a str) -> Result<(), SchemeFileParseError> {
+        let (name, value) = directive
+            .split_once(char::is_whitespace)
+            .unwrap_or((directive, ""));
+        let value = value.trim();
+
+        let slot = match name {
+            "alphabet" => &mut self.alphabet,
+            "extension" => &mut self.extension,
+            "delimiter" => &mut self.delimiter,
+            "final" => &mut self.final_marker,
+            "escape" => &mut self.escape_character,
+            "templates" => &mut self.template_brackets,
+            "variables" => &mut self.variables,
+            "anchors" => &mut self.anchors,
+            "groups" => &mut self.groups,
+            _ => {
+                return Err(SchemeFileParseError::UnknownDirective {
+                    line,
+                    name: name.to_owned(),
+                })
+            }
+        };
+
+        if value.is_empty() {
+            return Err(SchemeFileParseError::MissingValue {
+                line,
+                name: name.to_owned(),
+            });
+        }
+
+        if slot.is_some() {
+            return Err(SchemeFileParseError::DuplicateDirective {
+                line,
+                name: name.to_owned(),
+            });
+        }
+
+        _ = slot.insert((line, value));
+
+        Ok(())
+    }
+
+    fn into_builder(self) -> Result<AlgorithmSchemeBuilder, SchemeFileParseError> {
+        let mut builder = AlgorithmSchemeBuilder::new();
+
+        if let Some((_, delimiter)) = self.delimiter {
+            builder = builder.with_delimiter(delimiter);
+        }
+        if let Some((_, final_marker)) = self.final_marker {
+            builder = builder.with_final_marker(final_marker);
+        }
+        if let Some(escape_character) = Self::parse_character("escape", self.escape_character)? {
+            builder = builder.with_escape_character(escape_character);
+        }
+        if let Some((line, brackets)) = self.template_brackets {
+            let mut characters = brackets.chars();
+
+            let (Some(open), Some(close), None) =
+                (characters.next(), characters.next(), characters.next())
+            else {
+                return Err(SchemeFileParseError::NotABracketPair {
+                    line,
+                    value: brackets.to_owned(),
+                });
+            };
+
+            builder = builder.with_template_brackets(open, close);
+        }
+        if Self::parse_switch("variables", self.variables)? {
+            builder = builder.with_variables();
+        }
+        if Self::parse_switch("anchors", self.anchors)? {
+            builder = builder.with_anchors();
+        }
+        if let Some(tie_break) = Self::parse_tie_break(self.groups)? {
+            builder = builder.with_priority_groups(tie_break);
+        }
+
+        let properties = builder.clone().finalize_properties();
+
+        let unescape = |(line, value): (usize, &str)| {
+            FormulaLexer {
+                formula_definition: value,
+                properties: &properties,
+            }
+            .tokenize()
+            .map(|characters| {
+                (
+                    line,
+                    characters
+                        .iter()
+                        .map(|character| character.value)
+                        .collect::<String>(),
+                )
+            })
+            .map_err(|error| match error {
+                SubstitutionFormulaDefinitionError::InvalidEscapeSequence(_, sequence) => {
+                    SchemeFileParseError::InvalidEscapeSequence { line, sequence }
+                }
+                _ => unreachable!("Only the escape sequences are checked by the lexer."),
+            })
+        };
+
+        let alphabet = self.alphabet.map(unescape).transpose()?;
+        let extension = self.extension.map(unescape).transpose()?;
+
+        match (alphabet, extension) {
+            (None, None) => {}
+            (None, Some((line, _))) => {
+                return Err(SchemeFileParseError::ExtensionWithoutAlphabet { line })
+            }
+            (Some((line, alphabet)), extension) => {
+                let alphabet = Alphabet::from_str(&alphabet)
+                    .map_err(|source| SchemeFileParseError::InvalidAlphabet { line, source })?;
+
+                let alphabet = match extension {
+                    Some((line, extension)) => extension
+                        .chars()
+                        .try_fold(alphabet, Alphabet::extend)
+                        .map_err(|source| SchemeFileParseError::InvalidAlphabet { line, source })?,
+                    None => alphabet,
+                };
+
+                builder = builder.with_alphabet(alphabet);
+            }
+        }
+
+        Ok(builder)
+    }
+
+    fn parse_character(
+        name: &str,
+        directive: Option<(usize, &str)>,
+    ) -> Result<Option<char>, SchemeFileParseError> {
+        let Some((line, value)) = directive else {
+            return Ok(None);
+        };
+
+        let mut characters = value.chars();
+
+        match (characters.next(), characters.next()) {
+            (Some(character), None) => Ok(Some(character)),
+            _ => Err(SchemeFileParseError::NotASingleCharacter {
+                line,
+                name: name.to_owned(),
+                value: value.to_owned(),
+            }),
+        }
+    }
+
+    /// Reads `first` or `longest`, `off` and a missing directive mean that the formulas are not grouped.
+    fn parse_tie_break(
+        directive: Option<(usize, &str)>,
+    ) -> Result<Option<GroupTieBreak>, SchemeFileParseError> {
+        match directive {
+            None | Some((_, "off")) => Ok(None),
+            Some((_, "first")) => Ok(Some(GroupTieBreak::FirstFormula)),
+            Some((_, "longest")) => Ok(Some(GroupTieBreak::LongestMatch)),
+            Some((line, value)) => Err(SchemeFileParseError::NotATieBreak {
+                line,
+                value: value.to_owned(),
+            }),
+        }
+    }
+
+    /// Reads `on` or `off`, a missing directive means `off`.
+    fn parse_switch(
+        name: &str,
+        directive: Option<(usize, &str)>,
+    ) -> Result<bool, SchemeFileParseError> {
+        match directive {
+            None | Some((_, "off")) => Ok(false),
+            Some((_, "on")) => Ok(true),
+            Some((line, value)) => Err(SchemeFileParseError::NotASwitch {
+                line,
+                name: name.to_owned(),
+                value: value.to_owned(),
+            }),
+        }
+    }
+}
+
+/// An error in a scheme file, the lines are counted from one.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SchemeFileParseError {
+    /// The directive is not known.
+    #[error("line {line}: unknown directive \"@{name}\"")]
+    UnknownDirective { line: usize, name: String },
+    /// The directive has no value.
+    #[error("line {line}: the directive \"@{name}\" has no value")]
+    MissingValue { line: usize, name: String },
+    /// The directive is repeated.
+    #[error("line {line}: the directive \"@{name}\" is repeated")]
+    DuplicateDirective { line: usize, name: String },
+    /// The directives should precede the formula definitions.
+    #[error("line {line}: the directives should precede the formula definitions")]
+    DirectiveAfterFormulas { line: usize },
+    /// The directive expects a single character.
+    #[error(
+        "line {line}: the directive \"@{name}\" expects a single character, found \"{value}\""
+    )]
+    NotASingleCharacter {
+        line: usize,
+        name: String,
+        value: String,
+    },
+    /// The directive expects the opening and the closing brackets.
+    #[error("line {line}: the directive \"@templates\" expects two brackets, found \"{value}\"")]
+    NotABracketPair { line: usize, value: String },
+    /// The directive expects `on` or `off`.
+    #[error("line {line}: the directive \"@{name}\" expects \"on\" or \"off\", found \"{value}\"")]
+    NotASwitch {
+        line: usize,
+        name: String,
+        value: String,
+    },
+    /// The directive expects a tie-break or `off`.
+    #[error(
+        "line {line}: the directive \"@groups\" expects \"first\", \"longest\" or \"off\", found \"{value}\""
+    )]
+    NotATieBreak { line: usize, value: String },
+    /// The value contains an invalid escape sequence.
+    #[error("line {line}: the value contains an invalid escape sequence \"{sequence}\"")]
+    InvalidEscapeSequence { line: usize, sequence: String },
+    /// The extension is defined without the alphabet.
+    #[error("line {line}: the extension cannot be defined without the alphabet")]
+    ExtensionWithoutAlphabet { line: usize },
+    /// The line is ignored as a comment or a blank line, but may also be read as a formula definition.
+    #[error("line {line}: the line is ignored, but it may be a formula definition, set \"@escape\" to write it with an escape sequence")]
+    AmbiguousSkippedLine { line: usize },
+    /// The alphabet or its extension is not valid.
+    #[error("line {line}: {source}")]
+    InvalidAlphabet {
+        line: usize,
+        source: AlphabetDefinitionError,
+    },
+}
@@ -0,0 +1,302 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::*;
+use crate::scheme::AlgorithmSchemeInputValidationError;
+
+#[test]
+fn the_comments_and_blank_lines_are_ignored() {
+    let file: SchemeFile = "# A comment.\n\n  \na→b\n\n# Another comment.\nb→⋅c\n"
+        .parse()
+        .unwrap();
+
+    assert_eq!(
+        vec!["a→b", "b→⋅c"],
+        file.formula_definitions().collect::<Vec<_>>()
+    );
+    assert_eq!(Some(4), file.line_of_formula(0));
+    assert_eq!(Some(7), file.line_of_formula(1));
+    assert_eq!(None, file.line_of_formula(2));
+}
+
+#[test]
+fn the_indented_comments_are_ignored() {
+    let file: SchemeFile = "@alphabet ab\n  # An indented comment.\n\t# Another one.\na→b\n"
+        .parse()
+        .unwrap();
+
+    assert_eq!(vec!["a→b"], file.formula_definitions().collect::<Vec<_>>());
+    assert_eq!(Some(4), file.line_of_formula(0));
+    assert_eq!("bb", file.build().unwrap().apply("ab", 10).unwrap().word());
+}
+
+#[test]
+fn a_skipped_line_that_may_be_a_formula_is_reported() {
+    for text in [
+        "@alphabet #a\n#a→a#\na→⋅a",
+        "@alphabet a\n@extension #\na→⋅a\n#→",
+        "@alphabet # a\n  #a→a\na→⋅a",
+    ] {
+        let line = if text.contains("@extension") { 4 } else { 2 };
+
+        assert_eq!(
+            Err(SchemeFileParseError::AmbiguousSkippedLine { line }),
+            text.parse::<SchemeFile>().map(|_| ()),
+            "{text}"
+        );
+    }
+}
+
+#[test]
+fn a_skipped_line_that_cannot_be_a_formula_is_ignored() {
+    for text in [
+        "@alphabet #a\n# No delimiter here.\na→⋅a",
+        "@alphabet ab\n#a→b\na→⋅a",
+        "@alphabet #a\n  #a→b\na→⋅a",
+        "@alphabet #a\n@escape \\\n#a→b\n\\u{23}→⋅a",
+    ] {
+        let file: SchemeFile = text.parse().unwrap();
+
+        assert_eq!(1, file.formula_definitions().count(), "{text}");
+        assert!(file.build().is_ok(), "{text}");
+    }
+}
+
+#[test]
+fn the_directives_configure_the_builder() {
+    let file: SchemeFile = "\
+@alphabet   ab
+@extension  |
+@delimiter  ->
+@final      !
+a->|
+|->!b
+"
+    .parse()
+    .unwrap();
+
+    let scheme = file.build().unwrap();
+
+    assert_eq!("bb", scheme.apply("ab", 10).unwrap().word());
+    assert_eq!(
+        Err(AlgorithmSchemeInputValidationError::ExtensionCharactersEncountered("|".to_owned())),
+        scheme.apply_once("|").map(|_| ())
+    );
+}
+
+#[test]
+fn the_escape_sequences_can_be_used_in_the_alphabet() {
+    let file: SchemeFile = r"
+@escape \
+@alphabet a\u{20}\→
+@extension \n
+\u{20}→\n
+\n→⋅\→
+"
+    .parse()
+    .unwrap();
+
+    let scheme = file.build().unwrap();
+
+    assert_eq!("a→a", scheme.apply("a a", 10).unwrap().word());
+}
+
+#[test]
+fn the_templates_can_be_enabled() {
+    let file: SchemeFile = "\
+@alphabet _ab
+@templates <>
+_<x:ab>→x_
+_→⋅
+"
+    .parse()
+    .unwrap();
+
+    assert_eq!(Some(3), file.line_of_formula(0));
+    assert_eq!("ba", file.build().unwrap().apply("_ba", 10).unwrap().word());
+}
+
+#[test]
+fn the_anchors_can_be_enabled() {
+    let file: SchemeFile = "\
+@alphabet ab
+@anchors on
+^a→⋅b
+a$→⋅b
+"
+    .parse()
+    .unwrap();
+
+    let scheme = file.build().unwrap();
+
+    assert_eq!("bba", scheme.apply("aba", 10).unwrap().word());
+    assert_eq!("bbb", scheme.apply("bba", 10).unwrap().word());
+}
+
+#[test]
+fn the_variables_can_be_enabled() {
+    let file: SchemeFile = "\
+@alphabet ab*#
+@variables on
+e.x*e.y#→⋅e.y*e.x
+"
+    .parse()
+    .unwrap();
+
+    assert_eq!(
+        "b*a",
+        file.build().unwrap().apply("a*b#", 10).unwrap().word()
+    );
+}
+
+#[test]
+fn the_files_without_directives_use_the_defaults() {
+    let file: SchemeFile = "a→b".parse().unwrap();
+
+    assert_eq!("bz", file.build().unwrap().apply("az", 10).unwrap().word());
+}
+
+#[test]
+fn the_directives_cannot_follow_the_formulas() {
+    assert_eq!(
+        Err(SchemeFileParseError::DirectiveAfterFormulas { line: 3 }),
+        "a→b\n# Comment.\n@alphabet ab"
+            .parse::<SchemeFile>()
+            .map(|_| ())
+    );
+}
+
+#[test]
+fn the_malformed_directives_are_reported_with_their_lines() {
+    let cases = [
+        (
+            "@alpha abc",
+            SchemeFileParseError::UnknownDirective {
+                line: 1,
+                name: "alpha".to_owned(),
+            },
+        ),
+        (
+            "\n@alphabet",
+            SchemeFileParseError::MissingValue {
+                line: 2,
+                name: "alphabet".to_owned(),
+            },
+        ),
+        (
+            "@final !\n@final .",
+            SchemeFileParseError::DuplicateDirective {
+                line: 2,
+                name: "final".to_owned(),
+            },
+        ),
+        (
+            "@variables yes",
+            SchemeFileParseError::NotASwitch {
+                line: 1,
+                name: "variables".to_owned(),
+                value: "yes".to_owned(),
+            },
+        ),
+        (
+            "@anchors 1",
+            SchemeFileParseError::NotASwitch {
+                line: 1,
+                name: "anchors".to_owned(),
+                value: "1".to_owned(),
+            },
+        ),
+        (
+            "@groups on",
+            SchemeFileParseError::NotATieBreak {
+                line: 1,
+                value: "on".to_owned(),
+            },
+        ),
+        (
+            "@templates [",
+            SchemeFileParseError::NotABracketPair {
+                line: 1,
+                value: "[".to_owned(),
+            },
+        ),
+        (
+            "@escape \\\\",
+            SchemeFileParseError::NotASingleCharacter {
+                line: 1,
+                name: "escape".to_owned(),
+                value: "\\\\".to_owned(),
+            },
+        ),
+        (
+            "@escape \\\n@alphabet a\\q",
+            SchemeFileParseError::InvalidEscapeSequence {
+                line: 2,
+                sequence: "\\q".to_owned(),
+            },
+        ),
+        (
+            "@extension |",
+            SchemeFileParseError::ExtensionWithoutAlphabet { line: 1 },
+        ),
+        (
+            "@alphabet ab\n@extension b",
+            SchemeFileParseError::InvalidAlphabet {
+                line: 2,
+                source: AlphabetDefinitionError::ExtendedWithADuplicate,
+            },
+        ),
+    ];
+
+    for (text, expected) in cases {
+        assert_eq!(
+            Err(expected),
+            text.parse::<SchemeFile>().map(|_| ()),
+            "{text}"
+        );
+    }
+}
+
+#[test]
+fn the_builder_of_the_file_can_be_replaced() {
+    let file: SchemeFile = "@alphabet ab\na→b".parse().unwrap();
+    let builder = file
+        .builder()
+        .clone()
+        .with_alphabet(Alphabet::from_str("abc").unwrap());
+
+    let scheme = file.with_builder(builder).build().unwrap();
+
+    assert_eq!("bc", scheme.apply("ac", 10).unwrap().word());
+}
+
+#[test]
+fn the_priority_groups_can_be_enabled() {
+    let file: SchemeFile = "\
+@alphabet abc
+@groups longest
+b→⋅c
+|a→⋅c
+|ab→⋅
+"
+    .parse()
+    .unwrap();
+
+    assert_eq!("b", file.build().unwrap().apply("abb", 10).unwrap().word());
+}
@@ -0,0 +1,275 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! [ApplicationSnapshot](ApplicationSnapshot) structure that allows to persist and restore a running application.
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use thiserror::Error;
+
+use super::AlgorithmSchemeInputValidationError;
+
+/// The state of a step-by-step application, detached from the scheme.
+///
+/// The snapshot is serialized to a stable text format with [Display](std::fmt::Display)
+/// and deserialized with [FromStr](std::str::FromStr), and it can only be restored against the same scheme,
+/// which is checked with the fingerprint of the scheme.
+///
+/// # Example
+/// ```rust
+/// # use std::str;
+/// use markovalgorithms::prelude::*;
+///
+/// let scheme = AlgorithmSchemeBuilder::new()
+///     .with_alphabet(str::parse("ab").unwrap())
+///     .build_with_formula_definitions(["a→b"].into_iter())
+///     .unwrap();
+///
+/// let mut iterator = scheme.get_application_iterator("aaa").unwrap();
+///
+/// assert_eq!("baa", iterator.next().unwrap().word());
+///
+/// let serialized = iterator.snapshot().to_string();
+/// let snapshot: ApplicationSnapshot = serialized.parse().unwrap();
+///
+/// let mut iterator = scheme
+///     .restore_application_iterator(&snapshot, ExecutionLimits::new())
+///     .unwrap();
+///
+/// assert_eq!("bba", iterator.next().unwrap().word());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApplicationSnapshot {
+    pub(crate) scheme_fingerprint: u64,
+    pub(crate) word: String,
+    pub(crate) steps_done: u64,
+    pub(crate) is_completed: bool,
+    pub(crate) last_applied_formula: Option<usize>,
+}
+
+impl ApplicationSnapshot {
+    const HEADER: &'static str = "markov-algorithms snapshot 2";
+
+    /// The fingerprint of the scheme the snapshot has been taken from.
+    pub fn scheme_fingerprint(&self) -> u64 {
+        self.scheme_fingerprint
+    }
+
+    /// The word at the moment the snapshot has been taken.
+    pub fn word(&self) -> &str {
+        &self.word
+    }
+
+    /// The number of steps done before the snapshot has been taken.
+    pub fn steps_done(&self) -> u64 {
+        self.steps_done
+    }
+
+    /// Reports whether the algorithm has been completed.
+    pub fn is_completed(&self) -> bool {
+        self.is_completed
+    }
+}
+
+impl Display for ApplicationSnapshot {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(formatter, "{}", Self::HEADER)?;
+        writeln!(formatter, "scheme {:016x}", self.scheme_fingerprint)?;
+        writeln!(formatter, "steps {}", self.steps_done)?;
+        writeln!(formatter, "completed {}", self.is_completed)?;
+        match self.last_applied_formula {
+            Some(formula_index) => writeln!(formatter, "formula {formula_index}")?,
+            None => writeln!(formatter, "formula none")?,
+        }
+        // The word is preceded by its length in bytes and written as is, so it may contain any characters,
+        // including line breaks, and the line break after it is not a part of it.
+        writeln!(formatter, "word {}", self.word.len())?;
+        writeln!(formatter, "{}", self.word)
+    }
+}
+
+impl FromStr for ApplicationSnapshot {
+    type Err = ApplicationSnapshotParseError;
+
+    fn from_str(serialized: &str) -> Result<Self, Self::Err> {
+        let mut rest = serialized;
+
+        if Self::next_line(&mut rest) != Some(Self::HEADER) {
+            return Err(ApplicationSnapshotParseError::UnsupportedFormat);
+        }
+
+        let mut field = |name: &'static str| {
+            Self::next_line(&mut rest)
+                .and_then(|line| line.strip_prefix(name))
+                .and_then(|line| line.strip_prefix(' '))
+                .ok_or(ApplicationSnapshotParseError::MissingField(name))
+        };
+
+        let invalid =
+            |name: &'static str, value: &str| ApplicationSnapshotParseError::InvalidField {
+                name,
+                value: value.to_owned(),
+            };
+
+        let scheme = field("scheme")?;
+        let scheme_fingerprint =
+            u64::from_str_radix(scheme, 16).map_err(|_| invalid("scheme", scheme))?;
+
+        let steps = field("steps")?;
+        let steps_done = steps.parse().map_err(|_| invalid("steps", steps))?;
+
+        let completed = field("completed")?;
+        let is_completed = completed
+            .parse()
+            .map_err(|_| invalid("completed", completed))?;
+
+        let formula = field("formula")?;
+        let last_applied_formula = match formula {
+            "none" => None,
+            formula_index => Some(
+                formula_index
+                    .parse()
+                    .map_err(|_| invalid("formula", formula))?,
+            ),
+        };
+
+        let length = field("word")?;
+
+        // Only the line breaks may follow the word, whatever their style is.
+        let word = length
+            .parse()
+            .ok()
+            .and_then(|length| rest.get(..length).zip(rest.get(length..)))
+            .filter(|(_, after)| after.trim_end_matches(['\r', '\n']).is_empty())
+            .map(|(word, _)| word.to_owned())
+            .ok_or_else(|| invalid("word", length))?;
+
+        Ok(Self {
+            scheme_fingerprint,
+            word,
+            steps_done,
+            is_completed,
+            last_applied_formula,
+        })
+    }
+}
+
+impl ApplicationSnapshot {
+    /// Takes the next line off the text, the line may end with either `\n` or `\r\n`.
+    fn next_line<'a>(text: &mut &'a str) -> Option<&'a str> {
+        if text.is_empty() {
+            return None;
+        }
+
+        let (line, rest) = text.split_once('\n').unwrap_or((text, ""));
+        *text = rest;
+
+        Some(line.strip_suffix('\r').unwrap_or(line))
+    }
+}
+
+/// An error of the snapshot deserialization.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ApplicationSnapshotParseError {
+    /// The text does not start with the header of a supported snapshot format.
+    #[error("the text is not a snapshot of a supported format")]
+    UnsupportedFormat,
+    /// A field is absent or out of order.
+    #[error("the field \"{0}\" is missing in the snapshot")]
+    MissingField(&'static str),
+    /// A field has a value that cannot be parsed.
+    #[error("the field \"{name}\" of the snapshot has an invalid value \"{value}\"")]
+    InvalidField {
+        /// The name of the field.
+        name: &'static str,
+        /// The value of the field.
+        value: String,
+    },
+}
+
+/// An error of the restoration of an application from a snapshot.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ApplicationSnapshotRestorationError {
+    /// The snapshot has been taken from a different scheme.
+    #[error(
+        "the snapshot has been taken from the scheme {found:016x}, but the scheme is {expected:016x}"
+    )]
+    SchemeMismatch {
+        /// The fingerprint of the scheme the snapshot is restored against.
+        expected: u64,
+        /// The fingerprint stored in the snapshot.
+        found: u64,
+    },
+    /// The word of the snapshot cannot be processed by the scheme.
+    #[error("the word of the snapshot is not valid")]
+    InputValidationError {
+        /// The validation error.
+        #[from]
+        source: AlgorithmSchemeInputValidationError,
+    },
+}
+
+/// The 64-bit FNV-1a hash, which does not depend on the platform or the version of the standard library.
+#[derive(Debug)]
+pub(crate) struct Fingerprint {
+    state: u64,
+}
+
+impl Fingerprint {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    pub(crate) fn new() -> Self {
+        Self {
+            state: Self::OFFSET_BASIS,
+        }
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state ^= u64::from(byte);
+            self.state = self.state.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    /// Writes the string prefixed with its length, so the boundaries of consecutive strings are not ambiguous.
+    pub(crate) fn write_str(&mut self, text: &str) {
+        self.write(&(text.len() as u64).to_le_bytes());
+        self.write(text.as_bytes());
+    }
+
+    /// Writes whether an optional value is set, before the value itself if it is.
+    pub(crate) fn write_presence(&mut self, is_present: bool) {
+        self.write(&[u8::from(is_present)]);
+    }
+
+    pub(crate) fn write_char(&mut self, character: char) {
+        self.write(&u32::from(character).to_le_bytes());
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.state
+    }
+}
@@ -0,0 +1,167 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::*;
+
+fn create_snapshot(word: &str) -> ApplicationSnapshot {
+    ApplicationSnapshot {
+        scheme_fingerprint: 0x0123_4567_89ab_cdef,
+        word: word.to_owned(),
+        steps_done: 42,
+        is_completed: false,
+        last_applied_formula: Some(3),
+    }
+}
+
+#[test]
+fn a_snapshot_is_serialized_to_the_stable_format() {
+    let snapshot = create_snapshot("ab");
+
+    assert_eq!(
+        "markov-algorithms snapshot 2\nscheme 0123456789abcdef\nsteps 42\ncompleted false\nformula 3\nword 2\nab\n",
+        snapshot.to_string()
+    );
+}
+
+#[test]
+fn a_snapshot_survives_the_round_trip() {
+    for word in ["", "ab", "a\nb\n", "ぬ\r\n", "word\nformula none"] {
+        let snapshot = create_snapshot(word);
+
+        assert_eq!(Ok(snapshot.clone()), snapshot.to_string().parse());
+    }
+
+    let snapshot = ApplicationSnapshot {
+        last_applied_formula: None,
+        is_completed: true,
+        ..create_snapshot("a")
+    };
+
+    assert_eq!(Ok(snapshot.clone()), snapshot.to_string().parse());
+}
+
+#[test]
+fn a_snapshot_with_the_windows_line_breaks_is_parsed() {
+    for word in ["", "ab", "a\nb", "ぬ\r\n"] {
+        let snapshot = create_snapshot(word);
+        let serialized = snapshot.to_string();
+
+        // The line breaks of the word are its bytes, so only the ones of the header and the fields are replaced.
+        let (fields, word_and_break) = serialized.split_at(serialized.len() - word.len() - 1);
+        let crlf = format!("{}{word_and_break}", fields.replace('\n', "\r\n"));
+
+        assert_eq!(Ok(snapshot.clone()), crlf.parse());
+        assert_eq!(Ok(snapshot), format!("{}\r\n", crlf.trim_end()).parse());
+    }
+}
+
+#[test]
+fn a_trailing_line_break_is_not_a_part_of_the_word() {
+    let snapshot = create_snapshot("ab");
+    let serialized = snapshot.to_string();
+
+    for appended in ["\n", "\r\n", "\n\n"] {
+        assert_eq!(
+            Ok(snapshot.clone()),
+            format!("{serialized}{appended}").parse()
+        );
+    }
+
+    assert_eq!(
+        Ok(snapshot),
+        serialized
+            .trim_end_matches('\n')
+            .parse::<ApplicationSnapshot>()
+    );
+
+    let snapshot = create_snapshot("ab\n");
+
+    assert_eq!(Ok(snapshot.clone()), format!("{snapshot}\n").parse());
+}
+
+#[test]
+fn a_word_that_does_not_match_its_length_is_reported() {
+    let header = "markov-algorithms snapshot 2\nscheme 0\nsteps 1\ncompleted false\nformula none\n";
+
+    for (word, length) in [
+        ("word 3\nab", "3"),
+        ("word 1\nab", "1"),
+        ("word 1\nぬ", "1"),
+        ("word x\n", "x"),
+    ] {
+        assert_eq!(
+            Err(ApplicationSnapshotParseError::InvalidField {
+                name: "word",
+                value: length.to_owned()
+            }),
+            format!("{header}{word}").parse::<ApplicationSnapshot>()
+        );
+    }
+}
+
+#[test]
+fn a_text_of_an_unknown_format_is_not_parsed() {
+    assert_eq!(
+        Err(ApplicationSnapshotParseError::UnsupportedFormat),
+        "markov-algorithms snapshot 3\n".parse::<ApplicationSnapshot>()
+    );
+    assert_eq!(
+        Err(ApplicationSnapshotParseError::UnsupportedFormat),
+        "markov-algorithms snapshot 1\nscheme 0\nsteps 0\ncompleted false\nformula none\nword\n"
+            .parse::<ApplicationSnapshot>()
+    );
+}
+
+#[test]
+fn a_missing_field_is_reported() {
+    assert_eq!(
+        Err(ApplicationSnapshotParseError::MissingField("steps")),
+        "markov-algorithms snapshot 2\nscheme 0\ncompleted false".parse::<ApplicationSnapshot>()
+    );
+    assert_eq!(
+        Err(ApplicationSnapshotParseError::MissingField("word")),
+        "markov-algorithms snapshot 2\nscheme 0\nsteps 1\ncompleted false\nformula none"
+            .parse::<ApplicationSnapshot>()
+    );
+}
+
+#[test]
+fn an_invalid_field_is_reported() {
+    assert_eq!(
+        Err(ApplicationSnapshotParseError::InvalidField {
+            name: "steps",
+            value: "-1".to_owned()
+        }),
+        "markov-algorithms snapshot 2\nscheme 0\nsteps -1\ncompleted false\nformula none\nword 0\n"
+            .parse::<ApplicationSnapshot>()
+    );
+}
+
+#[test]
+fn the_fingerprint_matches_the_reference_fnv_1a_values() {
+    let fingerprint = |bytes: &[u8]| {
+        let mut fingerprint = Fingerprint::new();
+        fingerprint.write(bytes);
+        fingerprint.finish()
+    };
+
+    assert_eq!(0xcbf2_9ce4_8422_2325, fingerprint(b""));
+    assert_eq!(0xaf63_dc4c_8601_ec8c, fingerprint(b"a"));
+    assert_eq!(0x8594_4171_f739_67e8, fingerprint(b"foobar"));
+}
@@ -0,0 +1,554 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! [SymbolScheme](SymbolScheme) structure, an algorithm scheme over arbitrary symbols instead of characters.
+//!
+//! The symbols are interned, so the engine works with compact identifiers
+//! regardless of how large or expensive to compare the symbols are.
+
+#[cfg(test)]
+mod tests;
+
+use std::{collections::HashMap, hash::Hash};
+
+use thiserror::Error;
+
+use super::{
+    engine::{self, Execution, Progress, Rules, RunOutcome},
+    limits::{ExecutionLimits, ExhaustedLimit},
+};
+
+/// A substitution formula over symbols.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolFormula<S> {
+    left: Vec<S>,
+    right: Vec<S>,
+    is_final: bool,
+}
+
+impl<S> SymbolFormula<S> {
+    /// Creates a simple formula that replaces the left side with the right one.
+    pub fn new<L, R>(left: L, right: R) -> Self
+    where
+        L: IntoIterator<Item = S>,
+        R: IntoIterator<Item = S>,
+    {
+        Self {
+            left: left.into_iter().collect(),
+            right: right.into_iter().collect(),
+            is_final: false,
+        }
+    }
+
+    /// Creates a final formula that replaces the left side with the right one and stops the algorithm.
+    pub fn new_final<L, R>(left: L, right: R) -> Self
+    where
+        L: IntoIterator<Item = S>,
+        R: IntoIterator<Item = S>,
+    {
+        Self {
+            is_final: true,
+            ..Self::new(left, right)
+        }
+    }
+
+    /// The symbols to be replaced.
+    pub fn left(&self) -> &[S] {
+        &self.left
+    }
+
+    /// The symbols to replace with.
+    pub fn right(&self) -> &[S] {
+        &self.right
+    }
+
+    /// Reports whether the formula stops the algorithm.
+    pub fn is_final(&self) -> bool {
+        self.is_final
+    }
+}
+
+/// A builder to configure a scheme over symbols.
+///
+/// # Example
+/// Basic usage:
+/// ```rust
+/// use markovalgorithms::prelude::*;
+///
+/// #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// enum Token {
+///     Zero,
+///     One,
+///     Marker,
+/// }
+///
+/// use Token::*;
+///
+/// let scheme = SymbolSchemeBuilder::new()
+///     .with_alphabet([Zero, One, Marker])
+///     .with_formula(SymbolFormula::new([Marker, One], [Zero, Marker]))
+///     .with_formula(SymbolFormula::new_final([Marker], []))
+///     .build()
+///     .unwrap();
+///
+/// let result = scheme.apply(&[Marker, One, One], 10).unwrap();
+///
+/// assert_eq!([Zero, Zero], result.word());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SymbolSchemeBuilder<S> {
+    alphabet: Option<Vec<S>>,
+    formulas: Vec<SymbolFormula<S>>,
+}
+
+impl<S> Default for SymbolSchemeBuilder<S> {
+    fn default() -> Self {
+        Self {
+            alphabet: None,
+            formulas: Vec::new(),
+        }
+    }
+}
+
+impl<S> SymbolSchemeBuilder<S>
+where
+    S: Clone + Eq + Hash,
+{
+    /// Creates a new builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an alphabet to the builder, the duplicates are ignored.
+    ///
+    /// May be called multiple times in order to replace the prior alphabet.
+    pub fn with_alphabet<I>(mut self, alphabet: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+    {
+        _ = self.alphabet.insert(alphabet.into_iter().collect());
+        self
+    }
+
+    /// Adds a formula after the already added ones.
+    pub fn with_formula(mut self, formula: SymbolFormula<S>) -> Self {
+        self.formulas.push(formula);
+        self
+    }
+
+    /// Adds the formulas after the already added ones.
+    pub fn with_formulas<I>(mut self, formulas: I) -> Self
+    where
+        I: IntoIterator<Item = SymbolFormula<S>>,
+    {
+        self.formulas.extend(formulas);
+        self
+    }
+
+    /// Builds a scheme based on the provided alphabet and formulas.
+    ///
+    /// # Errors
+    /// Returns an [error](SymbolSchemeDefinitionError) if the alphabet is not provided or empty,
+    /// or if a formula contains a symbol that does not belong to the alphabet.
+    pub fn build(self) -> Result<SymbolScheme<S>, SymbolSchemeDefinitionError<S>> {
+        let mut symbols = Vec::new();
+        let mut ids = HashMap::new();
+
+        for symbol in self.alphabet.unwrap_or_default() {
+            if !ids.contains_key(&symbol) {
+                let id = u32::try_from(symbols.len())
+                    .map_err(|_| SymbolSchemeDefinitionError::TooManySymbols)?;
+
+                _ = ids.insert(symbol.clone(), id);
+                symbols.push(symbol);
+            }
+        }
+
+        if symbols.is_empty() {
+            return Err(SymbolSchemeDefinitionError::EmptyAlphabet);
+        }
+
+        let interned = self
+            .formulas
+            .iter()
+            .enumerate()
+            .map(|(formula_index, formula)| {
+                let intern = |part: &[S]| {
+                    part.iter()
+                        .map(|symbol| {
+                            ids.get(symbol).copied().ok_or_else(|| {
+                                SymbolSchemeDefinitionError::UnknownSymbolInFormula {
+                                    formula_index,
+                                    symbol: symbol.clone(),
+                                }
+                            })
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                };
+
+                Ok((intern(&formula.left)?, intern(&formula.right)?))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let rules = Rules::new(
+            interned
+                .iter()
+                .zip(&self.formulas)
+                .map(|((left, right), formula)| (&left[..], &right[..], formula.is_final)),
+        );
+
+        Ok(SymbolScheme {
+            symbols,
+            ids,
+            formulas: self.formulas,
+            rules,
+        })
+    }
+}
+
+/// An algorithm scheme over arbitrary symbols, like bytes, multi-character tokens or user enumerations.
+///
+/// The scheme over characters can be expressed as `SymbolScheme<char>`,
+/// but [AlgorithmScheme](crate::scheme::AlgorithmScheme) is more convenient and faster for strings.
+///
+/// # Example
+/// ```rust
+/// use markovalgorithms::prelude::*;
+///
+/// let scheme = SymbolSchemeBuilder::new()
+///     .with_alphabet(["x", "y", "plus"])
+///     .with_formula(SymbolFormula::new(["x", "plus"], ["plus", "x"]))
+///     .with_formula(SymbolFormula::new_final(["plus"], []))
+///     .build()
+///     .unwrap();
+///
+/// let result = scheme.apply(&["x", "x", "plus", "y"], 100).unwrap();
+///
+/// assert_eq!(["x", "x", "y"], result.word());
+/// assert_eq!(3, result.steps_done());
+/// ```
+#[derive(Debug)]
+pub struct SymbolScheme<S> {
+    symbols: Vec<S>,
+    ids: HashMap<S, u32>,
+    formulas: Vec<SymbolFormula<S>>,
+    rules: Rules<u32>,
+}
+
+impl<S> SymbolScheme<S>
+where
+    S: Clone + Eq + Hash,
+{
+    /// The formulas of the scheme, in the order they are checked.
+    pub fn formulas(&self) -> &[SymbolFormula<S>] {
+        &self.formulas
+    }
+
+    /// Checks whether the symbol belongs to the alphabet of the scheme.
+    pub fn contains(&self, symbol: &S) -> bool {
+        self.ids.contains_key(symbol)
+    }
+
+    /// Applies the scheme to the input word until the algorithm is completed.
+    ///
+    /// # Arguments
+    /// - `word` — the input word.
+    /// - `limits` — the [limits](ExecutionLimits) of the application, or simply the maximum number of steps to do,
+    ///   the length of the word is measured in symbols.
+    ///
+    /// # Errors
+    /// Returns an [error](SymbolSchemeApplicationError) if the input is not valid,
+    /// or a distinct error for each exhausted limit that carries the [partial result](PartialSymbolApplicationResult).
+    pub fn apply<L>(
+        &self,
+        word: &[S],
+        limits: L,
+    ) -> Result<SymbolApplicationResult<S>, SymbolSchemeApplicationError<S>>
+    where
+        L: Into<ExecutionLimits>,
+    {
+        let limits = limits.into();
+
+        if limits.steps() == Some(0) {
+            return Err(SymbolSchemeApplicationError::ZeroStepsLimit);
+        }
+
+        let word = self.intern(word)?;
+        let mut execution = Execution::incremental(&self.rules, &word);
+
+        match engine::run::<_, (), _>(&self.rules, &mut execution, &limits, |_| None) {
+            RunOutcome::Completed { steps_done } => Ok(SymbolApplicationResult {
+                word: self.resolve(&execution.word.to_vec()),
+                steps_done,
+            }),
+            RunOutcome::Interrupted {
+                limit,
+                steps_done,
+                last_applied_formula,
+            } => Err(self.interrupt(limit, &execution, steps_done, last_applied_formula)),
+            RunOutcome::CycleDetected(()) => unreachable!("The cycles are not detected."),
+        }
+    }
+
+    /// Gets an iterator that applies the scheme once to the input word on each iterator's step,
+    /// until the algorithm is completed or one of the limits is exhausted.
+    ///
+    /// # Errors
+    /// Returns an [error](SymbolSchemeApplicationError) if the word contains unknown symbols.
+    pub fn get_application_iterator<L>(
+        &self,
+        word: &[S],
+        limits: L,
+    ) -> Result<SymbolApplicationIterator<'_, S>, SymbolSchemeApplicationError<S>>
+    where
+        L: Into<ExecutionLimits>,
+    {
+        let word = self.intern(word)?;
+
+        Ok(SymbolApplicationIterator {
+            progress: Progress::new(&self.rules, &word, limits.into()),
+            scheme: self,
+            interruption: None,
+        })
+    }
+
+    fn intern(&self, word: &[S]) -> Result<Vec<u32>, SymbolSchemeApplicationError<S>> {
+        word.iter()
+            .map(|symbol| {
+                self.ids
+                    .get(symbol)
+                    .copied()
+                    .ok_or_else(|| SymbolSchemeApplicationError::UnknownSymbol(symbol.clone()))
+            })
+            .collect()
+    }
+
+    fn resolve(&self, word: &[u32]) -> Vec<S> {
+        word.iter()
+            .map(|&id| self.symbols[id as usize].clone())
+            .collect()
+    }
+
+    fn interrupt(
+        &self,
+        limit: ExhaustedLimit,
+        execution: &Execution<u32>,
+        steps_done: u64,
+        last_applied_formula: Option<usize>,
+    ) -> SymbolSchemeApplicationError<S> {
+        let partial_result = PartialSymbolApplicationResult {
+            word: self.resolve(&execution.word.to_vec()),
+            steps_done,
+            last_applied_formula,
+        };
+
+        match limit {
+            ExhaustedLimit::Steps => SymbolSchemeApplicationError::HitTheStepsLimit(partial_result),
+            ExhaustedLimit::WordLength => {
+                SymbolSchemeApplicationError::HitTheWordLengthLimit(partial_result)
+            }
+            ExhaustedLimit::Deadline => {
+                SymbolSchemeApplicationError::HitTheDeadline(partial_result)
+            }
+            ExhaustedLimit::Cancellation => SymbolSchemeApplicationError::Cancelled(partial_result),
+        }
+    }
+}
+
+/// An iterator that yields the results of the application of a scheme over symbols, one step at a time.
+#[derive(Debug)]
+pub struct SymbolApplicationIterator<'a, S> {
+    progress: Progress<u32>,
+    scheme: &'a SymbolScheme<S>,
+    interruption: Option<SymbolSchemeApplicationError<S>>,
+}
+
+impl<'a, S> SymbolApplicationIterator<'a, S>
+where
+    S: Clone + Eq + Hash,
+{
+    /// The reason the iterator has stopped before the algorithm is completed, if any.
+    pub fn interruption(&self) -> Option<&SymbolSchemeApplicationError<S>> {
+        self.interruption.as_ref()
+    }
+}
+
+impl<'a, S> Iterator for SymbolApplicationIterator<'a, S>
+where
+    S: Clone + Eq + Hash,
+{
+    type Item = SymbolApplicationStep<'a, S>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let outcome = self.progress.advance(&self.scheme.rules);
+
+        if let (None, Some(limit)) = (&self.interruption, self.progress.exhausted_limit) {
+            self.interruption = Some(self.scheme.interrupt(
+                limit,
+                &self.progress.execution,
+                self.progress.total_steps_done(),
+                self.progress.last_applied_formula,
+            ));
+        }
+
+        let outcome = outcome?;
+
+        Some(SymbolApplicationStep {
+            word: self.scheme.resolve(&self.progress.execution.word.to_vec()),
+            applied_formula: outcome
+                .applied_formula
+                .map(|formula_index| (formula_index, &self.scheme.formulas[formula_index])),
+            step: self.progress.total_steps_done(),
+            is_final: outcome.is_final,
+        })
+    }
+}
+
+/// The data about a single application of a scheme over symbols.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SymbolApplicationStep<'a, S> {
+    word: Vec<S>,
+    applied_formula: Option<(usize, &'a SymbolFormula<S>)>,
+    step: u64,
+    is_final: bool,
+}
+
+impl<'a, S> SymbolApplicationStep<'a, S> {
+    /// The output word of the step.
+    pub fn word(&self) -> &[S] {
+        &self.word
+    }
+
+    /// The formula that has been used, if any.
+    pub fn applied_formula(&self) -> Option<&'a SymbolFormula<S>> {
+        self.applied_formula.map(|(_, formula)| formula)
+    }
+
+    /// The index of the formula that has been used, if any.
+    pub fn applied_formula_index(&self) -> Option<usize> {
+        self.applied_formula.map(|(formula_index, _)| formula_index)
+    }
+
+    /// The number of the step, counting from one.
+    pub fn step(&self) -> u64 {
+        self.step
+    }
+
+    /// Reports whether the algorithm has halted after the step.
+    pub fn is_final(&self) -> bool {
+        self.is_final
+    }
+}
+
+/// The result of the application of a scheme over symbols.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolApplicationResult<S> {
+    word: Vec<S>,
+    steps_done: u64,
+}
+
+impl<S> SymbolApplicationResult<S> {
+    /// The output word.
+    pub fn word(&self) -> &[S] {
+        &self.word
+    }
+
+    /// Takes the output word.
+    pub fn into_word(self) -> Vec<S> {
+        self.word
+    }
+
+    /// Reports the number of steps it took the algorithm to finish.
+    pub fn steps_done(&self) -> u64 {
+        self.steps_done
+    }
+}
+
+/// The state of an application of a scheme over symbols at the moment it has been interrupted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialSymbolApplicationResult<S> {
+    word: Vec<S>,
+    steps_done: u64,
+    last_applied_formula: Option<usize>,
+}
+
+impl<S> PartialSymbolApplicationResult<S> {
+    /// The last intermediate word.
+    pub fn word(&self) -> &[S] {
+        &self.word
+    }
+
+    /// Reports the number of steps done before the application has been interrupted.
+    pub fn steps_done(&self) -> u64 {
+        self.steps_done
+    }
+
+    /// The index of the formula that has produced the last intermediate word, if any.
+    pub fn last_applied_formula_index(&self) -> Option<usize> {
+        self.last_applied_formula
+    }
+}
+
+/// An error in the definition of a scheme over symbols.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SymbolSchemeDefinitionError<S> {
+    /// The alphabet is not provided or contains no symbols.
+    #[error("the alphabet of the scheme is empty")]
+    EmptyAlphabet,
+    /// The alphabet contains more symbols than can be interned.
+    #[error("the alphabet of the scheme contains too many symbols")]
+    TooManySymbols,
+    /// A formula contains a symbol that does not belong to the alphabet.
+    #[error("the formula {formula_index} contains the symbol {symbol:?} that does not belong to the alphabet")]
+    UnknownSymbolInFormula {
+        /// The index of the formula.
+        formula_index: usize,
+        /// The unknown symbol.
+        symbol: S,
+    },
+}
+
+/// An error of the application of a scheme over symbols.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SymbolSchemeApplicationError<S> {
+    /// The input word contains a symbol that does not belong to the alphabet.
+    #[error("the input word contains the symbol {0:?} that does not belong to the alphabet")]
+    UnknownSymbol(S),
+    /// The executor would stop before doing any steps.
+    #[error("the limit of steps should be positive")]
+    ZeroStepsLimit,
+    /// The executor stops after the limit of applications is reached.
+    #[error("the application is not completed after reaching step {}", .0.steps_done())]
+    HitTheStepsLimit(PartialSymbolApplicationResult<S>),
+    /// The executor stops after an intermediate word exceeds the length limit.
+    #[error(
+        "the application is stopped at step {} because the word exceeds the length limit",
+        .0.steps_done()
+    )]
+    HitTheWordLengthLimit(PartialSymbolApplicationResult<S>),
+    /// The executor stops after the deadline passes.
+    #[error(
+        "the application is not completed before the deadline, stopped at step {}",
+        .0.steps_done()
+    )]
+    HitTheDeadline(PartialSymbolApplicationResult<S>),
+    /// The executor stops after the cancellation is requested.
+    #[error("the application is cancelled at step {}", .0.steps_done())]
+    Cancelled(PartialSymbolApplicationResult<S>),
+}
@@ -0,0 +1,217 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::*;
+use crate::scheme::scheme_builder::AlgorithmSchemeBuilder;
+
+#[test]
+fn a_scheme_over_bytes_can_be_applied() {
+    let scheme = SymbolSchemeBuilder::new()
+        .with_alphabet(0_u8..=255)
+        .with_formula(SymbolFormula::new([0xff, 0x00], [0x00, 0xff]))
+        .build()
+        .unwrap();
+
+    let result = scheme.apply(&[0xff, 0xff, 0x00, 0x00], 100).unwrap();
+
+    assert_eq!([0x00, 0x00, 0xff, 0xff], result.word());
+    assert_eq!(5, result.steps_done());
+}
+
+#[test]
+fn a_scheme_over_characters_agrees_with_the_string_scheme() {
+    let definitions = ["нa→b", "bb→⋅c", "a→н"];
+
+    let string_scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(str::parse("abcн").unwrap())
+        .build_with_formula_definitions(definitions.into_iter())
+        .unwrap();
+
+    let symbol_scheme = SymbolSchemeBuilder::new()
+        .with_alphabet("abcн".chars())
+        .with_formulas([
+            SymbolFormula::new("нa".chars(), "b".chars()),
+            SymbolFormula::new_final("bb".chars(), "c".chars()),
+            SymbolFormula::new("a".chars(), "н".chars()),
+        ])
+        .build()
+        .unwrap();
+
+    for word in ["нaнa", "aнa", "ab", "cнaн"] {
+        let expected = string_scheme.apply(word, 100).unwrap();
+        let actual = symbol_scheme
+            .apply(&word.chars().collect::<Vec<_>>(), 100)
+            .unwrap();
+
+        assert_eq!(expected.word(), actual.word().iter().collect::<String>());
+        assert_eq!(expected.steps_done(), actual.steps_done());
+    }
+}
+
+#[test]
+fn a_scheme_over_wide_identifiers_can_be_applied() {
+    let scheme = SymbolSchemeBuilder::new()
+        .with_alphabet([1_u32, 1_000_000, u32::MAX])
+        .with_formula(SymbolFormula::new([u32::MAX], [1, 1_000_000]))
+        .build()
+        .unwrap();
+
+    let result = scheme.apply(&[u32::MAX, 1], 100).unwrap();
+
+    assert_eq!([1, 1_000_000, 1], result.word());
+}
+
+#[test]
+fn the_duplicates_in_the_alphabet_are_ignored() {
+    let scheme = SymbolSchemeBuilder::new()
+        .with_alphabet(['a', 'a', 'b'])
+        .with_formula(SymbolFormula::new(['a'], ['b']))
+        .build()
+        .unwrap();
+
+    assert!(scheme.contains(&'a') && scheme.contains(&'b') && !scheme.contains(&'c'));
+    assert_eq!(['b', 'b'], scheme.apply(&['a', 'b'], 10).unwrap().word());
+}
+
+#[test]
+fn a_scheme_cannot_be_built_without_an_alphabet() {
+    let error = SymbolSchemeBuilder::<char>::new()
+        .with_formula(SymbolFormula::new(['a'], ['b']))
+        .build()
+        .unwrap_err();
+
+    assert_eq!(SymbolSchemeDefinitionError::EmptyAlphabet, error);
+}
+
+#[test]
+fn a_scheme_cannot_be_built_if_a_formula_contains_an_unknown_symbol() {
+    let error = SymbolSchemeBuilder::new()
+        .with_alphabet(["a", "b"])
+        .with_formula(SymbolFormula::new(["a"], ["b"]))
+        .with_formula(SymbolFormula::new(["b"], ["c"]))
+        .build()
+        .unwrap_err();
+
+    assert_eq!(
+        SymbolSchemeDefinitionError::UnknownSymbolInFormula {
+            formula_index: 1,
+            symbol: "c"
+        },
+        error
+    );
+    assert_eq!(
+        "the formula 1 contains the symbol \"c\" that does not belong to the alphabet",
+        error.to_string()
+    );
+}
+
+#[test]
+fn a_scheme_cannot_be_applied_to_a_word_with_an_unknown_symbol() {
+    let scheme = SymbolSchemeBuilder::new()
+        .with_alphabet([1, 2])
+        .with_formula(SymbolFormula::new([1], [2]))
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        Err(SymbolSchemeApplicationError::UnknownSymbol(3)),
+        scheme.apply(&[1, 3], 10)
+    );
+}
+
+#[test]
+fn the_limits_are_applied_to_the_words_of_symbols() {
+    let scheme = SymbolSchemeBuilder::new()
+        .with_alphabet(["ab"])
+        .with_formula(SymbolFormula::new(["ab"], ["ab", "ab"]))
+        .build()
+        .unwrap();
+
+    let limits = ExecutionLimits::new().with_word_length_in_bytes(3);
+
+    assert_eq!(
+        Err(SymbolSchemeApplicationError::HitTheWordLengthLimit(
+            PartialSymbolApplicationResult {
+                word: vec!["ab"; 4],
+                steps_done: 3,
+                last_applied_formula: Some(0),
+            }
+        )),
+        scheme.apply(&["ab"], limits)
+    );
+    assert_eq!(
+        Err(SymbolSchemeApplicationError::ZeroStepsLimit),
+        scheme.apply(&["ab"], 0)
+    );
+}
+
+#[test]
+fn a_scheme_over_symbols_can_be_applied_step_by_step() {
+    let scheme = SymbolSchemeBuilder::new()
+        .with_alphabet([0, 1])
+        .with_formula(SymbolFormula::new([0], [1]))
+        .with_formula(SymbolFormula::new_final([1, 1], [0]))
+        .build()
+        .unwrap();
+
+    let steps: Vec<_> = scheme
+        .get_application_iterator(&[0, 0], ExecutionLimits::new())
+        .unwrap()
+        .map(|step| {
+            (
+                step.word().to_vec(),
+                step.applied_formula_index(),
+                step.step(),
+                step.is_final(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        vec![
+            (vec![1, 0], Some(0), 1, false),
+            (vec![1, 1], Some(0), 2, false),
+            (vec![0], Some(1), 3, true),
+        ],
+        steps
+    );
+}
+
+#[test]
+fn an_iterator_over_symbols_reports_the_exhausted_limit() {
+    let scheme = SymbolSchemeBuilder::new()
+        .with_alphabet([0, 1])
+        .with_formula(SymbolFormula::new([0], [1]))
+        .build()
+        .unwrap();
+
+    let mut iterator = scheme.get_application_iterator(&[0, 0, 0], 2).unwrap();
+
+    assert_eq!(2, iterator.by_ref().count());
+    assert_eq!(
+        Some(&SymbolSchemeApplicationError::HitTheStepsLimit(
+            PartialSymbolApplicationResult {
+                word: vec![1, 1, 0],
+                steps_done: 2,
+                last_applied_formula: Some(0),
+            }
+        )),
+        iterator.interruption()
+    );
+}
@@ -0,0 +1,251 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! [FormulaTemplate](FormulaTemplate), a formula definition that stands for several formulas.
+
+#[cfg(test)]
+mod tests;
+
+use std::ops::Range;
+
+use super::{
+    DefinitionCharacter, FormulaLexer, SchemeProperties, SubstitutionFormulaDefinitionError,
+};
+
+/// A formula definition with variables bound to the classes of characters, like `_[x:ab]→x_`.
+///
+/// A binding `[name:characters]` is written with the template brackets on the left side and stands for each of the characters in turn,
+/// every other occurrence of the name stands for the same character.
+/// The template is expanded into a formula definition per combination of the characters,
+/// the variables bound earlier change slower, so `[x:ab][y:01]→yx` stands for `a0→0a`, `a1→1a`, `b0→0b` and `b1→1b`.
+pub(super) struct FormulaTemplate<'a> {
+    definition: &'a str,
+    properties: &'a SchemeProperties,
+    parts: Vec<TemplatePart>,
+    variables: Vec<TemplateVariable>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum TemplatePart {
+    /// The bytes of the definition that are copied as is.
+    Text(Range<usize>),
+    /// The index of the variable that is replaced with its character.
+    Variable(usize),
+}
+
+#[derive(Debug)]
+struct TemplateVariable {
+    name: char,
+    characters: Vec<char>,
+}
+
+/// A piece of the definition before the occurrences of the variables are found.
+enum Segment<'c> {
+    Character(&'c DefinitionCharacter),
+    Binding(usize),
+}
+
+impl<'a> FormulaTemplate<'a> {
+    const SEPARATOR: char = ':';
+
+    /// Finds the bindings and the occurrences of the variables in the definition.
+    ///
+    /// # Panics
+    /// If the template brackets are not set in the properties.
+    pub(super) fn parse(
+        definition: &'a str,
+        properties: &'a SchemeProperties,
+    ) -> Result<Self, SubstitutionFormulaDefinitionError> {
+        let (open, close) = properties
+            .template_brackets
+            .expect("The templates are only parsed when the brackets are set.");
+
+        let characters = FormulaLexer {
+            formula_definition: definition,
+            properties,
+        }
+        .tokenize()?;
+
+        let invalid = |start: usize, index: usize| {
+            let end = characters
+                .get(index)
+                .map_or(definition.len(), |character| character.position.end);
+
+            SubstitutionFormulaDefinitionError::InvalidTemplateBinding(
+                definition.to_owned(),
+                definition[start..end].to_owned(),
+            )
+        };
+
+        let mut segments = Vec::new();
+        let mut variables: Vec<TemplateVariable> = Vec::new();
+        let mut is_right_side = false;
+        let mut index = 0;
+
+        while index < characters.len() {
+            let character = &characters[index];
+
+            if character.is(close) {
+                return Err(invalid(character.position.start, index));
+            }
+
+            if !character.is(open) {
+                is_right_side |=
+                    DefinitionCharacter::starts_with(&characters[index..], &properties.delimiter);
+                segments.push(Segment::Character(character));
+                index += 1;
+                continue;
+            }
+
+            let start = character.position.start;
+
+            let name = match characters.get(index + 1) {
+                Some(name) if !name.is_escaped && !properties.is_reserved(name.value) => name.value,
+                _ => return Err(invalid(start, index + 1)),
+            };
+
+            if !characters
+                .get(index + 2)
+                .is_some_and(|separator| separator.is(Self::SEPARATOR))
+            {
+                return Err(invalid(start, index + 2));
+            }
+
+            index += 3;
+
+            let mut class = Vec::new();
+
+            loop {
+                match characters.get(index) {
+                    Some(character) if character.is(close) => break,
+                    Some(character) if !character.is(open) => class.push(character.value),
+                    _ => return Err(invalid(start, index)),
+                }
+                index += 1;
+            }
+
+            // A class on the right side would only produce the formulas that are shadowed by the first one.
+            if class.is_empty() || is_right_side {
+                return Err(invalid(start, index));
+            }
+
+            if variables.iter().any(|variable| variable.name == name) {
+                return Err(
+                    SubstitutionFormulaDefinitionError::DuplicateTemplateVariable(
+                        definition.to_owned(),
+                        name,
+                    ),
+                );
+            }
+
+            segments.push(Segment::Binding(variables.len()));
+            variables.push(TemplateVariable {
+                name,
+                characters: class,
+            });
+            index += 1;
+        }
+
+        let variable_named = |character: &DefinitionCharacter| {
+            variables
+                .iter()
+                .position(|variable| character.is(variable.name))
+        };
+
+        let parts = segments
+            .into_iter()
+            .map(|segment| match segment {
+                Segment::Binding(variable) => TemplatePart::Variable(variable),
+                Segment::Character(character) => match variable_named(character) {
+                    Some(variable) => TemplatePart::Variable(variable),
+                    None => TemplatePart::Text(character.position.clone()),
+                },
+            })
+            .collect();
+
+        Ok(Self {
+            definition,
+            properties,
+            parts,
+            variables,
+        })
+    }
+
+    /// The characters the variables of the template stand for.
+    pub(super) fn characters(&self) -> impl Iterator<Item = char> + '_ {
+        self.variables
+            .iter()
+            .flat_map(|variable| variable.characters.iter().copied())
+    }
+
+    /// Writes the formula definitions the template stands for, in the order of the characters of the classes.
+    ///
+    /// # Panics
+    /// If a character of a class cannot be written in a formula definition,
+    /// which is never the case for the characters of the alphabet.
+    pub(super) fn expand(&self) -> Vec<String> {
+        if self.variables.is_empty() {
+            return vec![self.definition.to_owned()];
+        }
+
+        let values: Vec<Vec<String>> = self
+            .variables
+            .iter()
+            .map(|variable| {
+                variable
+                    .characters
+                    .iter()
+                    .map(|&character| {
+                        self.properties
+                            .escape(character.encode_utf8(&mut [0; 4]))
+                            .expect("The characters of the alphabet can be written in a formula.")
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut choices = vec![0; values.len()];
+        let mut definitions = Vec::new();
+
+        loop {
+            let definition = self
+                .parts
+                .iter()
+                .map(|part| match part {
+                    TemplatePart::Text(range) => &self.definition[range.clone()],
+                    TemplatePart::Variable(variable) => &values[*variable][choices[*variable]],
+                })
+                .collect();
+
+            definitions.push(definition);
+
+            // The choices are advanced like the digits of a number, the last variable changes the fastest.
+            let Some(variable) = (0..choices.len())
+                .rfind(|&variable| choices[variable] + 1 < values[variable].len())
+            else {
+                break;
+            };
+
+            choices[variable] += 1;
+            choices[variable + 1..].fill(0);
+        }
+
+        definitions
+    }
+}
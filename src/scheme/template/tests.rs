@@ -0,0 +1,128 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::scheme::scheme_builder::AlgorithmSchemeBuilder;
+
+use super::*;
+
+fn create_properties() -> SchemeProperties {
+    AlgorithmSchemeBuilder::new()
+        .with_template_brackets('[', ']')
+        .finalize_properties()
+}
+
+fn expand(definition: &str) -> Result<Vec<String>, SubstitutionFormulaDefinitionError> {
+    let properties = create_properties();
+
+    FormulaTemplate::parse(definition, &properties).map(|template| template.expand())
+}
+
+#[test]
+fn the_definition_without_bindings_stands_for_itself() {
+    assert_eq!(vec!["ab→⋅ba"], expand("ab→⋅ba").unwrap());
+}
+
+#[test]
+fn the_template_is_expanded_in_the_order_of_the_class() {
+    assert_eq!(
+        vec!["_c→c_", "_a→a_", "_B→B_"],
+        expand("_[x:caB]→x_").unwrap()
+    );
+}
+
+#[test]
+fn the_variables_bound_earlier_change_slower() {
+    assert_eq!(
+        vec!["a0→0a", "a1→1a", "b0→0b", "b1→1b"],
+        expand("[x:ab][y:01]→yx").unwrap()
+    );
+}
+
+#[test]
+fn the_variable_can_occur_before_its_binding() {
+    assert_eq!(vec!["aa→a", "bb→b"], expand("x[x:ab]→x").unwrap());
+}
+
+#[test]
+fn the_class_characters_are_not_replaced() {
+    let properties = create_properties();
+    let template = FormulaTemplate::parse("[x:xy]→x", &properties).unwrap();
+
+    assert_eq!(vec!['x', 'y'], template.characters().collect::<Vec<_>>());
+    assert_eq!(vec!["x→x", "y→y"], template.expand());
+}
+
+#[test]
+fn the_escaped_occurrences_of_the_name_are_not_replaced() {
+    let properties = AlgorithmSchemeBuilder::new()
+        .with_template_brackets('[', ']')
+        .with_escape_character('\\')
+        .finalize_properties();
+
+    let template = FormulaTemplate::parse(r"[x:a\]]→x\u{78}\[", &properties).unwrap();
+
+    assert_eq!(vec![r"a→a\u{78}\[", r"\]→\]\u{78}\["], template.expand());
+}
+
+#[test]
+fn the_bindings_should_be_well_formed() {
+    for (definition, binding) in [
+        ("a[x:ab→x", "[x:ab→x"),
+        ("[xab]→x", "[xa"),
+        ("[x:]→x", "[x:]"),
+        ("[→:a]→", "[→"),
+        ("[x:a[y:b]]→x", "[x:a["),
+        ("a]→b", "]"),
+        ("a→b[", "["),
+    ] {
+        assert_eq!(
+            Err(SubstitutionFormulaDefinitionError::InvalidTemplateBinding(
+                definition.to_owned(),
+                binding.to_owned()
+            )),
+            expand(definition),
+        );
+    }
+}
+
+#[test]
+fn the_bindings_cannot_follow_the_delimiter() {
+    for (definition, binding) in [("[x:ab]→[y:ab]", "[y:ab]"), ("a→⋅[x:ab]x", "[x:ab]")] {
+        assert_eq!(
+            Err(SubstitutionFormulaDefinitionError::InvalidTemplateBinding(
+                definition.to_owned(),
+                binding.to_owned()
+            )),
+            expand(definition),
+        );
+    }
+}
+
+#[test]
+fn the_variable_cannot_be_bound_twice() {
+    assert_eq!(
+        Err(
+            SubstitutionFormulaDefinitionError::DuplicateTemplateVariable(
+                "[x:ab][x:cd]→x".to_owned(),
+                'x'
+            )
+        ),
+        expand("[x:ab][x:cd]→x"),
+    );
+}
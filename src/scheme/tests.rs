@@ -17,12 +17,26 @@
 *    along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use std::str::FromStr;
+use std::{collections::HashSet, str::FromStr};
 
 use crate::prelude::{AlgorithmSchemeBuilder, Alphabet};
 
 use super::*;
 
+fn ascii_substitution(
+    formula_index: usize,
+    matched: Range<usize>,
+    inserted: Range<usize>,
+) -> Option<AppliedSubstitution> {
+    Some(AppliedSubstitution {
+        formula_index,
+        matched_bytes: matched.clone(),
+        matched_characters: matched,
+        inserted_bytes: inserted.clone(),
+        inserted_characters: inserted,
+    })
+}
+
 #[test]
 fn a_scheme_can_be_applied_if_the_input_string_contains_only_characters_that_belong_to_the_alphabet(
 ) {
@@ -169,7 +183,12 @@ fn a_scheme_cannot_be_fully_applied_if_the_algorithm_does_not_complete_in_a_set_
 
     let error = scheme.apply("abc", 1).unwrap_err();
 
-    let extpected_error = AlgorithmSchemeFullApplicationError::HitTheStepsLimit(1);
+    let extpected_error =
+        AlgorithmSchemeFullApplicationError::HitTheStepsLimit(PartialApplicationResult {
+            word: "abc".to_owned(),
+            steps_done: 1,
+            last_applied_formula_definition: Some("b→b".to_owned()),
+        });
 
     assert_eq!(extpected_error, error);
 }
@@ -220,6 +239,10 @@ fn a_scheme_application_may_yield_the_final_result() {
     let expected = SingleApplicationResult::Final(SingleApplicationData {
         word: "dbc".to_owned(),
         applied_formula_definition: Some("a→⋅d"),
+        substitution: ascii_substitution(0, 0..1, 0..1),
+        choice: None,
+        step: 1,
+        is_final: true,
     });
 
     assert_eq!(expected, result);
@@ -239,6 +262,10 @@ fn a_scheme_application_yields_the_final_with_no_formula_if_no_substitution_form
     let expected = SingleApplicationResult::Final(SingleApplicationData {
         word: "bbb".to_owned(),
         applied_formula_definition: None,
+        substitution: None,
+        choice: None,
+        step: 1,
+        is_final: true,
     });
 
     assert_eq!(expected, result);
@@ -258,6 +285,10 @@ fn a_scheme_application_may_yield_the_intermediate_result() {
     let expected = SingleApplicationResult::Intermediate(SingleApplicationData {
         word: "dbc".to_owned(),
         applied_formula_definition: Some("a→d"),
+        substitution: ascii_substitution(0, 0..1, 0..1),
+        choice: None,
+        step: 1,
+        is_final: false,
     });
 
     assert_eq!(expected, result);
@@ -370,7 +401,11 @@ fn a_scheme_appication_may_be_viewed_through_iterator_step_by_step() {
     assert_eq!(
         Some(SingleApplicationData {
             word: "bbc".to_owned(),
-            applied_formula_definition: Some("a→b")
+            applied_formula_definition: Some("a→b"),
+            substitution: ascii_substitution(0, 0..1, 0..1),
+            choice: None,
+            step: 1,
+            is_final: false,
         }),
         iterator.next()
     );
@@ -378,7 +413,11 @@ fn a_scheme_appication_may_be_viewed_through_iterator_step_by_step() {
     assert_eq!(
         Some(SingleApplicationData {
             word: "cbc".to_owned(),
-            applied_formula_definition: Some("b→c")
+            applied_formula_definition: Some("b→c"),
+            substitution: ascii_substitution(1, 0..1, 0..1),
+            choice: None,
+            step: 2,
+            is_final: false,
         }),
         iterator.next()
     );
@@ -386,7 +425,11 @@ fn a_scheme_appication_may_be_viewed_through_iterator_step_by_step() {
     assert_eq!(
         Some(SingleApplicationData {
             word: "ccc".to_owned(),
-            applied_formula_definition: Some("b→c")
+            applied_formula_definition: Some("b→c"),
+            substitution: ascii_substitution(1, 1..2, 1..2),
+            choice: None,
+            step: 3,
+            is_final: false,
         }),
         iterator.next()
     );
@@ -394,10 +437,1434 @@ fn a_scheme_appication_may_be_viewed_through_iterator_step_by_step() {
     assert_eq!(
         Some(SingleApplicationData {
             word: "d".to_owned(),
-            applied_formula_definition: Some("ccc→⋅d")
+            applied_formula_definition: Some("ccc→⋅d"),
+            substitution: ascii_substitution(2, 0..3, 0..1),
+            choice: None,
+            step: 4,
+            is_final: true,
         }),
         iterator.next()
     );
 
     assert_eq!(None, iterator.next());
 }
+
+#[test]
+fn the_first_applicable_formula_is_applied_even_if_a_later_one_occurs_earlier_in_the_word() {
+    let alphabet = Alphabet::from_str("abc").unwrap();
+
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(alphabet)
+        .build_with_formula_definitions(["cb→a", "ab→c", "b→⋅"].into_iter())
+        .unwrap();
+
+    let result = scheme.apply_once("abcb").unwrap();
+
+    let expected = SingleApplicationResult::Intermediate(SingleApplicationData {
+        word: "aba".to_owned(),
+        applied_formula_definition: Some("cb→a"),
+        substitution: ascii_substitution(0, 2..4, 2..3),
+        choice: None,
+        step: 1,
+        is_final: false,
+    });
+
+    assert_eq!(expected, result);
+}
+
+#[test]
+fn the_incremental_execution_agrees_with_the_full_rescan_on_every_step() {
+    let alphabet = Alphabet::from_str("ab").unwrap().extend('_').unwrap();
+
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(alphabet)
+        .build_with_formula_definitions(
+            ["_ab→b_", "_a→a_", "_b→bb_", "_→⋅", "b→_b", "a→_a"].into_iter(),
+        )
+        .unwrap();
+
+    let word = scheme.encoding.encode(&"ab".repeat(50)).into_owned();
+    let mut incremental = Execution::incremental(&scheme.rules, &word);
+    let mut rescanning = Execution::new(&scheme.rules, &word);
+
+    loop {
+        let expected = rescanning.step(&scheme.rules);
+        let actual = incremental.step(&scheme.rules);
+
+        assert_eq!(expected.applied_formula, actual.applied_formula);
+        assert_eq!(rescanning.word, incremental.word);
+
+        if expected.is_final {
+            assert!(actual.is_final);
+            break;
+        }
+    }
+}
+
+#[test]
+fn a_scheme_with_cycle_detection_completes_if_the_algorithm_completes() {
+    let alphabet = Alphabet::from_str("abc").unwrap();
+
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(alphabet)
+        .build_with_formula_definitions(["a→b", "b→c"].into_iter())
+        .unwrap();
+
+    let result = scheme.apply_with_cycle_detection("aab", 1_000).unwrap();
+
+    let expected = FullApplicationResult {
+        word: "ccc".to_owned(),
+        steps_done: 6,
+    };
+
+    assert_eq!(expected, result);
+}
+
+#[test]
+fn a_cycle_is_reported_if_the_word_repeats_itself() {
+    let alphabet = Alphabet::from_str("abc").unwrap();
+
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(alphabet)
+        .build_with_formula_definitions(["c→a", "ab→ba", "ba→cb"].into_iter())
+        .unwrap();
+
+    let error = scheme
+        .apply_with_cycle_detection("abbb", 1_000)
+        .unwrap_err();
+
+    let expected_error = AlgorithmSchemeFullApplicationError::CycleDetected(ApplicationCycle {
+        start_step: 2,
+        period: 3,
+        words: vec!["bbab".to_owned(), "bbba".to_owned(), "bbcb".to_owned()],
+    });
+
+    assert_eq!(expected_error, error);
+}
+
+#[test]
+fn a_cycle_of_a_single_step_is_reported() {
+    let alphabet = Alphabet::from_str("abc").unwrap();
+
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(alphabet)
+        .build_with_formula_definitions(["a→b", "b→b"].into_iter())
+        .unwrap();
+
+    let error = scheme.apply_with_cycle_detection("aa", 1_000).unwrap_err();
+
+    let expected_error = AlgorithmSchemeFullApplicationError::CycleDetected(ApplicationCycle {
+        start_step: 2,
+        period: 1,
+        words: vec!["bb".to_owned()],
+    });
+
+    assert_eq!(expected_error, error);
+}
+
+#[test]
+fn an_error_is_reported_if_the_word_repeats_itself() {
+    let alphabet = Alphabet::from_str("abc").unwrap();
+
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(alphabet)
+        .build_with_formula_definitions(["a→b", "b→a"].into_iter())
+        .unwrap();
+
+    let error = scheme.apply_with_cycle_detection("ba", 1_000).unwrap_err();
+
+    assert_eq!(
+        "the application has entered a cycle of 2 steps that starts at step 1",
+        format!("{error}")
+    );
+}
+
+#[test]
+fn a_scheme_with_cycle_detection_still_respects_the_steps_limit() {
+    let alphabet = Alphabet::from_str("abc").unwrap();
+
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(alphabet)
+        .build_with_formula_definitions(["a→aa"].into_iter())
+        .unwrap();
+
+    let error = scheme.apply_with_cycle_detection("a", 100).unwrap_err();
+
+    assert!(matches!(
+        error,
+        AlgorithmSchemeFullApplicationError::HitTheStepsLimit(PartialApplicationResult {
+            steps_done: 100,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn the_last_intermediate_word_is_reported_if_the_steps_limit_is_hit() {
+    let alphabet = Alphabet::from_str("abc").unwrap().extend('d').unwrap();
+
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(alphabet)
+        .build_with_formula_definitions(["a→d", "d→c", "c→⋅b"].into_iter())
+        .unwrap();
+
+    let error = scheme.apply("aa", 3).unwrap_err();
+
+    let extpected_error =
+        AlgorithmSchemeFullApplicationError::HitTheStepsLimit(PartialApplicationResult {
+            word: "cd".to_owned(),
+            steps_done: 3,
+            last_applied_formula_definition: Some("d→c".to_owned()),
+        });
+
+    assert_eq!(extpected_error, error);
+}
+
+#[test]
+fn an_interrupted_application_can_be_resumed() {
+    let alphabet = Alphabet::from_str("abc").unwrap().extend('d').unwrap();
+
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(alphabet)
+        .build_with_formula_definitions(["a→d", "d→c", "c→⋅b"].into_iter())
+        .unwrap();
+
+    let partial_result = match scheme.apply("aa", 3).unwrap_err() {
+        AlgorithmSchemeFullApplicationError::HitTheStepsLimit(partial_result) => partial_result,
+        error => panic!("unexpected error: {error}"),
+    };
+
+    let result = scheme.resume(partial_result, 10).unwrap();
+
+    let expected = FullApplicationResult {
+        word: "bc".to_owned(),
+        steps_done: 5,
+    };
+
+    assert_eq!(expected, result);
+}
+
+#[test]
+fn an_interrupted_application_cannot_be_resumed_with_unknown_characters() {
+    let alphabet = Alphabet::from_str("abc").unwrap().extend('d').unwrap();
+
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(alphabet)
+        .build_with_formula_definitions(["a→d"].into_iter())
+        .unwrap();
+
+    let partial_result = PartialApplicationResult {
+        word: "aex".to_owned(),
+        steps_done: 1,
+        last_applied_formula_definition: None,
+    };
+
+    let error = scheme.resume(partial_result, 10).unwrap_err();
+
+    let extpected_error = AlgorithmSchemeFullApplicationError::InputValidationError {
+        source: AlgorithmSchemeInputValidationError::UnknownCharactersEncountered("ex".to_owned()),
+    };
+
+    assert_eq!(extpected_error, error);
+}
+
+#[test]
+fn the_last_intermediate_word_is_reported_if_the_word_length_limit_is_hit() {
+    let alphabet = Alphabet::from_str("a").unwrap();
+
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(alphabet)
+        .build_with_formula_definitions(["a→aa"].into_iter())
+        .unwrap();
+
+    let limits = ExecutionLimits::new().with_word_length_in_characters(3);
+
+    let error = scheme.apply("a", limits).unwrap_err();
+
+    let extpected_error =
+        AlgorithmSchemeFullApplicationError::HitTheWordLengthLimit(PartialApplicationResult {
+            word: "aaaa".to_owned(),
+            steps_done: 3,
+            last_applied_formula_definition: Some("a→aa".to_owned()),
+        });
+
+    assert_eq!(extpected_error, error);
+}
+
+#[test]
+fn an_input_string_longer_than_the_limit_is_not_processed() {
+    let alphabet = Alphabet::from_str("ab").unwrap();
+
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(alphabet)
+        .build_with_formula_definitions(["a→b"].into_iter())
+        .unwrap();
+
+    let limits = ExecutionLimits::new().with_word_length_in_bytes(2);
+
+    let error = scheme.apply("aaa", limits).unwrap_err();
+
+    let extpected_error =
+        AlgorithmSchemeFullApplicationError::HitTheWordLengthLimit(PartialApplicationResult {
+            word: "aaa".to_owned(),
+            steps_done: 0,
+            last_applied_formula_definition: None,
+        });
+
+    assert_eq!(extpected_error, error);
+}
+
+#[test]
+fn the_final_word_is_reported_even_if_it_exceeds_the_word_length_limit() {
+    let alphabet = Alphabet::from_str("a").unwrap();
+
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(alphabet)
+        .build_with_formula_definitions(["a→⋅aa"].into_iter())
+        .unwrap();
+
+    let limits = ExecutionLimits::new().with_word_length_in_characters(1);
+
+    let result = scheme.apply("a", limits).unwrap();
+
+    let expected = FullApplicationResult {
+        word: "aa".to_owned(),
+        steps_done: 1,
+    };
+
+    assert_eq!(expected, result);
+}
+
+#[test]
+fn an_error_is_reported_if_the_deadline_has_passed() {
+    let alphabet = Alphabet::from_str("a").unwrap();
+
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(alphabet)
+        .build_with_formula_definitions(["a→a"].into_iter())
+        .unwrap();
+
+    let limits = ExecutionLimits::new().with_deadline(std::time::Instant::now());
+
+    let error = scheme.apply("a", limits).unwrap_err();
+
+    let extpected_error =
+        AlgorithmSchemeFullApplicationError::HitTheDeadline(PartialApplicationResult {
+            word: "a".to_owned(),
+            steps_done: 0,
+            last_applied_formula_definition: None,
+        });
+
+    assert_eq!(extpected_error, error);
+}
+
+#[test]
+fn an_application_can_be_cancelled_from_another_thread() {
+    let alphabet = Alphabet::from_str("a").unwrap();
+
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(alphabet)
+        .build_with_formula_definitions(["a→a"].into_iter())
+        .unwrap();
+
+    let token = CancellationToken::new();
+    let limits = ExecutionLimits::new().with_cancellation_token(token.clone());
+
+    let canceller = std::thread::spawn(move || token.cancel());
+
+    let error = scheme.apply("a", limits).unwrap_err();
+
+    canceller.join().unwrap();
+
+    assert!(matches!(
+        error,
+        AlgorithmSchemeFullApplicationError::Cancelled(PartialApplicationResult { ref word, .. })
+            if word == "a"
+    ));
+}
+
+#[test]
+fn an_iterator_stops_and_reports_the_exhausted_limit() {
+    let alphabet = Alphabet::from_str("a").unwrap();
+
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(alphabet)
+        .build_with_formula_definitions(["a→aa"].into_iter())
+        .unwrap();
+
+    let limits = ExecutionLimits::new().with_word_length_in_characters(2);
+
+    let mut iterator = scheme
+        .get_application_iterator_with_limits("a", limits)
+        .unwrap();
+
+    assert_eq!(2, iterator.by_ref().count());
+
+    let extpected_error =
+        AlgorithmSchemeFullApplicationError::HitTheWordLengthLimit(PartialApplicationResult {
+            word: "aaa".to_owned(),
+            steps_done: 2,
+            last_applied_formula_definition: Some("a→aa".to_owned()),
+        });
+
+    assert_eq!(Some(&extpected_error), iterator.interruption());
+}
+
+#[test]
+fn a_scheme_and_its_owned_iterator_can_be_shared_between_threads() {
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+    let scheme = Arc::new(
+        AlgorithmSchemeBuilder::new()
+            .with_alphabet(Alphabet::from_str("ab").unwrap())
+            .build_with_formula_definitions(["a→b"].into_iter())
+            .unwrap(),
+    );
+
+    let iterator = scheme
+        .get_owned_application_iterator("aa", ExecutionLimits::new())
+        .unwrap();
+
+    assert_send_sync(&scheme);
+    assert_send_sync(&iterator);
+}
+
+#[test]
+fn an_owned_iterator_yields_the_same_steps_as_the_borrowing_one() {
+    let scheme = Arc::new(
+        AlgorithmSchemeBuilder::new()
+            .with_alphabet(Alphabet::from_str("abc").unwrap())
+            .build_with_formula_definitions(["a→b", "b→c", "cc→⋅a"].into_iter())
+            .unwrap(),
+    );
+
+    let expected: Vec<OwnedSingleApplicationData> = scheme
+        .get_application_iterator("aab")
+        .unwrap()
+        .map(Into::into)
+        .collect();
+
+    let iterator = scheme
+        .get_owned_application_iterator("aab", ExecutionLimits::new())
+        .unwrap();
+
+    let steps: Vec<_> = std::thread::spawn(move || iterator.collect())
+        .join()
+        .unwrap();
+
+    assert_eq!(expected, steps);
+    assert_eq!(
+        Some("cc→⋅a"),
+        steps.last().unwrap().applied_formula_definition()
+    );
+}
+
+#[test]
+fn an_owned_iterator_outlives_the_original_reference_to_the_scheme() {
+    let scheme = Arc::new(
+        AlgorithmSchemeBuilder::new()
+            .with_alphabet(Alphabet::from_str("ab").unwrap())
+            .build_with_formula_definitions(["a→b"].into_iter())
+            .unwrap(),
+    );
+
+    let mut iterator = scheme.get_owned_application_iterator("aaa", 2).unwrap();
+
+    drop(scheme);
+
+    assert_eq!("baa", iterator.next().unwrap().word());
+    assert_eq!("bba", iterator.next().unwrap().word());
+    assert_eq!(None, iterator.next());
+    assert!(matches!(
+        iterator.interruption(),
+        Some(AlgorithmSchemeFullApplicationError::HitTheStepsLimit(_))
+    ));
+}
+
+#[test]
+fn an_owned_iterator_cannot_be_created_if_the_input_string_contains_unknown_characters() {
+    let scheme = Arc::new(
+        AlgorithmSchemeBuilder::new()
+            .with_alphabet(Alphabet::from_str("ab").unwrap())
+            .build_with_formula_definitions(["a→b"].into_iter())
+            .unwrap(),
+    );
+
+    let error = scheme
+        .get_owned_application_iterator("abx", ExecutionLimits::new())
+        .unwrap_err();
+
+    assert_eq!(
+        AlgorithmSchemeInputValidationError::UnknownCharactersEncountered("x".to_owned()),
+        error
+    );
+}
+
+#[test]
+fn a_batch_application_returns_the_results_in_the_order_of_the_inputs() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("ab").unwrap())
+        .build_with_formula_definitions(["a→b"].into_iter())
+        .unwrap();
+
+    let words: Vec<_> = (0..500).map(|length| "a".repeat(length % 17)).collect();
+
+    let results = scheme.apply_batch(&words, 100);
+
+    assert_eq!(words.len(), results.len());
+
+    for (word, result) in words.iter().zip(results) {
+        assert_eq!(scheme.apply(word, 100), result);
+    }
+}
+
+#[test]
+fn a_batch_application_reports_errors_for_each_input_separately() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("ab").unwrap())
+        .build_with_formula_definitions(["a→b"].into_iter())
+        .unwrap();
+
+    let limits = ExecutionLimits::new().with_steps(2);
+
+    let results = scheme.apply_batch(&["a", "aaa", "x", ""], limits);
+
+    let expected = vec![
+        Ok(FullApplicationResult {
+            word: "b".to_owned(),
+            steps_done: 2,
+        }),
+        Err(AlgorithmSchemeFullApplicationError::HitTheStepsLimit(
+            PartialApplicationResult {
+                word: "bba".to_owned(),
+                steps_done: 2,
+                last_applied_formula_definition: Some("a→b".to_owned()),
+            },
+        )),
+        Err(AlgorithmSchemeFullApplicationError::InputValidationError {
+            source: AlgorithmSchemeInputValidationError::UnknownCharactersEncountered(
+                "x".to_owned(),
+            ),
+        }),
+        Ok(FullApplicationResult {
+            word: "".to_owned(),
+            steps_done: 1,
+        }),
+    ];
+
+    assert_eq!(expected, results);
+}
+
+#[test]
+fn a_batch_application_of_no_inputs_yields_no_results() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("ab").unwrap())
+        .build_with_formula_definitions(["a→b"].into_iter())
+        .unwrap();
+
+    let words: [&str; 0] = [];
+
+    assert!(scheme.apply_batch(&words, 10).is_empty());
+}
+
+#[test]
+fn an_iterator_restored_from_a_snapshot_continues_the_application() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("abc").unwrap())
+        .build_with_formula_definitions(["a→b", "b→c", "cc→⋅a"].into_iter())
+        .unwrap();
+
+    let expected: Vec<_> = scheme.get_application_iterator("aab").unwrap().collect();
+
+    let mut iterator = scheme.get_application_iterator("aab").unwrap();
+    let mut steps: Vec<_> = iterator.by_ref().take(3).collect();
+
+    let snapshot: ApplicationSnapshot = iterator.snapshot().to_string().parse().unwrap();
+
+    assert_eq!(3, snapshot.steps_done());
+
+    steps.extend(
+        scheme
+            .restore_application_iterator(&snapshot, ExecutionLimits::new())
+            .unwrap(),
+    );
+
+    assert_eq!(expected, steps);
+}
+
+#[test]
+fn an_interruption_after_the_restoration_reports_the_total_number_of_steps() {
+    let scheme = Arc::new(
+        AlgorithmSchemeBuilder::new()
+            .with_alphabet(Alphabet::from_str("ab").unwrap())
+            .build_with_formula_definitions(["a→b"].into_iter())
+            .unwrap(),
+    );
+
+    let mut iterator = scheme.get_owned_application_iterator("aaaa", 1).unwrap();
+
+    assert_eq!(1, iterator.by_ref().count());
+
+    let mut iterator = scheme
+        .restore_owned_application_iterator(&iterator.snapshot(), 2)
+        .unwrap();
+
+    assert_eq!(2, iterator.by_ref().count());
+
+    let extpected_error =
+        AlgorithmSchemeFullApplicationError::HitTheStepsLimit(PartialApplicationResult {
+            word: "bbba".to_owned(),
+            steps_done: 3,
+            last_applied_formula_definition: Some("a→b".to_owned()),
+        });
+
+    assert_eq!(Some(&extpected_error), iterator.interruption());
+}
+
+#[test]
+fn a_completed_application_stays_completed_after_the_restoration() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("ab").unwrap())
+        .build_with_formula_definitions(["a→⋅b"].into_iter())
+        .unwrap();
+
+    let mut iterator = scheme.get_application_iterator("a").unwrap();
+
+    assert_eq!(1, iterator.by_ref().count());
+
+    let snapshot = iterator.snapshot();
+
+    assert!(snapshot.is_completed());
+
+    let mut iterator = scheme
+        .restore_application_iterator(&snapshot, ExecutionLimits::new())
+        .unwrap();
+
+    assert_eq!(None, iterator.next());
+}
+
+#[test]
+fn a_snapshot_cannot_be_restored_against_a_different_scheme() {
+    let build = |formulas: [&str; 1]| {
+        AlgorithmSchemeBuilder::new()
+            .with_alphabet(Alphabet::from_str("ab").unwrap())
+            .build_with_formula_definitions(formulas.into_iter())
+            .unwrap()
+    };
+
+    let scheme = build(["a→b"]);
+    let other_scheme = build(["a→⋅b"]);
+
+    assert_eq!(scheme.fingerprint(), build(["a→b"]).fingerprint());
+    assert_ne!(scheme.fingerprint(), other_scheme.fingerprint());
+
+    let snapshot = scheme.get_application_iterator("aa").unwrap().snapshot();
+
+    let error = other_scheme
+        .restore_application_iterator(&snapshot, ExecutionLimits::new())
+        .unwrap_err();
+
+    assert_eq!(
+        ApplicationSnapshotRestorationError::SchemeMismatch {
+            expected: other_scheme.fingerprint(),
+            found: scheme.fingerprint(),
+        },
+        error
+    );
+}
+
+#[test]
+fn the_fingerprint_depends_on_the_alphabet() {
+    let build = |alphabet: &str| {
+        AlgorithmSchemeBuilder::new()
+            .with_alphabet(Alphabet::from_str(alphabet).unwrap())
+            .build_with_formula_definitions(["a→b"].into_iter())
+            .unwrap()
+    };
+
+    assert_eq!(build("ab").fingerprint(), build("ba").fingerprint());
+    assert_ne!(build("ab").fingerprint(), build("abc").fingerprint());
+}
+
+#[test]
+fn the_properties_written_with_the_same_characters_have_different_fingerprints() {
+    let build = |builder: AlgorithmSchemeBuilder| {
+        builder
+            .with_alphabet(Alphabet::from_str("ab").unwrap())
+            .build_with_formula_definitions(["a→b"].into_iter())
+            .unwrap()
+            .fingerprint()
+    };
+
+    assert_ne!(
+        build(AlgorithmSchemeBuilder::new().with_escape_character('.')),
+        build(AlgorithmSchemeBuilder::new().with_variables())
+    );
+    assert_ne!(
+        build(AlgorithmSchemeBuilder::new().with_template_brackets('^', '$')),
+        build(AlgorithmSchemeBuilder::new().with_anchors())
+    );
+    assert_ne!(
+        build(AlgorithmSchemeBuilder::new().with_match_strategy(MatchStrategy::Rightmost)),
+        build(AlgorithmSchemeBuilder::new().with_priority_groups(GroupTieBreak::LongestMatch))
+    );
+}
+
+#[test]
+fn a_reversible_iterator_recreates_every_previous_word() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("abc").unwrap())
+        .build_with_formula_definitions(["ab→bca", "c→", "ba→⋅a"].into_iter())
+        .unwrap();
+
+    let mut words = vec![("aabb".to_owned(), None)];
+    words.extend(
+        scheme
+            .get_application_iterator("aabb")
+            .unwrap()
+            .map(|data| (data.word().to_owned(), data.applied_formula_definition())),
+    );
+
+    let mut iterator = scheme
+        .get_reversible_application_iterator("aabb", ExecutionLimits::new())
+        .unwrap();
+
+    assert_eq!(words.len() - 1, iterator.by_ref().count());
+
+    for step in (0..words.len() - 1).rev() {
+        let data = iterator.step_back().unwrap();
+
+        assert_eq!(
+            words[step],
+            (data.word().to_owned(), data.applied_formula_definition())
+        );
+        assert_eq!(step as u64, iterator.steps_done());
+    }
+
+    assert_eq!(None, iterator.step_back());
+    assert_eq!(words.len() - 1, iterator.count());
+}
+
+#[test]
+fn a_reversible_iterator_can_be_rewound_to_a_remembered_step() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("ab").unwrap())
+        .build_with_formula_definitions(["a→b"].into_iter())
+        .unwrap();
+
+    let mut iterator = scheme
+        .get_reversible_application_iterator("aaaa", ExecutionLimits::new())
+        .unwrap()
+        .with_history_limit(2);
+
+    assert_eq!(5, iterator.by_ref().count());
+    assert_eq!(3, iterator.earliest_step());
+
+    assert_eq!(
+        Err(ApplicationRewindError::StepIsAhead {
+            step: 6,
+            steps_done: 5
+        }),
+        iterator.rewind_to(6)
+    );
+    assert_eq!(
+        Err(ApplicationRewindError::StepIsForgotten {
+            step: 2,
+            earliest_step: 3
+        }),
+        iterator.rewind_to(2)
+    );
+
+    let data = iterator.rewind_to(3).unwrap();
+
+    assert_eq!("bbba", data.word());
+    assert_eq!(Some("a→b"), data.applied_formula_definition());
+    assert_eq!(None, iterator.step_back());
+    assert_eq!("bbbb", iterator.next().unwrap().word());
+}
+
+#[test]
+fn a_reversible_iterator_continues_after_stepping_back_from_an_interruption() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("ab").unwrap())
+        .build_with_formula_definitions(["a→b"].into_iter())
+        .unwrap();
+
+    let mut iterator = scheme
+        .get_reversible_application_iterator("aaa", 2)
+        .unwrap();
+
+    assert_eq!(2, iterator.by_ref().count());
+    assert!(iterator.interruption().is_some());
+
+    assert_eq!("baa", iterator.step_back().unwrap().word());
+    assert_eq!(None, iterator.interruption());
+    assert_eq!("bba", iterator.next().unwrap().word());
+    assert_eq!(None, iterator.next());
+}
+
+#[test]
+fn each_step_reports_the_ranges_of_the_substitution_in_bytes_and_characters() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("abнぬ").unwrap())
+        .build_with_formula_definitions(["нa→ぬ", "bぬ→нb", "ぬ→⋅"].into_iter())
+        .unwrap();
+
+    let steps: Vec<_> = scheme
+        .get_application_iterator("ぬbнa")
+        .unwrap()
+        .map(|data| {
+            (
+                data.word().to_owned(),
+                data.step(),
+                data.is_final(),
+                data.applied_formula_index(),
+                data.substitution().cloned(),
+            )
+        })
+        .collect();
+
+    let expected = vec![
+        (
+            "ぬbぬ".to_owned(),
+            1,
+            false,
+            Some(0),
+            Some(AppliedSubstitution {
+                formula_index: 0,
+                matched_bytes: 4..7,
+                matched_characters: 2..4,
+                inserted_bytes: 4..7,
+                inserted_characters: 2..3,
+            }),
+        ),
+        (
+            "ぬнb".to_owned(),
+            2,
+            false,
+            Some(1),
+            Some(AppliedSubstitution {
+                formula_index: 1,
+                matched_bytes: 3..7,
+                matched_characters: 1..3,
+                inserted_bytes: 3..6,
+                inserted_characters: 1..3,
+            }),
+        ),
+        (
+            "нb".to_owned(),
+            3,
+            true,
+            Some(2),
+            Some(AppliedSubstitution {
+                formula_index: 2,
+                matched_bytes: 0..3,
+                matched_characters: 0..1,
+                inserted_bytes: 0..0,
+                inserted_characters: 0..0,
+            }),
+        ),
+    ];
+
+    assert_eq!(expected, steps);
+}
+
+#[test]
+fn the_step_without_a_substitution_is_final_and_reports_no_ranges() {
+    let scheme = Arc::new(
+        AlgorithmSchemeBuilder::new()
+            .with_alphabet(Alphabet::from_str("ab").unwrap())
+            .build_with_formula_definitions(["a→b"].into_iter())
+            .unwrap(),
+    );
+
+    let last = scheme
+        .get_owned_application_iterator("a", ExecutionLimits::new())
+        .unwrap()
+        .last()
+        .unwrap();
+
+    assert_eq!("b", last.word());
+    assert_eq!(2, last.step());
+    assert!(last.is_final());
+    assert_eq!(None, last.applied_formula_index());
+    assert_eq!(None, last.substitution());
+}
+
+/// Builds the same scheme twice, the second time with an alphabet too large to be encoded densely.
+fn build_dense_and_utf8_schemes(
+    alphabet: &str,
+    formula_definitions: &[&str],
+) -> (AlgorithmScheme, AlgorithmScheme) {
+    let build = |alphabet: Alphabet| {
+        AlgorithmSchemeBuilder::new()
+            .with_alphabet(alphabet)
+            .build_with_formula_definitions(formula_definitions.iter().copied())
+            .unwrap()
+    };
+
+    let dense = build(Alphabet::from_str(alphabet).unwrap());
+    let utf8 = build(
+        ('一'..)
+            .take(WordEncoding::MAX_DENSE_CHARACTERS)
+            .try_fold(Alphabet::from_str(alphabet).unwrap(), Alphabet::extend)
+            .unwrap(),
+    );
+
+    assert!(matches!(dense.encoding, WordEncoding::Dense(_)));
+    assert!(matches!(utf8.encoding, WordEncoding::Utf8));
+
+    (dense, utf8)
+}
+
+#[test]
+fn the_dense_encoding_agrees_with_utf8_on_every_step() {
+    let (dense, utf8) =
+        build_dense_and_utf8_schemes("abнж", &["нa→aн", "нb→bжн", "н→⋅", "ж→", "a→нa", "b→нb"]);
+
+    let steps = |scheme: &AlgorithmScheme| {
+        scheme
+            .get_application_iterator_with_limits("abжba", ExecutionLimits::new())
+            .unwrap()
+            .map(|data| {
+                (
+                    data.word().to_owned(),
+                    data.applied_formula_index(),
+                    data.substitution().cloned(),
+                    data.is_final(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(steps(&utf8), steps(&dense));
+}
+
+#[test]
+fn the_length_of_densely_encoded_words_is_limited_in_utf8_bytes() {
+    let (dense, utf8) = build_dense_and_utf8_schemes("aж", &["a→жa"]);
+
+    let limits = ExecutionLimits::new().with_word_length_in_bytes(8);
+
+    for scheme in [&dense, &utf8] {
+        let error = scheme.apply("a", limits.clone()).unwrap_err();
+
+        let partial_result =
+            if let AlgorithmSchemeFullApplicationError::HitTheWordLengthLimit(partial_result) =
+                error
+            {
+                Some(partial_result)
+            } else {
+                None
+            }
+            .unwrap();
+
+        assert_eq!("жжжжa", partial_result.word());
+        assert_eq!(4, partial_result.steps_done());
+    }
+}
+
+#[test]
+fn a_densely_encoded_scheme_reports_the_cycles_and_snapshots_as_strings() {
+    let (dense, utf8) = build_dense_and_utf8_schemes("жa", &["ж→a", "a→ж"]);
+
+    for scheme in [&dense, &utf8] {
+        let error = scheme.apply_with_cycle_detection("жa", 1_000).unwrap_err();
+
+        let cycle = if let AlgorithmSchemeFullApplicationError::CycleDetected(cycle) = error {
+            Some(cycle)
+        } else {
+            None
+        }
+        .unwrap();
+
+        assert_eq!(["жa", "aa"], cycle.words());
+
+        let mut iterator = scheme
+            .get_application_iterator_with_limits("жa", 3)
+            .unwrap();
+        iterator.by_ref().for_each(drop);
+
+        let restored = scheme
+            .restore_application_iterator(&iterator.snapshot(), 1)
+            .unwrap()
+            .next()
+            .unwrap();
+
+        assert_eq!("жa", restored.word());
+    }
+}
+
+#[test]
+fn the_formulas_with_variables_keep_their_place_in_the_order_of_the_formulas() {
+    let build = |formula_definitions: [&str; 2]| {
+        AlgorithmSchemeBuilder::new()
+            .with_alphabet(Alphabet::from_str("abc").unwrap())
+            .with_variables()
+            .build_with_formula_definitions(formula_definitions.into_iter())
+            .unwrap()
+    };
+
+    let steps = |scheme: &AlgorithmScheme| {
+        scheme
+            .get_application_iterator("cbb")
+            .unwrap()
+            .map(|data| (data.word().to_owned(), data.applied_formula_index()))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        vec![("cb".to_owned(), Some(0)), ("b".to_owned(), Some(1)),],
+        steps(&build(["s.xs.x→s.x", "c→⋅"]))
+    );
+    assert_eq!(
+        vec![
+            ("abb".to_owned(), Some(0)),
+            ("ab".to_owned(), Some(1)),
+            ("ab".to_owned(), None),
+        ],
+        steps(&build(["c→a", "s.xs.x→s.x"]))
+    );
+}
+
+#[test]
+fn the_substitutions_of_the_formulas_with_variables_are_measured_in_bytes_and_characters() {
+    let build = |alphabet: Alphabet| {
+        AlgorithmSchemeBuilder::new()
+            .with_alphabet(alphabet)
+            .with_variables()
+            .build_with_formula_definitions(["e.x*s.y→s.ye.x"].into_iter())
+            .unwrap()
+    };
+
+    let dense = build(Alphabet::from_str("aнж*").unwrap());
+    let utf8 = build(
+        ('一'..)
+            .take(WordEncoding::MAX_DENSE_CHARACTERS)
+            .try_fold(Alphabet::from_str("aнж*").unwrap(), Alphabet::extend)
+            .unwrap(),
+    );
+
+    for scheme in [&dense, &utf8] {
+        let data = scheme.apply_once("aн*ж*").unwrap().into_data();
+
+        assert_eq!("жaн*", data.word());
+        assert_eq!(
+            Some(&AppliedSubstitution {
+                formula_index: 0,
+                matched_bytes: 0..6,
+                matched_characters: 0..4,
+                inserted_bytes: 0..5,
+                inserted_characters: 0..3,
+            }),
+            data.substitution()
+        );
+    }
+}
+
+#[test]
+fn a_reversible_iterator_steps_back_over_the_formulas_with_variables() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("ab*").unwrap())
+        .with_variables()
+        .build_with_formula_definitions(["*s.xe.y*→e.y*", "*→⋅"].into_iter())
+        .unwrap();
+
+    let mut iterator = scheme
+        .get_reversible_application_iterator("*ab*ba*", ExecutionLimits::new())
+        .unwrap();
+
+    let words: Vec<_> = iterator
+        .by_ref()
+        .map(|data| data.word().to_owned())
+        .collect();
+
+    assert_eq!(vec!["b*ba*", "ba*", "ba"], words);
+
+    assert_eq!("ba*", iterator.step_back().unwrap().word());
+    assert_eq!("b*ba*", iterator.step_back().unwrap().word());
+    assert_eq!("*ab*ba*", iterator.rewind_to(0).unwrap().word());
+}
+
+#[test]
+fn the_match_strategy_selects_the_formula_and_its_occurrence() {
+    let build = |strategy| {
+        AlgorithmSchemeBuilder::new()
+            .with_alphabet(Alphabet::from_str("abc").unwrap())
+            .with_variables()
+            .with_match_strategy(strategy)
+            .build_with_formula_definitions(["b→⋅c", "s.xs.x→⋅s.x"].into_iter())
+            .unwrap()
+    };
+
+    let apply_once = |strategy| {
+        let scheme = build(strategy);
+        let data = scheme.apply_once("aabb").unwrap().into_data();
+
+        (data.word().to_owned(), data.substitution().cloned())
+    };
+
+    assert_eq!(
+        ("aacb".to_owned(), ascii_substitution(0, 2..3, 2..3)),
+        apply_once(MatchStrategy::Leftmost)
+    );
+    assert_eq!(
+        ("aabc".to_owned(), ascii_substitution(0, 3..4, 3..4)),
+        apply_once(MatchStrategy::Rightmost)
+    );
+    assert_eq!(
+        ("abb".to_owned(), ascii_substitution(1, 0..2, 0..1)),
+        apply_once(MatchStrategy::PositionFirst)
+    );
+}
+
+#[test]
+fn the_empty_left_side_is_matched_at_the_end_of_the_word_when_the_rightmost_occurrence_is_selected()
+{
+    let build = |strategy| {
+        AlgorithmSchemeBuilder::new()
+            .with_alphabet(Alphabet::from_str("abx").unwrap())
+            .with_match_strategy(strategy)
+            .build_with_formula_definitions(["→⋅x"].into_iter())
+            .unwrap()
+    };
+
+    assert_eq!(
+        "abx",
+        build(MatchStrategy::Rightmost)
+            .apply("ab", 1)
+            .unwrap()
+            .word()
+    );
+    assert_eq!(
+        "xab",
+        build(MatchStrategy::PositionFirst)
+            .apply("ab", 1)
+            .unwrap()
+            .word()
+    );
+}
+
+#[test]
+fn a_reversible_iterator_steps_back_over_the_rightmost_occurrences() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("ab").unwrap())
+        .with_match_strategy(MatchStrategy::Rightmost)
+        .build_with_formula_definitions(["ab→ba"].into_iter())
+        .unwrap();
+
+    let mut iterator = scheme
+        .get_reversible_application_iterator("abab", ExecutionLimits::new())
+        .unwrap();
+
+    let words: Vec<_> = iterator
+        .by_ref()
+        .map(|data| data.word().to_owned())
+        .collect();
+
+    assert_eq!(vec!["abba", "baba", "bbaa", "bbaa"], words);
+
+    assert_eq!("abba", iterator.rewind_to(1).unwrap().word());
+    assert_eq!("abab", iterator.step_back().unwrap().word());
+}
+
+#[test]
+fn the_match_strategy_is_a_part_of_the_fingerprint() {
+    let build = |strategy| {
+        AlgorithmSchemeBuilder::new()
+            .with_match_strategy(strategy)
+            .build_with_formula_definitions(["a→b"].into_iter())
+            .unwrap()
+    };
+
+    assert_ne!(
+        build(MatchStrategy::Leftmost).fingerprint,
+        build(MatchStrategy::Rightmost).fingerprint
+    );
+}
+
+fn create_stochastic_scheme(seed: u64, formula_definitions: &[(&str, u32)]) -> AlgorithmScheme {
+    AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("abc").unwrap())
+        .with_stochastic_selection(seed)
+        .build_with_weighted_formula_definitions(formula_definitions.iter().copied())
+        .unwrap()
+}
+
+#[test]
+fn the_formula_is_chosen_at_random_among_the_applicable_ones() {
+    let formula_definitions = [("a→b", 1), ("c→a", 1), ("a→c", 1), ("b→⋅", 1)];
+
+    let choices: HashSet<_> = (0..50)
+        .map(|seed| {
+            let scheme = create_stochastic_scheme(seed, &formula_definitions);
+            let data = scheme
+                .get_application_iterator("ab")
+                .unwrap()
+                .next()
+                .unwrap();
+            let choice = data.choice().unwrap();
+
+            assert_eq!(seed, choice.seed());
+            assert_eq!([0, 2, 3], choice.alternatives());
+            assert_eq!(data.applied_formula_index(), Some(choice.chosen()));
+
+            choice.chosen()
+        })
+        .collect();
+
+    assert_eq!(HashSet::from([0, 2, 3]), choices);
+}
+
+#[test]
+fn the_formulas_are_chosen_in_proportion_to_their_weights() {
+    let scheme = create_stochastic_scheme(2024, &[("a→b", 1), ("a→c", 3)]);
+
+    let word = scheme.apply("a".repeat(4_000).as_str(), 5_000).unwrap();
+    let chosen_with_greater_weight = word.word().matches('c').count();
+
+    assert!(
+        (2_850..3_150).contains(&chosen_with_greater_weight),
+        "{chosen_with_greater_weight}"
+    );
+}
+
+#[test]
+fn the_stochastic_application_is_reproducible() {
+    let formula_definitions = [("a→b", 1), ("b→c", 1), ("ab→ba", 2)];
+
+    let scheme = create_stochastic_scheme(5, &formula_definitions);
+    let result = scheme.apply("aaaaaa", 1_000).unwrap();
+
+    assert_eq!(
+        result,
+        create_stochastic_scheme(5, &formula_definitions)
+            .apply("aaaaaa", 1_000)
+            .unwrap()
+    );
+
+    let words: Vec<_> = scheme
+        .get_application_iterator("aaaaaa")
+        .unwrap()
+        .map(|data| data.word().to_owned())
+        .collect();
+
+    assert_eq!(Some(result.word()), words.last().map(String::as_str));
+    assert_eq!(result.steps_done(), words.len() as u64);
+
+    let partial_result = match scheme.apply("aaaaaa", 2) {
+        Err(AlgorithmSchemeFullApplicationError::HitTheStepsLimit(partial_result)) => {
+            partial_result
+        }
+        other => panic!("{other:?}"),
+    };
+
+    assert_eq!(result, scheme.resume(partial_result, 1_000).unwrap());
+
+    let mut iterator = scheme.get_application_iterator("aaaaaa").unwrap();
+    _ = iterator.nth(1);
+
+    let restored: Vec<_> = scheme
+        .restore_application_iterator(&iterator.snapshot(), ExecutionLimits::new())
+        .unwrap()
+        .map(|data| data.word().to_owned())
+        .collect();
+
+    assert_eq!(words[2..], restored);
+}
+
+#[test]
+fn a_reversible_iterator_repeats_the_random_choices_after_stepping_back() {
+    let scheme = create_stochastic_scheme(11, &[("a→b", 1), ("a→c", 1), ("b→a", 1)]);
+
+    let mut iterator = scheme
+        .get_reversible_application_iterator("aaaa", 10)
+        .unwrap();
+
+    let forward: Vec<_> = iterator
+        .by_ref()
+        .map(|data| (data.word().to_owned(), data.choice().cloned()))
+        .collect();
+
+    _ = iterator.rewind_to(3).unwrap();
+
+    let replayed: Vec<_> = iterator
+        .map(|data| (data.word().to_owned(), data.choice().cloned()))
+        .collect();
+
+    assert_eq!(forward[3..], replayed);
+}
+
+#[test]
+fn the_cycles_are_not_detected_for_the_stochastic_schemes() {
+    let scheme = create_stochastic_scheme(3, &[("a→b", 1), ("b→a", 1)]);
+
+    assert!(matches!(
+        scheme.apply_with_cycle_detection("ab", 100),
+        Err(AlgorithmSchemeFullApplicationError::HitTheStepsLimit(_))
+    ));
+}
+
+#[test]
+fn the_deterministic_steps_have_no_random_choice() {
+    let scheme = AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("ab").unwrap())
+        .build_with_weighted_formula_definitions([("a→b", 5)].into_iter())
+        .unwrap();
+
+    let data = scheme
+        .get_application_iterator("a")
+        .unwrap()
+        .next()
+        .unwrap();
+
+    assert_eq!("b", data.word());
+    assert_eq!(None, data.choice());
+}
+
+#[test]
+fn the_seed_and_the_weights_are_parts_of_the_fingerprint() {
+    let fingerprints: HashSet<_> = [
+        create_stochastic_scheme(1, &[("a→b", 1), ("b→c", 1)]),
+        create_stochastic_scheme(2, &[("a→b", 1), ("b→c", 1)]),
+        create_stochastic_scheme(1, &[("a→b", 2), ("b→c", 1)]),
+    ]
+    .iter()
+    .map(AlgorithmScheme::fingerprint)
+    .collect();
+
+    assert_eq!(3, fingerprints.len());
+}
+
+fn create_grouped_scheme(
+    strategy: MatchStrategy,
+    tie_break: GroupTieBreak,
+    formula_definitions: &[&str],
+) -> AlgorithmScheme {
+    AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("abc").unwrap())
+        .with_variables()
+        .with_match_strategy(strategy)
+        .with_priority_groups(tie_break)
+        .build_with_formula_definitions(formula_definitions.iter().copied())
+        .unwrap()
+}
+
+#[test]
+fn the_first_applicable_group_wins_and_the_strategy_selects_the_occurrence_inside_it() {
+    let formula_definitions = ["cc→⋅", "b→⋅c", "|s.xs.x→⋅s.x", "a→⋅c"];
+
+    let apply_once = |strategy| {
+        let scheme =
+            create_grouped_scheme(strategy, GroupTieBreak::FirstFormula, &formula_definitions);
+        let data = scheme.apply_once("abaa").unwrap().into_data();
+
+        (data.word().to_owned(), data.substitution().cloned())
+    };
+
+    assert_eq!(
+        ("acaa".to_owned(), ascii_substitution(1, 1..2, 1..2)),
+        apply_once(MatchStrategy::Leftmost)
+    );
+    assert_eq!(
+        ("aba".to_owned(), ascii_substitution(2, 2..4, 2..3)),
+        apply_once(MatchStrategy::Rightmost)
+    );
+    assert_eq!(
+        ("cbaa".to_owned(), ascii_substitution(3, 0..1, 0..1)),
+        apply_once(MatchStrategy::PositionFirst)
+    );
+}
+
+#[test]
+fn the_ties_inside_a_group_are_broken_as_declared() {
+    let formula_definitions = ["a→⋅c", "|s.xs.x→⋅c", "|ab→⋅"];
+
+    let apply = |tie_break, word| {
+        create_grouped_scheme(MatchStrategy::Leftmost, tie_break, &formula_definitions)
+            .apply(word, 1)
+            .unwrap()
+            .word()
+            .to_owned()
+    };
+
+    assert_eq!("cab", apply(GroupTieBreak::FirstFormula, "aab"));
+    assert_eq!("cb", apply(GroupTieBreak::LongestMatch, "aab"));
+    assert_eq!("", apply(GroupTieBreak::LongestMatch, "ab"));
+    assert_eq!("c", apply(GroupTieBreak::LongestMatch, "aa"));
+}
+
+#[test]
+fn the_formulas_without_group_markers_keep_their_order() {
+    let formula_definitions = ["ab→ba", "s.xs.x→s.x", "c→a", "a→⋅c"];
+
+    let grouped = create_grouped_scheme(
+        MatchStrategy::Leftmost,
+        GroupTieBreak::LongestMatch,
+        &formula_definitions,
+    );
+    let ordered = AlgorithmSchemeBuilder::new()
+        .with_alphabet(Alphabet::from_str("abc").unwrap())
+        .with_variables()
+        .build_with_formula_definitions(formula_definitions.into_iter())
+        .unwrap();
+
+    for word in ["", "abc", "cbacba", "aabbcc", "bcbcab"] {
+        let words = |scheme: &AlgorithmScheme| -> Vec<_> {
+            scheme
+                .get_application_iterator(word)
+                .unwrap()
+                .map(|data| data.word().to_owned())
+                .collect()
+        };
+
+        assert_eq!(words(&ordered), words(&grouped), "{word}");
+    }
+}
+
+#[test]
+fn the_formula_is_chosen_at_random_inside_the_first_applicable_group() {
+    let choices: HashSet<_> = (0..50)
+        .map(|seed| {
+            let scheme = AlgorithmSchemeBuilder::new()
+                .with_alphabet(Alphabet::from_str("abc").unwrap())
+                .with_stochastic_selection(seed)
+                .with_priority_groups(GroupTieBreak::FirstFormula)
+                .build_with_formula_definitions(["c→a", "b→a", "|a→b", "a→c"].into_iter())
+                .unwrap();
+            let data = scheme
+                .get_application_iterator("ab")
+                .unwrap()
+                .next()
+                .unwrap();
+            let choice = data.choice().unwrap();
+
+            assert_eq!([1, 2], choice.alternatives());
+
+            choice.chosen()
+        })
+        .collect();
+
+    assert_eq!(HashSet::from([1, 2]), choices);
+}
+
+#[test]
+fn the_priority_groups_are_a_part_of_the_fingerprint() {
+    let build = |builder: AlgorithmSchemeBuilder| {
+        builder
+            .with_alphabet(Alphabet::from_str("ab").unwrap())
+            .build_with_formula_definitions(["a→b", "b→a"].into_iter())
+            .unwrap()
+    };
+
+    let fingerprints: HashSet<_> = [
+        build(AlgorithmSchemeBuilder::new()),
+        build(AlgorithmSchemeBuilder::new().with_priority_groups(GroupTieBreak::FirstFormula)),
+        build(AlgorithmSchemeBuilder::new().with_priority_groups(GroupTieBreak::LongestMatch)),
+    ]
+    .iter()
+    .map(AlgorithmScheme::fingerprint)
+    .collect();
+
+    assert_eq!(3, fingerprints.len());
+}
@@ -0,0 +1,650 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! [TuringMachine](TuringMachine), a deterministic single-tape Turing machine
+//! that can be simulated directly or compiled into an algorithm scheme.
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    str::FromStr,
+};
+
+use thiserror::Error;
+
+use crate::alphabet::Alphabet;
+
+use super::{scheme_builder::AlgorithmSchemeBuilder, AlgorithmScheme};
+
+/// A deterministic Turing machine with a single tape that is infinite in both directions.
+///
+/// The machine starts in the start state with the head over the first character of the input,
+/// and halts as soon as there is no transition for its state and the scanned symbol.
+/// The result is the content of the tape without the leading and the trailing blanks.
+///
+/// The machine is described by a text, each line of which is one of the following:
+/// - a blank line, which is ignored;
+/// - a comment that starts with `#`, possibly after whitespace, which is ignored;
+/// - a directive that starts with `@`, followed by the value after whitespace:
+///   `@tape`, followed by the symbols of the tape, `@blank`, followed by the blank symbol, `_` by default,
+///   or `@start`, followed by the start state, the state of the first transition by default;
+/// - a transition `state symbol -> state symbol move`, where the move is `L`, `R` or `S` to stay.
+///
+/// The directives form the header and cannot follow the transitions, and `@tape` is required.
+/// The states are any words without whitespace that do not start with `#`, and the symbols are any characters but whitespace.
+///
+/// # Example
+/// ```rust
+/// use markovalgorithms::prelude::*;
+///
+/// let machine: TuringMachine = "\
+/// ## Inverts the bits.
+/// @tape 01
+///
+/// flip 0 -> flip 1 R
+/// flip 1 -> flip 0 R
+/// ".parse().unwrap();
+///
+/// assert_eq!("0110", machine.simulate("1001", 100).unwrap().tape());
+///
+/// let compiled = machine.compile();
+///
+/// assert_eq!("0110", compiled.scheme().apply("1001", 100).unwrap().word());
+/// ```
+#[derive(Debug, Clone)]
+pub struct TuringMachine {
+    states: Vec<String>,
+    start_state: usize,
+    /// The symbols of the tape in the order of their code points, including the blank.
+    symbols: Vec<char>,
+    blank: char,
+    transitions: HashMap<(usize, char), Transition>,
+}
+
+/// The movement of the head after a transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeadMovement {
+    /// Moves the head to the previous cell.
+    Left,
+    /// Moves the head to the next cell.
+    Right,
+    /// Leaves the head over the same cell.
+    Stay,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Transition {
+    state: usize,
+    symbol: char,
+    movement: HeadMovement,
+}
+
+/// The machine that has halted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TuringMachineHalt<'a> {
+    state: &'a str,
+    tape: String,
+    steps_done: u64,
+}
+
+/// The scheme that simulates a Turing machine, and the characters it represents the states with.
+///
+/// The word of the scheme is the content of the tape, with the character of the current state
+/// inserted right before the scanned symbol. The character of the state at the end of the word
+/// means that the head is over a blank, which is written out before the transition is made.
+/// The scheme starts by inserting the character of the start state before the input,
+/// and once the machine halts, it removes the state and trims the blanks with two auxiliary markers.
+#[derive(Debug)]
+pub struct CompiledTuringMachine {
+    scheme: AlgorithmScheme,
+    state_characters: Vec<(String, char)>,
+}
+
+/// An error in the description of a Turing machine, the lines are counted from one.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TuringMachineParseError {
+    /// The directive is not known.
+    #[error("line {line}: unknown directive \"@{name}\"")]
+    UnknownDirective { line: usize, name: String },
+    /// The directive has no value.
+    #[error("line {line}: the directive \"@{name}\" has no value")]
+    MissingValue { line: usize, name: String },
+    /// The directive is defined more than once.
+    #[error("line {line}: the directive \"@{name}\" is repeated")]
+    DuplicateDirective { line: usize, name: String },
+    /// The directive follows the transitions.
+    #[error("line {line}: the directives should precede the transitions")]
+    DirectiveAfterTransitions { line: usize },
+    /// The value of the directive should be a single character.
+    #[error(
+        "line {line}: the directive \"@{name}\" expects a single character, found \"{value}\""
+    )]
+    NotASingleCharacter {
+        line: usize,
+        name: String,
+        value: String,
+    },
+    /// The symbols of the tape or the blank contain whitespace.
+    #[error(
+        "line {line}: the directive \"@{name}\" contains whitespace, which cannot be a symbol"
+    )]
+    WhitespaceSymbol { line: usize, name: String },
+    /// The symbols of the tape are not defined.
+    #[error("the symbols of the tape are not defined with \"@tape\"")]
+    MissingTape,
+    /// Neither the start state nor any transition is defined.
+    #[error("the start state is not defined with \"@start\", and there are no transitions")]
+    MissingStartState,
+    /// The line is not a transition.
+    #[error("line {line}: expected a transition \"state symbol -> state symbol move\", found \"{content}\"")]
+    MalformedTransition { line: usize, content: String },
+    /// The symbol does not belong to the tape.
+    #[error("line {line}: the symbol \"{symbol}\" does not belong to the tape")]
+    UnknownSymbol { line: usize, symbol: String },
+    /// The move is not `L`, `R` or `S`.
+    #[error("line {line}: the move should be \"L\", \"R\" or \"S\", found \"{value}\"")]
+    NotAMove { line: usize, value: String },
+    /// The state starts with the comment character or contains whitespace.
+    #[error(
+        "line {line}: the state \"{state}\" should neither start with \"#\" nor contain whitespace"
+    )]
+    InvalidState { line: usize, state: String },
+    /// The transition for the state and the symbol is already defined.
+    #[error("line {line}: the transition for the state \"{state}\" and the symbol \"{symbol}\" is repeated")]
+    DuplicateTransition {
+        line: usize,
+        state: String,
+        symbol: char,
+    },
+}
+
+/// An error that stops the simulation of a Turing machine.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TuringSimulationError {
+    /// The input contains a character that is not a symbol of the tape.
+    #[error("the input contains the character \"{0}\" that is not a symbol of the tape")]
+    UnknownSymbol(char),
+    /// The machine has not halted in the allowed number of steps.
+    #[error("the machine has not halted in {0} steps")]
+    StepLimitExhausted(u64),
+}
+
+impl TuringMachine {
+    const COMMENT: char = '#';
+    const DIRECTIVE: char = '@';
+    const ARROW: &'static str = "->";
+    const DEFAULT_BLANK: char = '_';
+
+    /// The characters reserved by the default builder, the escape character and the anchors.
+    const RESERVED: [char; 5] = ['→', '⋅', '\\', '^', '$'];
+
+    /// The states in the order of their first occurrence in the description.
+    pub fn states(&self) -> impl Iterator<Item = &str> {
+        self.states.iter().map(String::as_str)
+    }
+
+    /// The state the machine starts in.
+    pub fn start_state(&self) -> &str {
+        &self.states[self.start_state]
+    }
+
+    /// The symbols of the tape in the order of their code points, including the blank.
+    pub fn symbols(&self) -> &[char] {
+        &self.symbols
+    }
+
+    /// The symbol of the empty cells.
+    pub fn blank(&self) -> char {
+        self.blank
+    }
+
+    /// Runs the machine on the input until it halts.
+    ///
+    /// # Errors
+    /// Returns an [error](TuringSimulationError) if the input contains a character that is not a symbol of the tape,
+    /// or if the machine does not halt in the given number of steps.
+    pub fn simulate(
+        &self,
+        input: &str,
+        max_steps: u64,
+    ) -> Result<TuringMachineHalt<'_>, TuringSimulationError> {
+        if let Some(character) = input
+            .chars()
+            .find(|character| self.symbols.binary_search(character).is_err())
+        {
+            return Err(TuringSimulationError::UnknownSymbol(character));
+        }
+
+        let mut tape: VecDeque<_> = input.chars().collect();
+        let mut head = 0;
+        let mut state = self.start_state;
+        let mut steps_done = 0;
+
+        loop {
+            if head == tape.len() {
+                tape.push_back(self.blank);
+            }
+
+            let Some(transition) = self.transitions.get(&(state, tape[head])) else {
+                break;
+            };
+
+            if steps_done == max_steps {
+                return Err(TuringSimulationError::StepLimitExhausted(max_steps));
+            }
+
+            tape[head] = transition.symbol;
+            state = transition.state;
+            steps_done += 1;
+
+            match transition.movement {
+                HeadMovement::Left if head == 0 => tape.push_front(self.blank),
+                HeadMovement::Left => head -= 1,
+                HeadMovement::Right => head += 1,
+                HeadMovement::Stay => {}
+            }
+        }
+
+        let tape: String = tape.into_iter().collect();
+
+        Ok(TuringMachineHalt {
+            state: &self.states[state],
+            tape: tape.trim_matches(self.blank).to_owned(),
+            steps_done,
+        })
+    }
+
+    /// Compiles the machine into the scheme that rewrites the input into the result of the machine.
+    ///
+    /// The alphabet of the scheme is the tape, and the states are represented by the characters of the extension,
+    /// chosen among the ones that are neither symbols of the tape nor reserved by the scheme.
+    /// The scheme does not stop on the inputs the machine does not halt on.
+    pub fn compile(&self) -> CompiledTuringMachine {
+        let mut free_characters = ('A'..='Z')
+            .chain('α'..='ω')
+            .chain('\u{100}'..=char::MAX)
+            .filter(|character| {
+                self.symbols.binary_search(character).is_err()
+                    && !Self::RESERVED.contains(character)
+            });
+
+        let mut take_character = || {
+            free_characters
+                .next()
+                .expect("There are more characters than the states of a machine.")
+        };
+
+        let state_characters: Vec<_> = self.states.iter().map(|_| take_character()).collect();
+        let (rewinding, trimming) = (take_character(), take_character());
+
+        let escape = |character: char| {
+            if Self::RESERVED.contains(&character) {
+                format!("\\{character}")
+            } else {
+                character.to_string()
+            }
+        };
+
+        let blank = escape(self.blank);
+        let symbols: Vec<_> = self.symbols.iter().copied().map(escape).collect();
+        let mut formula_definitions = Vec::new();
+
+        for (index, &state) in state_characters.iter().enumerate() {
+            for (symbol, escaped) in self.symbols.iter().zip(&symbols) {
+                let Some(transition) = self.transitions.get(&(index, *symbol)) else {
+                    formula_definitions.push(format!("{state}{escaped}→{rewinding}{escaped}"));
+                    continue;
+                };
+
+                let next = state_characters[transition.state];
+                let written = escape(transition.symbol);
+
+                match transition.movement {
+                    HeadMovement::Left => {
+                        formula_definitions.extend(symbols.iter().map(|previous| {
+                            format!("{previous}{state}{escaped}→{next}{previous}{written}")
+                        }));
+                        formula_definitions
+                            .push(format!("^{state}{escaped}→{next}{blank}{written}"));
+                    }
+                    HeadMovement::Right => {
+                        formula_definitions.push(format!("{state}{escaped}→{written}{next}"))
+                    }
+                    HeadMovement::Stay => {
+                        formula_definitions.push(format!("{state}{escaped}→{next}{written}"))
+                    }
+                }
+            }
+
+            formula_definitions.push(format!("{state}$→{state}{blank}"));
+        }
+
+        // Once the machine halts, the state is rewound to the start to trim the leading blanks,
+        // then it walks to the end to trim the trailing ones and stops.
+        formula_definitions.extend(
+            symbols
+                .iter()
+                .map(|symbol| format!("{symbol}{rewinding}→{rewinding}{symbol}")),
+        );
+        formula_definitions.push(format!("^{rewinding}{blank}→{rewinding}"));
+        formula_definitions.push(format!("{rewinding}→{trimming}"));
+        formula_definitions.push(format!("{blank}{trimming}$→{trimming}"));
+        formula_definitions.push(format!("{trimming}$→⋅"));
+        formula_definitions.extend(
+            symbols
+                .iter()
+                .map(|symbol| format!("{trimming}{symbol}→{symbol}{trimming}")),
+        );
+        formula_definitions.push(format!("→{}", state_characters[self.start_state]));
+
+        let alphabet = state_characters
+            .iter()
+            .chain([&rewinding, &trimming])
+            .try_fold(
+                Alphabet::try_from(&self.symbols.iter().copied().collect::<HashSet<_>>())
+                    .expect("The tape contains at least the blank."),
+                |alphabet, &character| alphabet.extend(character),
+            )
+            .expect("The characters of the states are not symbols of the tape.");
+
+        let scheme = AlgorithmSchemeBuilder::new()
+            .with_alphabet(alphabet)
+            .with_escape_character('\\')
+            .with_anchors()
+            .build_with_formula_definitions(formula_definitions.iter().map(String::as_str))
+            .expect("The formulas compiled from a machine are valid.");
+
+        CompiledTuringMachine {
+            scheme,
+            state_characters: self.states.iter().cloned().zip(state_characters).collect(),
+        }
+    }
+}
+
+impl FromStr for TuringMachine {
+    type Err = TuringMachineParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut header = Header::default();
+        let mut transition_lines = Vec::new();
+
+        for (index, content) in text.lines().enumerate() {
+            let line = index + 1;
+
+            if content.trim().is_empty() || content.trim_start().starts_with(Self::COMMENT) {
+                continue;
+            }
+
+            if let Some(directive) = content.strip_prefix(Self::DIRECTIVE) {
+                if !transition_lines.is_empty() {
+                    return Err(TuringMachineParseError::DirectiveAfterTransitions { line });
+                }
+
+                header.add(line, directive)?;
+            } else {
+                transition_lines.push((line, content));
+            }
+        }
+
+        let blank = match header.blank {
+            None => Self::DEFAULT_BLANK,
+            Some((line, value)) => {
+                Self::assert_no_whitespace(line, "blank", value)?;
+
+                let mut characters = value.chars();
+
+                match (characters.next(), characters.next()) {
+                    (Some(blank), None) => blank,
+                    _ => {
+                        return Err(TuringMachineParseError::NotASingleCharacter {
+                            line,
+                            name: "blank".to_owned(),
+                            value: value.to_owned(),
+                        })
+                    }
+                }
+            }
+        };
+
+        let (line, tape) = header.tape.ok_or(TuringMachineParseError::MissingTape)?;
+
+        Self::assert_no_whitespace(line, "tape", tape)?;
+
+        let mut symbols: Vec<_> = tape.chars().chain([blank]).collect();
+        symbols.sort_unstable();
+        symbols.dedup();
+
+        let mut machine = Self {
+            states: Vec::new(),
+            start_state: 0,
+            symbols,
+            blank,
+            transitions: HashMap::new(),
+        };
+
+        if let Some((line, start_state)) = header.start_state {
+            machine.state_index(line, start_state)?;
+        }
+
+        for (line, content) in transition_lines {
+            machine.add_transition(line, content)?;
+        }
+
+        if machine.states.is_empty() {
+            Err(TuringMachineParseError::MissingStartState)
+        } else {
+            Ok(machine)
+        }
+    }
+}
+
+impl TuringMachine {
+    /// Checks that the value of the directive does not contain whitespace, which cannot be a symbol.
+    fn assert_no_whitespace(
+        line: usize,
+        name: &str,
+        value: &str,
+    ) -> Result<(), TuringMachineParseError> {
+        if value.contains(char::is_whitespace) {
+            Err(TuringMachineParseError::WhitespaceSymbol {
+                line,
+                name: name.to_owned(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Parses the transition and adds it to the machine.
+    fn add_transition(
+        &mut self,
+        line: usize,
+        content: &str,
+    ) -> Result<(), TuringMachineParseError> {
+        let malformed = || TuringMachineParseError::MalformedTransition {
+            line,
+            content: content.to_owned(),
+        };
+
+        let [state, symbol, arrow, next_state, written, movement] = content
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .try_into()
+            .map_err(|_| malformed())?;
+
+        if arrow != Self::ARROW {
+            return Err(malformed());
+        }
+
+        let symbol = self.parse_symbol(line, symbol)?;
+        let written = self.parse_symbol(line, written)?;
+
+        let movement = match movement {
+            "L" => HeadMovement::Left,
+            "R" => HeadMovement::Right,
+            "S" => HeadMovement::Stay,
+            _ => {
+                return Err(TuringMachineParseError::NotAMove {
+                    line,
+                    value: movement.to_owned(),
+                })
+            }
+        };
+
+        let state_index = self.state_index(line, state)?;
+
+        let transition = Transition {
+            state: self.state_index(line, next_state)?,
+            symbol: written,
+            movement,
+        };
+
+        if self
+            .transitions
+            .insert((state_index, symbol), transition)
+            .is_some()
+        {
+            return Err(TuringMachineParseError::DuplicateTransition {
+                line,
+                state: state.to_owned(),
+                symbol,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn parse_symbol(&self, line: usize, value: &str) -> Result<char, TuringMachineParseError> {
+        let mut characters = value.chars();
+
+        match (characters.next(), characters.next()) {
+            (Some(symbol), None) if self.symbols.binary_search(&symbol).is_ok() => Ok(symbol),
+            _ => Err(TuringMachineParseError::UnknownSymbol {
+                line,
+                symbol: value.to_owned(),
+            }),
+        }
+    }
+
+    /// The index of the state, the new states are added in the order of their first occurrence.
+    ///
+    /// The state cannot start with the comment character, since the line of its transitions would be a comment.
+    fn state_index(&mut self, line: usize, state: &str) -> Result<usize, TuringMachineParseError> {
+        if state.starts_with(Self::COMMENT) || state.contains(char::is_whitespace) {
+            return Err(TuringMachineParseError::InvalidState {
+                line,
+                state: state.to_owned(),
+            });
+        }
+
+        Ok(match self.states.iter().position(|known| known == state) {
+            Some(index) => index,
+            None => {
+                self.states.push(state.to_owned());
+                self.states.len() - 1
+            }
+        })
+    }
+}
+
+impl<'a> TuringMachineHalt<'a> {
+    /// The state the machine has halted in.
+    pub fn state(&self) -> &'a str {
+        self.state
+    }
+
+    /// The content of the tape without the leading and the trailing blanks.
+    pub fn tape(&self) -> &str {
+        &self.tape
+    }
+
+    /// The number of transitions made.
+    pub fn steps_done(&self) -> u64 {
+        self.steps_done
+    }
+}
+
+impl CompiledTuringMachine {
+    /// The scheme that simulates the machine.
+    pub fn scheme(&self) -> &AlgorithmScheme {
+        &self.scheme
+    }
+
+    /// Consumes the compiled machine and returns the scheme.
+    pub fn into_scheme(self) -> AlgorithmScheme {
+        self.scheme
+    }
+
+    /// The character that represents the state in the words of the scheme.
+    pub fn state_character(&self, state: &str) -> Option<char> {
+        self.state_characters
+            .iter()
+            .find(|(known, _)| known == state)
+            .map(|&(_, character)| character)
+    }
+}
+
+/// The values of the directives and the lines they are defined at.
+#[derive(Default)]
+struct Header<'a> {
+    tape: Option<(usize, &'a str)>,
+    blank: Option<(usize, &'a str)>,
+    start_state: Option<(usize, &'a str)>,
+}
+
+impl<'a> Header<'a> {
+    fn add(&mut self, line: usize, directive: &'a str) -> Result<(), TuringMachineParseError> {
+        let (name, value) = directive
+            .split_once(char::is_whitespace)
+            .unwrap_or((directive, ""));
+        let value = value.trim();
+
+        let slot = match name {
+            "tape" => &mut self.tape,
+            "blank" => &mut self.blank,
+            "start" => &mut self.start_state,
+            _ => {
+                return Err(TuringMachineParseError::UnknownDirective {
+                    line,
+                    name: name.to_owned(),
+                })
+            }
+        };
+
+        if value.is_empty() {
+            return Err(TuringMachineParseError::MissingValue {
+                line,
+                name: name.to_owned(),
+            });
+        }
+
+        if slot.is_some() {
+            return Err(TuringMachineParseError::DuplicateDirective {
+                line,
+                name: name.to_owned(),
+            });
+        }
+
+        _ = slot.insert((line, value));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,297 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::*;
+
+const BINARY_INCREMENT: &str = "\
+# Adds one to a binary number.
+@tape 01
+@start right
+
+right 0 -> right 0 R
+right 1 -> right 1 R
+right _ -> carry _ L
+carry 1 -> carry 0 L
+carry 0 -> done 1 S
+carry _ -> done 1 S
+";
+
+fn parse(text: &str) -> TuringMachine {
+    text.parse().unwrap()
+}
+
+fn assert_compiled_like_simulated(machine: &TuringMachine, inputs: &[&str]) {
+    let scheme = machine.compile().into_scheme();
+
+    for input in inputs {
+        let expected = machine.simulate(input, 10_000).unwrap();
+        let result = scheme.apply(input, 100_000).unwrap();
+
+        assert_eq!(expected.tape(), result.word(), "input \"{input}\"");
+    }
+}
+
+#[test]
+fn the_description_is_parsed() {
+    let machine = parse(BINARY_INCREMENT);
+
+    assert_eq!(
+        vec!["right", "carry", "done"],
+        machine.states().collect::<Vec<_>>()
+    );
+    assert_eq!("right", machine.start_state());
+    assert_eq!(['0', '1', '_'], machine.symbols());
+    assert_eq!('_', machine.blank());
+}
+
+#[test]
+fn the_start_state_defaults_to_the_state_of_the_first_transition() {
+    let machine = parse("@tape a\n@blank .\nnext . -> first a R\nfirst . -> next a R");
+
+    assert_eq!("next", machine.start_state());
+    assert_eq!(['.', 'a'], machine.symbols());
+}
+
+#[test]
+fn the_indented_comments_are_ignored() {
+    let machine = parse("@tape a\n  # An indented comment.\n\t# Another one.\nq a -> q _ R");
+
+    assert_eq!(vec!["q"], machine.states().collect::<Vec<_>>());
+    assert_eq!("", machine.simulate("aa", 10).unwrap().tape());
+}
+
+#[test]
+fn the_symbols_cannot_be_whitespace() {
+    let parse = |text: &str| text.parse::<TuringMachine>().map(|_| ());
+
+    assert_eq!(
+        Err(TuringMachineParseError::WhitespaceSymbol {
+            line: 1,
+            name: "tape".to_owned()
+        }),
+        parse("@tape 0 1\nq 0 -> q 1 R")
+    );
+    assert_eq!(
+        Err(TuringMachineParseError::WhitespaceSymbol {
+            line: 2,
+            name: "blank".to_owned()
+        }),
+        parse("@tape 01\n@blank _\u{3000}.\nq 0 -> q 1 R")
+    );
+}
+
+#[test]
+fn the_states_cannot_start_with_the_comment_character() {
+    let parse = |text: &str| text.parse::<TuringMachine>().map(|_| ());
+
+    assert_eq!(
+        Err(TuringMachineParseError::InvalidState {
+            line: 2,
+            state: "#next".to_owned()
+        }),
+        parse("@tape a\nq a -> #next a R")
+    );
+    assert_eq!(
+        Err(TuringMachineParseError::InvalidState {
+            line: 2,
+            state: "#q".to_owned()
+        }),
+        parse("@tape a\n@start #q\nq a -> q a R")
+    );
+    assert_eq!(
+        Err(TuringMachineParseError::InvalidState {
+            line: 1,
+            state: "first q".to_owned()
+        }),
+        parse("@start first q\n@tape a")
+    );
+    assert!(parse("@tape a\nq# a -> q# a R").is_ok());
+}
+
+#[test]
+fn the_errors_in_the_description_point_at_the_lines() {
+    let parse = |text: &str| text.parse::<TuringMachine>().unwrap_err();
+
+    assert_eq!(
+        TuringMachineParseError::UnknownDirective {
+            line: 1,
+            name: "halt".to_owned()
+        },
+        parse("@halt q")
+    );
+    assert_eq!(
+        TuringMachineParseError::MissingValue {
+            line: 2,
+            name: "start".to_owned()
+        },
+        parse("@tape a\n@start")
+    );
+    assert_eq!(
+        TuringMachineParseError::DuplicateDirective {
+            line: 2,
+            name: "tape".to_owned()
+        },
+        parse("@tape a\n@tape b")
+    );
+    assert_eq!(
+        TuringMachineParseError::DirectiveAfterTransitions { line: 3 },
+        parse("@tape a\nq a -> q a R\n@blank .")
+    );
+    assert_eq!(
+        TuringMachineParseError::NotASingleCharacter {
+            line: 2,
+            name: "blank".to_owned(),
+            value: "..".to_owned()
+        },
+        parse("@tape a\n@blank ..")
+    );
+    assert_eq!(TuringMachineParseError::MissingTape, parse("q a -> q a R"));
+    assert_eq!(
+        TuringMachineParseError::MissingStartState,
+        parse("# Nothing to do.\n@tape a")
+    );
+    assert_eq!(
+        TuringMachineParseError::MalformedTransition {
+            line: 2,
+            content: "q a => q a R".to_owned()
+        },
+        parse("@tape a\nq a => q a R")
+    );
+    assert_eq!(
+        TuringMachineParseError::MalformedTransition {
+            line: 2,
+            content: "q a -> q a".to_owned()
+        },
+        parse("@tape a\nq a -> q a")
+    );
+    assert_eq!(
+        TuringMachineParseError::UnknownSymbol {
+            line: 2,
+            symbol: "b".to_owned()
+        },
+        parse("@tape a\nq a -> q b R")
+    );
+    assert_eq!(
+        TuringMachineParseError::NotAMove {
+            line: 2,
+            value: "U".to_owned()
+        },
+        parse("@tape a\nq a -> q a U")
+    );
+    assert_eq!(
+        TuringMachineParseError::DuplicateTransition {
+            line: 3,
+            state: "q".to_owned(),
+            symbol: 'a'
+        },
+        parse("@tape a\nq a -> q a R\nq a -> p a L")
+    );
+}
+
+#[test]
+fn the_simulation_halts_without_a_transition() {
+    let machine = parse(BINARY_INCREMENT);
+
+    let halt = machine.simulate("1011", 100).unwrap();
+
+    assert_eq!("1100", halt.tape());
+    assert_eq!("done", halt.state());
+    assert_eq!(8, halt.steps_done());
+
+    let halt = machine.simulate("", 100).unwrap();
+
+    assert_eq!("1", halt.tape());
+    assert_eq!(2, halt.steps_done());
+
+    assert_eq!("1000", machine.simulate("111", 100).unwrap().tape());
+}
+
+#[test]
+fn the_simulation_trims_the_blanks() {
+    let machine = parse("@tape a\nerase a -> erase _ R");
+
+    assert_eq!("", machine.simulate("aaa", 100).unwrap().tape());
+    assert_eq!("a", machine.simulate("_a_", 100).unwrap().tape());
+}
+
+#[test]
+fn the_simulation_is_limited() {
+    let machine = parse("@tape a\nloop _ -> loop _ L");
+
+    assert_eq!(
+        Err(TuringSimulationError::StepLimitExhausted(50)),
+        machine.simulate("", 50)
+    );
+    assert_eq!(
+        Err(TuringSimulationError::UnknownSymbol('b')),
+        parse(BINARY_INCREMENT).simulate("0b", 50)
+    );
+}
+
+#[test]
+fn the_compiled_machine_rewrites_the_input_into_the_result() {
+    let machine = parse(BINARY_INCREMENT);
+
+    assert_compiled_like_simulated(&machine, &["", "0", "1", "1011", "111", "0_1", "_"]);
+}
+
+#[test]
+fn the_compiled_machine_extends_the_tape_to_the_left() {
+    // Walks to the end and back, then writes two separators before the start.
+    let machine = parse(
+        "\
+@tape 1#
+right 1 -> right 1 R
+right _ -> back _ L
+back 1 -> back 1 L
+back _ -> done # L
+done _ -> stop # S
+",
+    );
+
+    assert_eq!("##11", machine.simulate("11", 100).unwrap().tape());
+
+    assert_compiled_like_simulated(&machine, &["", "1", "11", "1111", "#", "1_1"]);
+}
+
+#[test]
+fn the_compiled_machine_represents_the_states_with_unused_characters() {
+    let machine = parse("@tape AB→^$\\\nA A -> B B R\nB B -> A A R");
+    let compiled = machine.compile();
+
+    let first = compiled.state_character("A").unwrap();
+    let second = compiled.state_character("B").unwrap();
+
+    assert_eq!(('C', 'D'), (first, second));
+    assert_eq!(None, compiled.state_character("C"));
+
+    let mut iterator = compiled.scheme().get_application_iterator("AB").unwrap();
+
+    assert_eq!("CAB", iterator.next().unwrap().word());
+    assert_eq!("BDB", iterator.next().unwrap().word());
+
+    assert_compiled_like_simulated(&machine, &["AB→^$\\", "^A$", "\\A→", "BB"]);
+}
+
+#[test]
+fn the_compiled_machine_keeps_the_blanks_between_the_symbols() {
+    let machine = parse("@tape ab\nskip a -> skip _ R\nskip b -> skip b R");
+
+    assert_compiled_like_simulated(&machine, &["aba", "abab", "bab", "ab_ab", "__b_"]);
+}
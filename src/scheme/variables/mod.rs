@@ -0,0 +1,224 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! [FormulaVariables](FormulaVariables), the Refal-style variables of a substitution formula.
+
+#[cfg(test)]
+mod tests;
+
+use std::{mem, ops::Range};
+
+use super::{
+    encoding::WordEncoding,
+    pattern::{Pattern, PatternItem},
+    DefinitionCharacter, SchemeProperties, SubstitutionFormulaDefinitionError,
+};
+
+/// The variables of a formula and its sides split into the text and the occurrences of the variables.
+///
+/// A variable is written as its kind, the separator and the single character of its name:
+/// `s.x` stands for a single character and `e.x` stands for a segment of any length, even an empty one.
+/// The variables are bound on the left side and may be reused on the right side,
+/// a variable that occurs on the left side several times stands for the same characters each time.
+#[derive(Debug)]
+pub(super) struct FormulaVariables {
+    pub(super) kinds: Vec<VariableKind>,
+    pub(super) left: Vec<SidePart>,
+    pub(super) right: Vec<SidePart>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum VariableKind {
+    Symbol,
+    Segment,
+}
+
+/// A part of a side of a formula, the variables are numbered in the order they are bound.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum SidePart {
+    Text(String),
+    Variable(usize),
+}
+
+impl FormulaVariables {
+    /// The character that separates the kind of a variable from its name.
+    pub(super) const SEPARATOR: char = '.';
+
+    const KINDS: [(char, VariableKind); 2] =
+        [('s', VariableKind::Symbol), ('e', VariableKind::Segment)];
+
+    /// Finds the variables on the sides of the formula.
+    ///
+    /// # Returns
+    /// `None` if there are no variables, so the formula is an ordinary one.
+    pub(super) fn parse(
+        formula_definition: &str,
+        properties: &SchemeProperties,
+        left: &[DefinitionCharacter],
+        right: &[DefinitionCharacter],
+    ) -> Result<Option<Self>, SubstitutionFormulaDefinitionError> {
+        let mut parser = SideParser {
+            formula_definition,
+            properties,
+            names: Vec::new(),
+            kinds: Vec::new(),
+        };
+
+        let left = parser.parse(left, true)?;
+        let right = parser.parse(right, false)?;
+
+        Ok((!parser.kinds.is_empty()).then_some(Self {
+            kinds: parser.kinds,
+            left,
+            right,
+        }))
+    }
+
+    /// Translates the sides to a pattern over the units of the encoding.
+    pub(super) fn compile(&self, encoding: &WordEncoding) -> Pattern<u8> {
+        let compile_side = |side: &[SidePart]| {
+            side.iter()
+                .flat_map(|part| match part {
+                    SidePart::Text(text) => encoding
+                        .encode(text)
+                        .iter()
+                        .map(|&unit| PatternItem::Unit(unit))
+                        .collect(),
+                    &SidePart::Variable(variable) => vec![match self.kinds[variable] {
+                        VariableKind::Symbol => PatternItem::Symbol(variable),
+                        VariableKind::Segment => PatternItem::Segment(variable),
+                    }],
+                })
+                .collect()
+        };
+
+        Pattern::new(compile_side(&self.left), compile_side(&self.right))
+    }
+
+    /// The text of both sides, without the variables.
+    pub(super) fn text(&self) -> impl Iterator<Item = &str> {
+        self.left
+            .iter()
+            .chain(&self.right)
+            .filter_map(|part| match part {
+                SidePart::Text(text) => Some(text.as_str()),
+                SidePart::Variable(_) => None,
+            })
+    }
+}
+
+struct SideParser<'a> {
+    formula_definition: &'a str,
+    properties: &'a SchemeProperties,
+    names: Vec<char>,
+    kinds: Vec<VariableKind>,
+}
+
+impl<'a> SideParser<'a> {
+    /// Splits the side into parts, the unknown variables are bound if the side binds them and are errors otherwise.
+    fn parse(
+        &mut self,
+        side: &[DefinitionCharacter],
+        binds: bool,
+    ) -> Result<Vec<SidePart>, SubstitutionFormulaDefinitionError> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut index = 0;
+
+        while index < side.len() {
+            let character = &side[index];
+
+            let kind = FormulaVariables::KINDS
+                .iter()
+                .find(|&&(letter, _)| character.is(letter))
+                .map(|&(_, kind)| kind)
+                .filter(|_| {
+                    side.get(index + 1)
+                        .is_some_and(|separator| separator.is(FormulaVariables::SEPARATOR))
+                });
+
+            let Some(kind) = kind else {
+                if character.is(FormulaVariables::SEPARATOR) {
+                    return Err(self.invalid(side, index..index + 1));
+                }
+
+                text.push(character.value);
+                index += 1;
+                continue;
+            };
+
+            let name = match side.get(index + 2) {
+                Some(name) if !name.is_escaped && !self.properties.is_reserved(name.value) => {
+                    name.value
+                }
+                _ => return Err(self.invalid(side, index..index + 3)),
+            };
+
+            let variable = match self.names.iter().position(|&bound| bound == name) {
+                Some(variable) if self.kinds[variable] == kind => variable,
+                Some(_) => return Err(self.invalid(side, index..index + 3)),
+                None if binds => {
+                    self.names.push(name);
+                    self.kinds.push(kind);
+                    self.names.len() - 1
+                }
+                None => {
+                    return Err(SubstitutionFormulaDefinitionError::UnboundVariable(
+                        self.formula_definition.to_owned(),
+                        self.written(side, index..index + 3),
+                    ))
+                }
+            };
+
+            if !text.is_empty() {
+                parts.push(SidePart::Text(mem::take(&mut text)));
+            }
+
+            parts.push(SidePart::Variable(variable));
+            index += 3;
+        }
+
+        if !text.is_empty() {
+            parts.push(SidePart::Text(text));
+        }
+
+        Ok(parts)
+    }
+
+    fn invalid(
+        &self,
+        side: &[DefinitionCharacter],
+        range: Range<usize>,
+    ) -> SubstitutionFormulaDefinitionError {
+        SubstitutionFormulaDefinitionError::InvalidVariable(
+            self.formula_definition.to_owned(),
+            self.written(side, range),
+        )
+    }
+
+    /// The part of the definition the characters in the range are written with, cut at the end of the side.
+    fn written(&self, side: &[DefinitionCharacter], range: Range<usize>) -> String {
+        let start = side[range.start].position.start;
+        let end = side[..range.end.min(side.len())]
+            .last()
+            .map_or(start, |character| character.position.end);
+
+        self.formula_definition[start..end].to_owned()
+    }
+}
@@ -0,0 +1,127 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::scheme::{scheme_builder::AlgorithmSchemeBuilder, FormulaLexer};
+
+use super::*;
+
+fn parse(
+    left: &str,
+    right: &str,
+) -> Result<Option<FormulaVariables>, SubstitutionFormulaDefinitionError> {
+    let properties = AlgorithmSchemeBuilder::new()
+        .with_variables()
+        .with_escape_character('\\')
+        .finalize_properties();
+
+    let definition = format!("{left}→{right}");
+
+    let characters = FormulaLexer {
+        formula_definition: &definition,
+        properties: &properties,
+    }
+    .tokenize()
+    .unwrap();
+
+    let delimiter_index = characters
+        .iter()
+        .position(|character| character.is('→'))
+        .unwrap();
+
+    FormulaVariables::parse(
+        &definition,
+        &properties,
+        &characters[..delimiter_index],
+        &characters[delimiter_index + 1..],
+    )
+}
+
+#[test]
+fn the_formula_without_variables_is_an_ordinary_one() {
+    assert!(parse("ab", "se").unwrap().is_none());
+}
+
+#[test]
+fn the_sides_are_split_into_the_text_and_the_variables() {
+    let variables = parse("e.xas.1", "s.1e.xe.x").unwrap().unwrap();
+
+    assert_eq!(
+        vec![VariableKind::Segment, VariableKind::Symbol],
+        variables.kinds
+    );
+    assert_eq!(
+        vec![
+            SidePart::Variable(0),
+            SidePart::Text("a".to_owned()),
+            SidePart::Variable(1)
+        ],
+        variables.left
+    );
+    assert_eq!(
+        vec![
+            SidePart::Variable(1),
+            SidePart::Variable(0),
+            SidePart::Variable(0)
+        ],
+        variables.right
+    );
+    assert_eq!(vec!["a"], variables.text().collect::<Vec<_>>());
+}
+
+#[test]
+fn the_escaped_characters_do_not_form_variables() {
+    let variables = parse(r"\u{65}\.xe.x", r"s\.").unwrap().unwrap();
+
+    assert_eq!(
+        vec![SidePart::Text("e.x".to_owned()), SidePart::Variable(0)],
+        variables.left
+    );
+    assert_eq!(vec![SidePart::Text("s.".to_owned())], variables.right);
+}
+
+#[test]
+fn the_malformed_variables_are_reported() {
+    for (left, right, variable) in [
+        ("a.b", "", "."),
+        ("ae.", "", "e."),
+        (r"e.\→", "a", r"e.\→"),
+        ("e.xs.x", "", "s.x"),
+        ("e.x", "s.x", "s.x"),
+    ] {
+        assert_eq!(
+            Err(SubstitutionFormulaDefinitionError::InvalidVariable(
+                format!("{left}→{right}"),
+                variable.to_owned()
+            )),
+            parse(left, right).map(|_| ()),
+            "{left}→{right}"
+        );
+    }
+}
+
+#[test]
+fn the_variables_of_the_right_side_should_be_bound() {
+    assert_eq!(
+        Err(SubstitutionFormulaDefinitionError::UnboundVariable(
+            "e.x→e.ye.x".to_owned(),
+            "e.y".to_owned()
+        )),
+        parse("e.x", "e.ye.x").map(|_| ())
+    );
+}
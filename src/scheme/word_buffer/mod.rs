@@ -0,0 +1,263 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! [WordBuffer](WordBuffer), a reusable gap buffer that holds the word being processed.
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    fmt::{self, Debug, Display},
+    ops::Range,
+    str,
+};
+
+/// A unit of a word the engine works with, either a UTF-8 byte of a string or an interned symbol.
+pub(crate) trait Unit: Copy + Ord + Default + Debug + Send + Sync {
+    /// Reports whether the unit starts a new character, so the length of the word can be measured in characters.
+    fn starts_character(self) -> bool;
+
+    /// The unit as an index into the tables indexed by units.
+    fn index(self) -> usize;
+}
+
+impl Unit for u8 {
+    fn starts_character(self) -> bool {
+        // UTF-8 continuation bytes belong to the character started by a preceding byte.
+        self & 0b1100_0000 != 0b1000_0000
+    }
+
+    fn index(self) -> usize {
+        self.into()
+    }
+}
+
+impl Unit for u32 {
+    fn starts_character(self) -> bool {
+        true
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// A gap buffer that stores the units of a word and allows to rewrite it in place.
+///
+/// Consecutive substitutions that happen close to each other, like a marker walking across the word,
+/// only move a few units around the gap instead of copying the whole word.
+#[derive(Debug, Clone)]
+pub(crate) struct WordBuffer<U> {
+    storage: Vec<U>,
+    gap: Range<usize>,
+    characters: usize,
+}
+
+impl<U: Unit> WordBuffer<U> {
+    const MINIMAL_GAP: usize = 16;
+
+    /// Creates a buffer that contains the word.
+    pub(crate) fn new(word: &[U]) -> Self {
+        let mut storage = Vec::with_capacity(word.len() + Self::MINIMAL_GAP);
+        storage.extend_from_slice(word);
+        storage.resize(word.len() + Self::MINIMAL_GAP, U::default());
+
+        Self {
+            storage,
+            gap: word.len()..word.len() + Self::MINIMAL_GAP,
+            characters: Self::count_characters(word),
+        }
+    }
+
+    /// The length of the word in units.
+    pub(crate) fn len(&self) -> usize {
+        self.storage.len() - self.gap.len()
+    }
+
+    /// The length of the word in characters.
+    pub(crate) fn char_count(&self) -> usize {
+        self.characters
+    }
+
+    /// The number of characters that fall into the range of units.
+    pub(crate) fn char_count_in(&self, range: Range<usize>) -> usize {
+        Self::count_characters(self.units_in(range))
+    }
+
+    /// The unit at the position.
+    pub(crate) fn unit(&self, position: usize) -> U {
+        let (before, after) = self.as_slices();
+
+        match position.checked_sub(before.len()) {
+            Some(position) => after[position],
+            None => before[position],
+        }
+    }
+
+    /// The parts of the word before and after the gap.
+    pub(crate) fn as_slices(&self) -> (&[U], &[U]) {
+        (
+            &self.storage[..self.gap.start],
+            &self.storage[self.gap.end..],
+        )
+    }
+
+    /// Iterates over the units of the word.
+    pub(crate) fn units(&self) -> impl Iterator<Item = &U> {
+        self.units_in(0..self.len())
+    }
+
+    /// Iterates over the units of the word that fall into the range.
+    pub(crate) fn units_in(&self, range: Range<usize>) -> impl Iterator<Item = &U> {
+        debug_assert!(range.start <= range.end && range.end <= self.len());
+
+        let (before, after) = self.as_slices();
+        let split = before.len();
+
+        let head = &before[range.start.min(split)..range.end.min(split)];
+        let tail = &after[range.start.max(split) - split..range.end.max(split) - split];
+
+        head.iter().chain(tail)
+    }
+
+    /// Copies the word out of the buffer.
+    pub(crate) fn to_vec(&self) -> Vec<U> {
+        let (before, after) = self.as_slices();
+
+        [before, after].concat()
+    }
+
+    /// Replaces a range of units with the replacement, reusing the allocated storage when possible.
+    pub(crate) fn replace(&mut self, range: Range<usize>, replacement: &[U]) {
+        debug_assert!(range.start <= range.end && range.end <= self.len());
+
+        self.characters -= Self::count_characters(self.units_in(range.clone()));
+        self.characters += Self::count_characters(replacement);
+
+        self.move_gap(range.end);
+        self.gap.start = range.start;
+
+        self.reserve_gap(replacement.len());
+
+        let start = self.gap.start;
+        self.storage[start..start + replacement.len()].copy_from_slice(replacement);
+        self.gap.start += replacement.len();
+    }
+
+    fn count_characters<'a, I>(units: I) -> usize
+    where
+        I: IntoIterator<Item = &'a U>,
+        U: 'a,
+    {
+        units
+            .into_iter()
+            .filter(|&&unit| unit.starts_character())
+            .count()
+    }
+
+    fn move_gap(&mut self, position: usize) {
+        if position < self.gap.start {
+            let count = self.gap.start - position;
+
+            self.storage
+                .copy_within(position..self.gap.start, self.gap.end - count);
+            self.gap = position..self.gap.end - count;
+        } else if position > self.gap.start {
+            let count = position - self.gap.start;
+
+            self.storage
+                .copy_within(self.gap.end..self.gap.end + count, self.gap.start);
+            self.gap = self.gap.start + count..self.gap.end + count;
+        }
+    }
+
+    fn reserve_gap(&mut self, required: usize) {
+        if self.gap.len() >= required {
+            return;
+        }
+
+        let length = self.len();
+        let gap_length = (length + required)
+            .max(2 * self.storage.len())
+            .saturating_sub(length)
+            .max(Self::MINIMAL_GAP);
+
+        let mut storage = Vec::with_capacity(length + gap_length);
+        storage.extend_from_slice(&self.storage[..self.gap.start]);
+        storage.resize(self.gap.start + gap_length, U::default());
+        storage.extend_from_slice(&self.storage[self.gap.end..]);
+
+        self.storage = storage;
+        self.gap = self.gap.start..self.gap.start + gap_length;
+    }
+}
+
+impl WordBuffer<u8> {
+    /// The parts of the word before and after the gap as string slices.
+    ///
+    /// The gap is only ever moved to the boundaries of substituted ranges,
+    /// so it never splits a character.
+    pub(crate) fn as_str_slices(&self) -> (&str, &str) {
+        let (before, after) = self.as_slices();
+
+        (
+            str::from_utf8(before).expect("The gap never splits a character."),
+            str::from_utf8(after).expect("The gap never splits a character."),
+        )
+    }
+}
+
+impl From<&str> for WordBuffer<u8> {
+    fn from(word: &str) -> Self {
+        Self::new(word.as_bytes())
+    }
+}
+
+impl Display for WordBuffer<u8> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (before, after) = self.as_str_slices();
+
+        formatter.write_str(before)?;
+        formatter.write_str(after)
+    }
+}
+
+impl<U: Unit> PartialEq for WordBuffer<U> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.units().eq(other.units())
+    }
+}
+
+impl<U: Unit> Eq for WordBuffer<U> {}
+
+impl<U: Unit> PartialEq<[U]> for WordBuffer<U> {
+    fn eq(&self, other: &[U]) -> bool {
+        let (before, after) = self.as_slices();
+
+        self.len() == other.len()
+            && other[..before.len()] == *before
+            && other[before.len()..] == *after
+    }
+}
+
+impl PartialEq<str> for WordBuffer<u8> {
+    fn eq(&self, other: &str) -> bool {
+        *self == *other.as_bytes()
+    }
+}
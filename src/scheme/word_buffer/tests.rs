@@ -0,0 +1,137 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use rand::Rng;
+
+use super::*;
+
+#[test]
+fn a_buffer_contains_the_initial_word() {
+    let buffer = WordBuffer::from("abc");
+
+    assert_eq!("abc", buffer.to_string());
+    assert_eq!(3, buffer.len());
+}
+
+#[test]
+fn a_range_can_be_replaced_with_a_longer_replacement() {
+    let mut buffer = WordBuffer::from("abc");
+
+    buffer.replace(1..2, "ぬぬ".as_bytes());
+
+    assert_eq!("aぬぬc", buffer.to_string());
+    assert_eq!(8, buffer.len());
+    assert_eq!(4, buffer.char_count());
+}
+
+#[test]
+fn a_range_can_be_replaced_with_a_shorter_replacement() {
+    let mut buffer = WordBuffer::from("abcd");
+
+    buffer.replace(1..3, b"");
+
+    assert_eq!("ad", buffer.to_string());
+}
+
+#[test]
+fn an_empty_range_can_be_replaced_to_insert_text() {
+    let mut buffer = WordBuffer::from("");
+
+    buffer.replace(0..0, b"ab");
+    buffer.replace(2..2, b"c");
+    buffer.replace(0..0, b"d");
+
+    assert_eq!("dabc", buffer.to_string());
+}
+
+#[test]
+fn the_buffer_grows_beyond_the_initial_gap() {
+    let mut buffer = WordBuffer::from("a");
+    let long = "b".repeat(1_000);
+
+    buffer.replace(0..1, long.as_bytes());
+    buffer.replace(500..500, b"c");
+
+    assert_eq!(
+        format!("{}c{}", &long[..500], &long[500..]),
+        buffer.to_string()
+    );
+}
+
+#[test]
+fn the_units_of_the_word_can_be_iterated_over_regardless_of_the_gap() {
+    let mut buffer = WordBuffer::from("abcd");
+
+    buffer.replace(1..2, b"x");
+
+    assert_eq!(
+        b"axcd".to_vec(),
+        buffer.units().cloned().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn the_buffer_agrees_with_a_string_on_random_replacements() {
+    let mut generator = rand::thread_rng();
+    let mut expected = "abcdef".to_owned();
+    let mut buffer = WordBuffer::from(expected.as_str());
+
+    for _ in 0..10_000 {
+        let start = generator.gen_range(0..=expected.len());
+        let end = generator.gen_range(start..=expected.len().min(start + 3));
+        let replacement = ["", "x", "yz", "uvw"][generator.gen_range(0..4)];
+
+        expected.replace_range(start..end, replacement);
+        buffer.replace(start..end, replacement.as_bytes());
+
+        assert_eq!(expected, buffer.to_string());
+        assert_eq!(expected.chars().count(), buffer.char_count());
+    }
+}
+
+#[test]
+fn the_units_in_a_range_can_be_iterated_over_regardless_of_the_gap() {
+    let mut buffer = WordBuffer::from("abcdef");
+
+    buffer.replace(2..3, b"x");
+
+    assert_eq!(
+        b"bx".to_vec(),
+        buffer.units_in(1..3).cloned().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        b"xde".to_vec(),
+        buffer.units_in(2..5).cloned().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        b"ef".to_vec(),
+        buffer.units_in(4..6).cloned().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn a_buffer_of_interned_symbols_counts_each_symbol_as_a_character() {
+    let mut buffer = WordBuffer::new(&[1_u32, 200, 70_000]);
+
+    buffer.replace(1..2, &[5, 5, 5]);
+
+    assert_eq!(vec![1, 5, 5, 5, 70_000], buffer.to_vec());
+    assert_eq!(5, buffer.len());
+    assert_eq!(5, buffer.char_count());
+}
@@ -21,22 +21,26 @@ mod input;
 
 use std::{fs::File, io::Read, path::PathBuf};
 
-use anyhow::{Context, Ok, Result};
+use anyhow::{bail, Context, Ok, Result};
 use clap::{ArgGroup, Parser};
 
 use markovalgorithms::prelude::*;
 
-use crate::input::UserInputHandler;
+use crate::input::{UserCommand, UserInputHandler};
 
 fn main() -> Result<()> {
     let parsed = Cli::parse();
 
-    let builder = parsed.create_builder()?;
+    let scheme_file: SchemeFile = parsed
+        .read_scheme()?
+        .parse()
+        .with_context(|| "Failed to parse the algorithm scheme file")?;
 
-    let scheme_definition = parsed.read_scheme()?;
+    let builder = parsed.configure_builder(scheme_file.builder().clone())?;
 
-    let scheme = builder
-        .build_with_formula_definitions(scheme_definition.lines())
+    let scheme = scheme_file
+        .with_builder(builder)
+        .build()
         .with_context(|| "Failed to create the algorithm scheme")?;
 
     if parsed.interactive {
@@ -90,15 +94,52 @@ struct Cli {
     )]
     alphabet_extension: Option<String>,
 
-    /// An optional character to be used as a delimiter.
-    #[clap(short, long, value_parser, value_name = "CHARACTER", display_order = 3)]
-    delimiter: Option<char>,
-
-    /// An optional character to be used as a final marker.
-    #[clap(short, long, value_parser, value_name = "CHARACTER", display_order = 4)]
-    final_marker: Option<char>,
+    /// An optional string to be used as a delimiter.
+    #[clap(
+        short,
+        long,
+        value_parser,
+        value_name = "STRING",
+        allow_hyphen_values = true,
+        display_order = 3
+    )]
+    delimiter: Option<String>,
 
-    /// The UTF-8 file that contains the algorithm scheme. Each rule should take its own line. Empty lines are forbidden.
+    /// An optional string to be used as a final marker.
+    #[clap(
+        short,
+        long,
+        value_parser,
+        value_name = "STRING",
+        allow_hyphen_values = true,
+        display_order = 4
+    )]
+    final_marker: Option<String>,
+
+    /// An optional character that starts escape sequences in the scheme, like "\→", "\n" or "\u{2192}".
+    /// Escaped delimiter, final marker and new lines can be used as characters of the alphabet.
+    #[clap(long, value_parser, value_name = "CHARACTER", display_order = 5)]
+    escape: Option<char>,
+
+    /// Optional opening and closing brackets that enable the rule templates, like "_[x:abc]→x_",
+    /// which stands for a rule per character of the class.
+    #[clap(long, value_parser, value_name = "BRACKETS", display_order = 6)]
+    templates: Option<String>,
+
+    /// When set, enables the variables in the rules, "s.x" stands for a single character
+    /// and "e.x" stands for a segment of any length, like in "e.x*e.y#→⋅e.y*e.x".
+    #[clap(long, action, display_order = 7)]
+    variables: bool,
+
+    /// When set, enables the anchors in the rules, a left side that starts with "^" only matches
+    /// at the start of the word and a left side that ends with "$" only matches at its end.
+    #[clap(long, action, display_order = 8)]
+    anchors: bool,
+
+    /// The UTF-8 file that contains the algorithm scheme. Each rule should take its own line.
+    /// Blank lines and the lines starting with '#', possibly after whitespace, are ignored.
+    /// The header may contain the directives "@alphabet", "@extension", "@delimiter", "@final", "@escape",
+    /// "@templates", "@variables", "@anchors" and "@groups", each followed by its value, the command line options take precedence over them.
     #[clap(
         short,
         long,
@@ -113,25 +154,50 @@ struct Cli {
     string: String,
 
     /// When set, defines the limit of steps the algorithm is allowed to take.
-    #[clap(short, long, value_parser = clap::value_parser!(u32).range(1..), value_name = "NUMBER-OF-STEPS", display_order = 5)]
+    #[clap(short, long, value_parser = clap::value_parser!(u32).range(1..), value_name = "NUMBER-OF-STEPS", display_order = 9)]
     limit: Option<u32>,
 
     /// When set, enables interactive iteration through algorithm steps.
-    #[clap(short, long, action, display_order = 6)]
+    #[clap(short, long, action, display_order = 10)]
     interactive: bool,
 }
 
 impl Cli {
-    fn create_builder(&self) -> Result<AlgorithmSchemeBuilder> {
-        let builder = AlgorithmSchemeBuilder::default();
-
-        let builder = if let Some(delimiter) = self.delimiter {
-            builder.with_delimiter(delimiter)
+    fn configure_builder(&self, builder: AlgorithmSchemeBuilder) -> Result<AlgorithmSchemeBuilder> {
+        let builder = if let Some(delimiter) = &self.delimiter {
+            builder.with_delimiter(delimiter.as_str())
+        } else {
+            builder
+        };
+        let builder = if let Some(final_marker) = &self.final_marker {
+            builder.with_final_marker(final_marker.as_str())
+        } else {
+            builder
+        };
+        let builder = if let Some(escape) = self.escape {
+            builder.with_escape_character(escape)
+        } else {
+            builder
+        };
+        let builder = if let Some(templates) = &self.templates {
+            let mut brackets = templates.chars();
+
+            match (brackets.next(), brackets.next(), brackets.next()) {
+                (Some(open), Some(close), None) => builder.with_template_brackets(open, close),
+                _ => bail!(
+                    "Expected the opening and the closing template brackets, found \"{templates}\""
+                ),
+            }
+        } else {
+            builder
+        };
+        let builder = if self.variables {
+            builder.with_variables()
         } else {
             builder
         };
-        let builder = if let Some(final_marker) = self.final_marker {
-            builder.with_final_marker(final_marker)
+        let builder = if self.anchors {
+            builder.with_anchors()
         } else {
             builder
         };
@@ -156,10 +222,7 @@ impl Cli {
             let alphabet = if let Some(extension) = &self.alphabet_extension {
                 extension
                     .chars()
-                    .fold(Ok(alphabet), |result, character| match result {
-                        Ok(alphabet) => alphabet.extend(character),
-                        error => error,
-                    })
+                    .try_fold(alphabet, |alphabet, character| alphabet.extend(character))
                     .with_context(|| "Failed to extend the alphabet definition")?
             } else {
                 alphabet
@@ -210,18 +273,15 @@ fn apply_scheme(scheme: &AlgorithmScheme, word: &str, limit: u32) -> Result<()>
 fn iterate_over_scheme_results(scheme: &AlgorithmScheme, word: &str) -> Result<()> {
     let mut old_word = word.to_owned();
 
-    let iterator = scheme
-        .get_application_iterator(word)
+    let mut iterator = scheme
+        .get_reversible_application_iterator(word, ExecutionLimits::new())
         .with_context(|| "Failed to apply the algorithm scheme to the input")?;
 
-    let mut final_step = 0;
     let mut input_handler = UserInputHandler::setup()?;
 
-    for (step, result) in iterator.enumerate() {
-        final_step = step
-            .checked_add(1)
-            .with_context(|| "Too many steps taken")?;
+    let mut next_result = iterator.next();
 
+    while let Some(result) = next_result {
         let new_word = result.word();
 
         if let Some(forumula_definition) = result.applied_formula_definition() {
@@ -231,20 +291,39 @@ fn iterate_over_scheme_results(scheme: &AlgorithmScheme, word: &str) -> Result<(
             );
 
             old_word = new_word.to_owned();
-
-            if !input_handler.should_continue()? {
-                println!("Stopping due to the received Ctrl-C signal.");
-
-                return Ok(());
-            }
         } else {
             println!("No transformaion was made, no rules were applied.");
             break;
         }
+
+        next_result = loop {
+            match input_handler.next_command()? {
+                UserCommand::Continue => break iterator.next(),
+                UserCommand::StepBack => {
+                    if let Some(previous_result) = iterator.step_back() {
+                        old_word = previous_result.word().to_owned();
+
+                        println!(
+                            "Returned to the word \"{old_word}\" after taking {} steps.",
+                            iterator.steps_done()
+                        );
+                    } else {
+                        println!("There are no steps to revert.");
+                    }
+                }
+                UserCommand::Stop => {
+                    println!("Stopping due to the received Ctrl-C signal.");
+
+                    return Ok(());
+                }
+            }
+        };
     }
 
     println!(
-        "The algorithm is finished after taking {final_step} steps. The output string is \"{old_word}\".");
+        "The algorithm is finished after taking {} steps. The output string is \"{old_word}\".",
+        iterator.steps_done()
+    );
 
     Ok(())
 }
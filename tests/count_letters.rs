@@ -129,7 +129,6 @@ fn prepare_scheme() -> AlgorithmScheme {
     let set: HashSet<_> = ['a', 'b', 'c'].into_iter().collect();
 
     let alphabet = ('0'..='9')
-        .into_iter()
         .fold(
             Alphabet::try_from(&set).unwrap(),
             |accumulator, character| accumulator.extend(character).unwrap(),
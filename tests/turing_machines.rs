@@ -0,0 +1,134 @@
+/*
+*    markov-algorithms — Rust implementation of Markov Algorithms.
+*
+*    Copyright (C) 2022 by Sergey Ivanov <quixoticaxisgit@gmail.com, quixoticaxisgit@mail.ru>
+*
+*    This program is free software: you can redistribute it and/or modify
+*    it under the terms of the GNU General Public License as published by
+*    the Free Software Foundation, either version 3 of the License, or
+*    (at your option) any later version.
+*
+*    This program is distributed in the hope that it will be useful,
+*    but WITHOUT ANY WARRANTY; without even the implied warranty of
+*    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*    GNU General Public License for more details.
+*
+*    You should have received a copy of the GNU General Public License
+*    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::iter;
+
+use rand::{seq::SliceRandom, Rng};
+
+use markovalgorithms::prelude::*;
+
+/// A machine that multiplies the unary numbers separated by `*`, writing the product after `=`.
+///
+/// The digits of the first factor are marked with `x` one by one,
+/// and for each of them the second factor is copied to the end of the tape with its digits marked by `y`.
+/// Then the second factor and the separators are erased, the marked first factor stays before the product.
+const UNARY_MULTIPLICATION: &str = "\
+# Multiplies the unary numbers, \"11*111\" becomes \"xx_____111111\".
+@tape 1*=xy
+@start mark
+
+mark 1 -> seek x R
+mark * -> clean _ R
+seek 1 -> seek 1 R
+seek * -> copy * R
+copy y -> copy y R
+copy 1 -> carry y R
+copy = -> rewind = L
+copy _ -> rewind = L
+carry 1 -> carry 1 R
+carry = -> append = R
+carry _ -> append = R
+append 1 -> append 1 R
+append _ -> return 1 L
+return 1 -> return 1 L
+return = -> return = L
+return y -> copy y R
+rewind y -> rewind 1 L
+rewind 1 -> rewind 1 L
+rewind * -> rewind * L
+rewind x -> mark x R
+clean 1 -> clean _ R
+clean = -> done _ R
+clean _ -> done _ R
+";
+
+#[test]
+fn the_compiled_multiplication_agrees_with_the_machine() {
+    let machine: TuringMachine = UNARY_MULTIPLICATION.parse().unwrap();
+    let scheme = machine.compile().into_scheme();
+
+    for (first, second) in [(0, 0), (0, 3), (2, 0), (1, 1), (2, 3), (4, 4)] {
+        let input = format!("{}*{}", "1".repeat(first), "1".repeat(second));
+        let product = "1".repeat(first * second);
+
+        let halt = machine.simulate(&input, 100_000).unwrap();
+
+        assert_eq!(
+            format!("{}{product}", "x".repeat(first)),
+            halt.tape().replace('_', "")
+        );
+
+        let result = scheme.apply(&input, 1_000_000).unwrap();
+
+        assert_eq!(halt.tape(), result.word());
+    }
+}
+
+#[test]
+fn the_compiled_random_machines_agree_with_the_simulation() {
+    let mut generator = rand::thread_rng();
+    let symbols = ['a', 'b', '_'];
+    let movements = ["L", "R", "S"];
+
+    for _ in 0..200 {
+        let states = generator.gen_range(1..=4);
+
+        let mut description = String::from("@tape ab\n");
+
+        for state in 0..states {
+            for symbol in symbols {
+                if generator.gen_bool(0.2) {
+                    continue;
+                }
+
+                description.push_str(&format!(
+                    "q{state} {symbol} -> q{} {} {}\n",
+                    generator.gen_range(0..states),
+                    symbols.choose(&mut generator).unwrap(),
+                    movements.choose(&mut generator).unwrap(),
+                ));
+            }
+        }
+
+        let Ok(machine) = description.parse::<TuringMachine>() else {
+            // Every transition has been skipped.
+            continue;
+        };
+        let scheme = machine.compile().into_scheme();
+
+        for _ in 0..5 {
+            let length = generator.gen_range(0..8);
+            let input: String = iter::repeat_with(|| *symbols.choose(&mut generator).unwrap())
+                .take(length)
+                .collect();
+
+            let Ok(halt) = machine.simulate(&input, 200) else {
+                continue;
+            };
+
+            let result = scheme.apply(&input, 100_000).unwrap();
+
+            assert_eq!(
+                halt.tape(),
+                result.word(),
+                "the machine\n{description}on the input \"{input}\""
+            );
+        }
+    }
+}